@@ -18,10 +18,13 @@ use crate::strahl::tonemap::*;
 use crate::strahl::texture::*;
 //use crate::strahl::as3dcamera::*;
 use crate::strahl::quaternion::*;
+use crate::strahl::settings::*;
+use crate::strahl::integrator::*;
 
 use image::{GenericImageView, ImageBuffer, imageops};
 use rayon::prelude::*;
 use std::time::{Duration, SystemTime};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 #[cfg(debug_assertions)]
@@ -34,94 +37,13 @@ fn debug_divisior() -> u32 {
     1
 }
 
-const MAX_DEPTH : usize = 10;
 const MULTTHREADING: bool = true;
 
-pub struct RayInfo
-{
-    pub depth: u32,
-    pub ray: Ray,
-    pub mat_info: [MaterialInfo; MAX_DEPTH]
-}
-
 type ScanLine = std::vec::Vec<Vec4>;
 type TraceOutput = std::vec::Vec<ScanLine>;
 
-impl RayInfo
-{
-    #[inline]
-    pub fn new() -> RayInfo
-    {
-        RayInfo{ray: Ray::invalid(), mat_info: [MaterialInfo::new(); MAX_DEPTH], depth: 0}
-    }
-
-    #[inline]
-    pub fn add_mat(&mut self, mat: &MaterialInfo)
-    {
-        if self.depth < MAX_DEPTH as u32
-        {
-            self.mat_info[self.depth as usize] = *mat;
-            self.depth += 1;
-        }
-    }
-
-    #[inline]
-    pub fn accumulate(&self) -> Vec4
-    {
-        if self.depth == 0 { return Vec4::zero();}
-
-        let mut col = self.mat_info[(self.depth - 1) as usize].emission;
-
-        for i in 2..self.depth+1
-        {
-            let cur = self.mat_info[(self.depth - i) as usize];
-            col *= cur.attenuation;
-            col += cur.emission;
-        }
-
-        col
-    }
-
-    #[inline]
-    pub fn reset(&mut self, ray: &Ray)
-    {
-        self.ray = *ray;
-        self.depth = 0;
-    }
-}
-
-// return true if terminated
-#[inline]
-pub fn trace(r: &mut RayInfo, scn: &Scene, normal: bool) -> bool
-{
-    let mut hit = HitInfo::new();
-    let mut mat_info = MaterialInfo::new();
-
-    if scn.hit(&r.ray, &mut hit, 0.0, 100.0)
-    {
-        if !normal
-        {            
-            let scattered = scn.get_mat(hit.material).scatter(&mut r.ray, &hit, &mut mat_info);
-            r.add_mat(&mat_info);
-            return !scattered;   
-        }
-        else
-        {
-            mat_info.attenuation = (hit.normal + 1.0) * 0.5; 
-            r.add_mat(&mat_info);
-            return true; // terminated
-        }
-    }
-    else // missed / escaped scene
-    {
-        scn.get_miss_mat().scatter(&mut r.ray, &hit, &mut mat_info);
-        r.add_mat(&mat_info);
-        return true;  // terminated
-    }
-}
-
 #[inline]
-pub fn color(scn: &Scene, cam: &Camera, x: u32, y: u32, ray_info: &mut RayInfo, ray_count: &mut u32) -> Vec4
+pub fn color(scn: &Scene, cam: &Camera, integrator: &dyn Integrator, x: u32, y: u32, ray_info: &mut RayInfo, ray_count: &mut u32) -> Vec4
 {
     let mut rnd_ray = |channel: Channel| -> Vec4{
         let mut col = Vec4::zero();
@@ -132,13 +54,7 @@ pub fn color(scn: &Scene, cam: &Camera, x: u32, y: u32, ray_info: &mut RayInfo,
             let v = y as f32 + t * 0.5;
             ray_info.reset(&cam.get_ray(u, v, channel));
 
-            for _ in 0..MAX_DEPTH {
-                if trace(ray_info, &scn, false) {
-                    break;
-                }
-            }
-
-            col += ray_info.accumulate();
+            col += integrator.li(&scn, cam, ray_info);
             *ray_count += ray_info.depth;
         }
 
@@ -155,22 +71,25 @@ pub fn color(scn: &Scene, cam: &Camera, x: u32, y: u32, ray_info: &mut RayInfo,
     }
 }
 
-pub fn trace_image(cam: &Camera, scn: &Scene, print_progress: bool) -> TraceOutput
+pub fn trace_image(cam: &Camera, scn: &Scene, integrator: &dyn Integrator, print_progress: bool) -> TraceOutput
 {
     let ray_count = AtomicU32::new(0);
     let line_count = AtomicU32::new(0);
 
+    let film = Arc::new(Film::new(cam.width(), cam.height()));
+
     let trace_scan_line = |y: u32| -> ScanLine
     {
         let mut scan_line = ScanLine::with_capacity(cam.width() as usize);
 
         let scan_time = SystemTime::now();
-        let mut ray = RayInfo::new();        
+        let mut ray = RayInfo::new();
+        ray.film = Some(film.clone());
 
         let mut local_ray_count = 0;
         for x in 0..cam.width()
         {
-            scan_line.push(color(&scn, cam, x, y, &mut ray, &mut local_ray_count));        
+            scan_line.push(color(&scn, cam, integrator, x, y, &mut ray, &mut local_ray_count));
         }
 
         let cur_ray_count = ray_count.fetch_add(local_ray_count, Ordering::SeqCst);
@@ -204,6 +123,16 @@ pub fn trace_image(cam: &Camera, scn: &Scene, print_progress: bool) -> TraceOutp
         }
     }
 
+    // splats are estimates of the whole image per camera sample
+    let splat_scale = 1.0 / cam.sample_count() as f32;
+    for (y, scan_line) in scanlines.iter_mut().enumerate()
+    {
+        for (x, pixel) in scan_line.iter_mut().enumerate()
+        {
+            *pixel += film.get(x as u32, y as u32) * splat_scale;
+        }
+    }
+
     let elapsed = total_time.elapsed().unwrap();
     //// TRACING ////
 
@@ -218,10 +147,13 @@ pub fn trace_image(cam: &Camera, scn: &Scene, print_progress: bool) -> TraceOutp
 
 fn main() {
 
+    let settings = TraceSettings::from_args(std::env::args().skip(1));
+
     println!("loading scene...");
 
     let mut world = Scene::new();
-    //world.set_envmap("Ocean.jpg", Vec4::from(2.0), DynamicTextureType::sRGB);
+    //world.set_envmap("Ocean.jpg", 2.0, Quat::new(), DynamicTextureType::sRGB);
+    //world.set_sky(PreethamSky::new(Vec4::from3(0.3, 0.5, 0.2), 3.0, Vec4::from(0.3)).model(), 0.1);
 
     let earth = world.add_mat(Lambertian::from_path("earth.jpg", DynamicTextureType::sRGB));
 
@@ -265,7 +197,10 @@ fn main() {
     let cam = PerspectiveCamera::new(origin, target, up, 60.0, width, height, 0.0, 100.0, samples);
     //let cam = AS3DCamera::new(origin, target, up, 60.0, width, height, 100.0, samples, 0.1, 8, 2.0, 3.0);
 
-    let scanlines = trace_image(&cam, &world, false);
+    let mut integrator = create_integrator(&settings);
+    integrator.preprocess(&world);
+
+    let scanlines = trace_image(&cam, &world, integrator.as_ref(), false);
 
     let mut imgbuf = image::ImageBuffer::new(cam.width(), cam.height());
 
@@ -65,11 +65,7 @@ impl Camera for AS3DCamera
 
         let view = ((x * 3.0 + y * self.lenticular_slope + 4.0) + (channel as u32) as f32) as usize % self.pos.len();
 
-        Ray
-        {
-            origin: self.pos[view],
-            direction: self.lower_left_corner[view] + s*self.horizontal[view] + t*self.vertical[view] - self.pos[view]
-        }
+        Ray::new(self.pos[view], self.lower_left_corner[view] + s*self.horizontal[view] + t*self.vertical[view] - self.pos[view])
     }
 
     fn mode(&self ) -> Mode { Mode::Separate }
@@ -0,0 +1,691 @@
+use super::vec::*;
+use super::ray::*;
+use super::hit::*;
+use super::scene::*;
+use super::bsdf::*;
+use super::settings::*;
+use super::camera::Camera;
+use super::primitives::SampleSurface;
+use super::integrator::*;
+use super::light::Emitter;
+
+use std::f32::consts::PI;
+
+// https://www.pbr-book.org/3ed-2018/Light_Transport_III_Bidirectional_Methods/Bidirectional_Path_Tracing
+
+#[derive(PartialEq, Copy, Clone)]
+enum VertexType
+{
+    Camera,
+    Light,
+    Surface
+}
+
+#[derive(Copy, Clone)]
+struct Vertex
+{
+    kind: VertexType,
+    hit: HitInfo,
+    wo: Vec4, // towards the previous vertex of the subpath
+    beta: Vec4,
+    pdf_fwd: f32, // area density of generating this vertex from the previous one
+    pdf_rev: f32, // area density of generating this vertex from the next one
+    delta: bool,
+    from_light: bool,
+    light: Option<u32> // analytic light the vertex lies on, emissive objects are found through hit.object
+}
+
+impl Vertex
+{
+    fn camera(pos: &Vec4, _beta: Vec4) -> Vertex
+    {
+        let mut hit = HitInfo::new();
+        hit.point = *pos;
+        Vertex{kind: VertexType::Camera, hit: hit, wo: Vec4::zero(), beta: _beta, pdf_fwd: 0.0, pdf_rev: 0.0, delta: false, from_light: false, light: None}
+    }
+
+    // pdf: area density of choosing this point on the light, including the light selection
+    fn light(_hit: &HitInfo, le: Vec4, pdf: f32, _light: Option<u32>) -> Vertex
+    {
+        Vertex{kind: VertexType::Light, hit: *_hit, wo: Vec4::zero(), beta: le / pdf, pdf_fwd: pdf, pdf_rev: 0.0, delta: false, from_light: true, light: _light}
+    }
+
+    fn surface(_hit: &HitInfo, _wo: Vec4, _beta: Vec4, _from_light: bool) -> Vertex
+    {
+        Vertex{kind: VertexType::Surface, hit: *_hit, wo: _wo, beta: _beta, pdf_fwd: 0.0, pdf_rev: 0.0, delta: false, from_light: _from_light, light: None}
+    }
+
+    // |cos| at this vertex towards dir, points without a surface (the pinhole, point lights) don't have one
+    fn cos(&self, dir: &Vec4) -> f32
+    {
+        if self.kind == VertexType::Camera || self.hit.normal == Vec4::zero() { 1.0 } else { self.hit.normal.dot(dir).abs() }
+    }
+
+    // solid angle density from this vertex to an area density at next
+    fn convert_density(&self, pdf_dir: f32, next: &Vertex) -> f32
+    {
+        let to_next = next.hit.point - self.hit.point;
+        let dist2 = to_next.square_length();
+        if dist2 == 0.0 { return 0.0; }
+
+        pdf_dir * next.cos(&(to_next / dist2.sqrt())) / dist2
+    }
+
+    // density of the light emitting towards next, cosine weighted and split between both sides of two sided objects
+    fn pdf_light(&self, scn: &Scene, next: &Vertex) -> f32
+    {
+        let dir = (next.hit.point - self.hit.point).norm();
+
+        let pdf_dir = match self.light
+        {
+            Some(light) => scn.get_light(light).pdf_le(&dir).1,
+            None =>
+            {
+                let cos = self.hit.normal.dot(&dir);
+                if scn.get_object(self.hit.object).two_sided() { cos.abs() / (2.0 * PI) } else { cos.max(0.0) / PI }
+            }
+        };
+
+        self.convert_density(pdf_dir, next)
+    }
+
+    // area density of sampling next from this vertex, given it was reached from prev
+    fn pdf(&self, scn: &Scene, cam: &dyn Camera, prev: Option<&Vertex>, next: &Vertex) -> f32
+    {
+        let dir = (next.hit.point - self.hit.point).norm();
+
+        let pdf_dir = match self.kind
+        {
+            VertexType::Light => return self.pdf_light(scn, next),
+            VertexType::Camera => cam.importance(&dir).1,
+            VertexType::Surface =>
+            {
+                let wo = match prev
+                {
+                    Some(p) => (p.hit.point - self.hit.point).norm(),
+                    None => return 0.0
+                };
+
+                scn.get_mat(self.hit.material).pdf(&dir, &wo, &self.hit)
+            }
+        };
+
+        self.convert_density(pdf_dir, next)
+    }
+
+    // bsdf for light flowing along the full path through this vertex, dir points to the other subpath
+    fn f(&self, scn: &Scene, dir: &Vec4) -> Vec4
+    {
+        let mat = scn.get_mat(self.hit.material);
+        if self.from_light { mat.eval(&self.wo, dir, &self.hit) } else { mat.eval(dir, &self.wo, &self.hit) }
+    }
+}
+
+// light subpaths start on any emitter but infinite lights, those are found by escaping camera paths and s == 1
+// camera paths don't intersect analytic lights, they only see them directly or through delta bounces
+#[derive(Copy, Clone)]
+pub struct BidirectionalPathTracer
+{
+    pub max_depth: u32
+}
+
+impl BidirectionalPathTracer
+{
+    pub fn new(_settings: &TraceSettings) -> BidirectionalPathTracer
+    {
+        BidirectionalPathTracer{max_depth: _settings.bdpt_max_depth}
+    }
+
+    // extends path from its last vertex, returns radiance camera paths find without connections
+    fn random_walk(&self, scn: &Scene, r: &mut RayInfo, ray: Ray, beta: Vec4, pdf_dir: f32, from_light: bool, max_vertices: usize, path: &mut std::vec::Vec<Vertex>) -> Vec4
+    {
+        let mut ray = ray;
+        let mut beta = beta;
+        let mut pdf_fwd = pdf_dir;
+        let mut l = Vec4::zero();
+
+        while path.len() < max_vertices
+        {
+            let mut hit = HitInfo::new();
+            r.depth += 1;
+
+            let found = scn.hit(&ray, &mut hit, 0.0, MAX_DISTANCE);
+
+            // analytic lights seen from the camera or through a delta bounce, no connection can reach them
+            if !from_light && (path.len() == 1 || path[path.len() - 1].delta)
+            {
+                let max = if found { hit.depth } else { std::f32::INFINITY };
+                l += beta * scn.light_emission(&ray, max, &ray.origin, path.len() == 1, |light, _| if light.is_infinite() { 0.0 } else { 1.0 });
+            }
+
+            if !found
+            {
+                // the miss material is not part of the light set, infinite lights are also found by the s == 1 strategy
+                if !from_light
+                {
+                    let direct = path.len() == 1 || path[path.len() - 1].delta;
+                    let env = scn.light_emission(&ray, std::f32::INFINITY, &ray.origin, path.len() == 1, |light, pdf| if !light.is_infinite() { 0.0 } else if direct { 1.0 } else { power_heuristic(pdf_fwd, pdf) });
+                    l += beta * (scn.get_miss_mat().emission(&-ray.direction.norm(), &hit) + env);
+                }
+
+                break;
+            }
+
+            let wo = -ray.direction.norm();
+            let prev = path.len() - 1;
+
+            let mut vertex = Vertex::surface(&hit, wo, beta, from_light);
+            vertex.pdf_fwd = path[prev].convert_density(pdf_fwd, &vertex);
+            path.push(vertex);
+
+            let mat = scn.get_mat(hit.material);
+            let s = match mat.sample(&wo, &hit)
+            {
+                Some(s) => s,
+                None => break
+            };
+
+            beta *= s.weight(&hit.normal);
+
+            let pdf_rev = if s.flags.is_delta()
+            {
+                path[prev + 1].delta = true;
+                pdf_fwd = 0.0;
+                0.0
+            }
+            else
+            {
+                pdf_fwd = s.pdf;
+                mat.pdf(&wo, &s.wi, &hit)
+            };
+
+            path[prev].pdf_rev = path[prev + 1].convert_density(pdf_rev, &path[prev]);
+
+            if beta == Vec4::zero()
+            {
+                break;
+            }
+
+            ray = hit.spawn_ray(&s.wi);
+        }
+
+        l
+    }
+
+    fn camera_subpath(&self, scn: &Scene, cam: &dyn Camera, r: &mut RayInfo, path: &mut std::vec::Vec<Vertex>) -> Vec4
+    {
+        let pos = match cam.position()
+        {
+            Some(pos) => pos,
+            None => return Vec4::zero()
+        };
+
+        let dir = r.ray.direction.norm();
+        let (_, pdf_dir) = cam.importance(&dir);
+
+        path.push(Vertex::camera(&pos, Vec4::one()));
+        self.random_walk(scn, r, Ray::new(r.ray.origin, dir), Vec4::one(), pdf_dir, false, self.max_depth as usize + 2, path)
+    }
+
+    fn light_subpath(&self, scn: &Scene, r: &mut RayInfo, path: &mut std::vec::Vec<Vertex>)
+    {
+        let light = match scn.pick_light()
+        {
+            Some(light) => light,
+            None => return
+        };
+
+        // infinite lights don't emit rays
+        let emission = match scn.sample_emission_from(light)
+        {
+            Some(emission) => emission,
+            None => return
+        };
+
+        let mut hit = HitInfo::new();
+        hit.point = emission.point;
+        hit.normal = emission.normal;
+
+        let index = match light
+        {
+            LightRef::Object{obj} => { hit.object = obj; None },
+            LightRef::Light{light} => Some(light)
+        };
+
+        let select_pdf = scn.light_select_pdf();
+        path.push(Vertex::light(&hit, emission.le, emission.pdf_pos * select_pdf, index));
+
+        let beta = emission.power() / select_pdf;
+        self.random_walk(scn, r, hit.spawn_ray(&emission.dir), beta, emission.pdf_dir, true, self.max_depth as usize + 1, path);
+    }
+
+    // resamples a light as seen from pt for the s == 1 strategy, None if it doesn't illuminate pt
+    // returns the light vertex and the unweighted contribution, or no vertex and the weighted contribution of infinite lights
+    fn sample_light(&self, scn: &Scene, r: &mut RayInfo, pt: &Vertex) -> Option<(Option<Vertex>, Vec4)>
+    {
+        let light = scn.pick_light()?;
+        let select_pdf = scn.light_select_pdf();
+
+        match light
+        {
+            LightRef::Object{obj} =>
+            {
+                let mut ls = scn.get_object(obj).sample_area()?;
+                ls.hit.object = obj;
+
+                let to_light = ls.hit.point - pt.hit.point;
+                let dist2 = to_light.square_length();
+                let wi = to_light / dist2.sqrt();
+
+                // one sided lights are dark from behind
+                if !scn.get_object(obj).two_sided() && wi.dot(&ls.hit.normal) >= 0.0 { return None; }
+                let le = scn.get_mat(ls.hit.material).emission(&-wi, &ls.hit);
+
+                let vertex = Vertex::light(&ls.hit, le, ls.pdf * select_pdf, None);
+                let g = pt.cos(&wi) * vertex.cos(&wi) / dist2;
+                let l = pt.beta * pt.f(scn, &wi) * vertex.beta * g;
+
+                if l == Vec4::zero() || !self.visible(scn, r, pt, &vertex) { return None; }
+                Some((Some(vertex), l))
+            },
+            LightRef::Light{light: index} =>
+            {
+                let light = scn.get_light(index);
+                let ls = light.sample_li(&pt.hit.point)?;
+
+                let l = pt.beta * pt.f(scn, &ls.wi) * ls.le * pt.cos(&ls.wi) / (ls.pdf * select_pdf);
+                if l == Vec4::zero() { return None; }
+
+                if light.is_infinite()
+                {
+                    // infinite lights don't emit rays, only escaping camera paths compete with this strategy
+                    let weight = if light.is_delta() { 1.0 } else { power_heuristic(ls.pdf * select_pdf, scn.get_mat(pt.hit.material).pdf(&ls.wi, &pt.wo, &pt.hit)) };
+
+                    r.depth += 1;
+                    if scn.occluded(&pt.hit.spawn_ray(&ls.wi), std::f32::INFINITY) { return None; }
+                    return Some((None, l * weight));
+                }
+
+                let mut hit = HitInfo::new();
+                hit.point = ls.point;
+                hit.normal = ls.normal;
+
+                let vertex = Vertex::light(&hit, ls.le, light.pdf_le(&-ls.wi).0 * select_pdf, Some(index));
+                if !self.visible(scn, r, pt, &vertex) { return None; }
+                Some((Some(vertex), l))
+            }
+        }
+    }
+
+    // mutual visibility of two path vertices
+    fn visible(&self, scn: &Scene, r: &mut RayInfo, a: &Vertex, b: &Vertex) -> bool
+    {
+        let (from, to) = if a.kind == VertexType::Camera { (b, a) } else { (a, b) };
+        let to_other = to.hit.point - from.hit.point;
+        let dist = to_other.length();
+
+        r.depth += 1;
+        !scn.occluded(&from.hit.spawn_ray(&(to_other / dist)), dist * (1.0 - 1e-3))
+    }
+
+    // balance heuristic over all strategies that could have generated the path
+    fn mis_weight(&self, scn: &Scene, cam: &dyn Camera, light: &[Vertex], camera: &[Vertex], sampled: Option<Vertex>, s: usize, t: usize) -> f32
+    {
+        if s + t == 2 { return 1.0; }
+
+        let mut light_path: std::vec::Vec<Vertex> = light[..s.min(light.len())].to_vec();
+        let mut camera_path: std::vec::Vec<Vertex> = camera[..t].to_vec();
+
+        if let Some(v) = sampled
+        {
+            if s == 1 { light_path = vec![v]; }
+            if t == 1 { camera_path = vec![v]; }
+        }
+
+        // reverse densities of the connection vertices and their predecessors
+        let pt_rev = if s > 0
+        {
+            light_path[s - 1].pdf(scn, cam, if s > 1 { Some(&light_path[s - 2]) } else { None }, &camera_path[t - 1])
+        }
+        else
+        {
+            scn.light_area_pdf(camera_path[t - 1].hit.object)
+        };
+
+        // emitters that can't be sampled are only found by camera paths
+        if s == 0 && pt_rev == 0.0 { return 1.0; }
+
+        let pt_minus_rev = if t > 1
+        {
+            if s > 0 { camera_path[t - 1].pdf(scn, cam, Some(&light_path[s - 1]), &camera_path[t - 2]) } else { camera_path[t - 1].pdf_light(scn, &camera_path[t - 2]) }
+        }
+        else { 0.0 };
+
+        let qs_rev = if s > 0
+        {
+            camera_path[t - 1].pdf(scn, cam, if t > 1 { Some(&camera_path[t - 2]) } else { None }, &light_path[s - 1])
+        }
+        else { 0.0 };
+
+        let qs_minus_rev = if s > 1 { light_path[s - 1].pdf(scn, cam, Some(&camera_path[t - 1]), &light_path[s - 2]) } else { 0.0 };
+
+        camera_path[t - 1].pdf_rev = pt_rev;
+        camera_path[t - 1].delta = false;
+        if t > 1 { camera_path[t - 2].pdf_rev = pt_minus_rev; }
+
+        if s > 0
+        {
+            light_path[s - 1].pdf_rev = qs_rev;
+            light_path[s - 1].delta = false;
+        }
+        if s > 1 { light_path[s - 2].pdf_rev = qs_minus_rev; }
+
+        let remap = |pdf: f32| if pdf != 0.0 { pdf } else { 1.0 };
+        let mut sum = 0.0;
+
+        let mut ri = 1.0;
+        for i in (1..t).rev()
+        {
+            ri *= remap(camera_path[i].pdf_rev) / remap(camera_path[i].pdf_fwd);
+            if !camera_path[i].delta && !camera_path[i - 1].delta { sum += ri; }
+        }
+
+        ri = 1.0;
+        for i in (0..s).rev()
+        {
+            ri *= remap(light_path[i].pdf_rev) / remap(light_path[i].pdf_fwd);
+
+            // camera paths can't hit analytic lights
+            let delta_prev = if i > 0 { light_path[i - 1].delta } else { light_path[0].light.is_some() };
+            if !light_path[i].delta && !delta_prev { sum += ri; }
+        }
+
+        1.0 / (1.0 + sum)
+    }
+
+    // contribution of the strategy using s light and t camera vertices, t == 1 splats to the film
+    fn connect(&self, scn: &Scene, cam: &dyn Camera, r: &mut RayInfo, light: &[Vertex], camera: &[Vertex], s: usize, t: usize) -> Vec4
+    {
+        let pt = camera[t - 1];
+        let mut sampled = None;
+        let mut splat = None;
+
+        let l = if s == 0
+        {
+            // camera path hit an emitter
+            if pt.kind != VertexType::Surface { return Vec4::zero(); }
+            pt.beta * scn.get_mat(pt.hit.material).emission(&pt.wo, &pt.hit)
+        }
+        else if t == 1
+        {
+            // light tracing: connect to the camera
+            let qs = light[s - 1];
+            if qs.delta { return Vec4::zero(); }
+
+            let cs = match cam.sample_importance(&qs.hit.point)
+            {
+                Some(cs) => cs,
+                None => return Vec4::zero()
+            };
+
+            let vertex = Vertex::camera(&(qs.hit.point + cs.wi * cs.dist), Vec4::from(cs.weight));
+            let l = qs.beta * qs.f(scn, &cs.wi) * vertex.beta * qs.cos(&cs.wi);
+
+            if l == Vec4::zero() || !self.visible(scn, r, &qs, &vertex) { return Vec4::zero(); }
+
+            sampled = Some(vertex);
+            splat = Some((cs.x, cs.y));
+            l
+        }
+        else if s == 1
+        {
+            // light sampling: resample a point on a light
+            if pt.delta { return Vec4::zero(); }
+
+            match self.sample_light(scn, r, &pt)
+            {
+                Some((Some(vertex), l)) => { sampled = Some(vertex); l },
+                Some((None, l)) => return l,
+                None => return Vec4::zero()
+            }
+        }
+        else
+        {
+            let qs = light[s - 1];
+            if qs.delta || pt.delta { return Vec4::zero(); }
+
+            let to_camera = pt.hit.point - qs.hit.point;
+            let dist2 = to_camera.square_length();
+            let dir = to_camera / dist2.sqrt();
+            let g = qs.cos(&dir) * pt.cos(&dir) / dist2;
+
+            let l = qs.beta * qs.f(scn, &dir) * pt.f(scn, &-dir) * pt.beta * g;
+
+            if l == Vec4::zero() || !self.visible(scn, r, &qs, &pt) { return Vec4::zero(); }
+            l
+        };
+
+        if l == Vec4::zero() { return l; }
+
+        let weighted = l * self.mis_weight(scn, cam, light, camera, sampled, s, t);
+
+        match splat
+        {
+            Some((x, y)) =>
+            {
+                if let Some(film) = &r.film { film.add(x, y, &weighted); }
+                Vec4::zero()
+            },
+            None => weighted
+        }
+    }
+}
+
+impl Integrator for BidirectionalPathTracer
+{
+    fn li(&self, scn: &Scene, cam: &dyn Camera, r: &mut RayInfo) -> Vec4
+    {
+        let mut camera = std::vec::Vec::with_capacity(self.max_depth as usize + 2);
+        let mut light = std::vec::Vec::with_capacity(self.max_depth as usize + 1);
+
+        let mut l = self.camera_subpath(scn, cam, r, &mut camera);
+        self.light_subpath(scn, r, &mut light);
+
+        for t in 1..camera.len() + 1
+        {
+            // s == 1 resamples its light, it doesn't need a light subpath
+            for s in 0..light.len().max(1) + 1
+            {
+                let depth = (s + t) as i32 - 2;
+                if (s == 1 && t == 1) || depth < 0 || depth > self.max_depth as i32
+                {
+                    continue;
+                }
+
+                l += self.connect(scn, cam, r, &light, &camera, s, t);
+            }
+        }
+
+        r.radiance = l;
+        l
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::camera::*;
+    use super::super::material::*;
+    use super::super::primitives::*;
+    use super::super::random::*;
+    use super::super::light::*;
+    use super::super::shapes::*;
+
+    use std::sync::Arc;
+
+    const WIDTH: u32 = 16;
+    const HEIGHT: u32 = 12;
+    const SAMPLES: u32 = 256;
+
+    fn camera() -> PerspectiveCamera
+    {
+        PerspectiveCamera::new(Vec4::from3(0.0, 1.5, 4.0), Vec4::from3(0.0, 0.5, 0.0), Vec4::from3(0.0, 1.0, 0.0), 45.0, WIDTH, HEIGHT, 0.0, 100.0, SAMPLES)
+    }
+
+    // unlit scene with a diffuse floor and ball
+    fn scene() -> Scene
+    {
+        let mut scn = Scene::new();
+        let black = scn.add_mat(Background::new(Vec4::zero(), 0.0));
+        scn.set_miss_mat(black);
+
+        let diffuse = scn.add_mat(Lambertian::new(0.5, 0.5, 0.5));
+        scn.add_prmitive(Plane::new_two_sided(Vec4::zero(), Vec4::from3(0.0, 1.0, 0.0)).object(diffuse));
+        scn.add_prmitive(Sphere::new(Vec4::from3(1.0, 0.4, 0.5), 0.4).object(diffuse));
+        scn
+    }
+
+    // pixels including light tracing splats, same as trace_image
+    fn render(scn: &Scene, cam: &PerspectiveCamera, integrator: &dyn Integrator) -> std::vec::Vec<Vec4>
+    {
+        let mut image = vec![Vec4::zero(); (WIDTH * HEIGHT) as usize];
+        let film = Arc::new(Film::new(WIDTH, HEIGHT));
+        let mut info = RayInfo::new();
+        info.film = Some(film.clone());
+
+        for y in 0..HEIGHT
+        {
+            for x in 0..WIDTH
+            {
+                for _ in 0..SAMPLES
+                {
+                    let (s, t) = random_in_unit_disk2();
+                    info.reset(&cam.get_ray(x as f32 + s * 0.5, y as f32 + t * 0.5, Channel::All));
+                    image[(y * WIDTH + x) as usize] += integrator.li(scn, cam, &mut info);
+                }
+            }
+        }
+
+        for y in 0..HEIGHT
+        {
+            for x in 0..WIDTH
+            {
+                image[(y * WIDTH + x) as usize] += film.get(x, y);
+            }
+        }
+
+        image.iter().map(|p| *p / SAMPLES as f32).collect()
+    }
+
+    fn mean(image: &[Vec4]) -> f32
+    {
+        image.iter().map(|p| p.x() + p.y() + p.z()).sum::<f32>() / (3.0 * image.len() as f32)
+    }
+
+    fn compare(scn: &Scene, reference: IntegratorType)
+    {
+        compare_with(scn, reference, IntegratorType::Bidirectional);
+    }
+
+    fn compare_with(scn: &Scene, reference: IntegratorType, integrator: IntegratorType)
+    {
+        let cam = camera();
+        let mut settings = TraceSettings::new();
+        settings.integrator = reference;
+        let reference = mean(&render(scn, &cam, create_integrator(&settings).as_ref()));
+        settings.integrator = integrator;
+        let result = mean(&render(scn, &cam, create_integrator(&settings).as_ref()));
+
+        assert!(reference > 0.01, "reference {}", reference);
+        assert!((result - reference).abs() < 0.1 * reference, "result {} reference {}", result, reference);
+    }
+
+    #[test]
+    fn emissive_box()
+    {
+        let mut scn = scene();
+        let emissive = scn.add_mat(Emissive::new(2.0, 2.0, 2.0));
+        scn.add_prmitive(BBox::new(Vec4::from3(0.0, 0.5, 0.0), Vec4::from3(0.5, 0.5, 0.5)).object(emissive));
+
+        let image = render(&scn, &camera(), &BidirectionalPathTracer::new(&TraceSettings::new()));
+        let center = image[(HEIGHT / 2 * WIDTH + WIDTH / 2) as usize];
+        assert!((center.x() - 2.0).abs() < 0.05, "box radiance {}", center.x());
+
+        compare(&scn, IntegratorType::Path);
+    }
+
+    #[test]
+    fn emissive_sphere()
+    {
+        let mut scn = scene();
+        let emissive = scn.add_mat(Emissive::new(2.0, 2.0, 2.0));
+        scn.add_prmitive(Sphere::new(Vec4::from3(0.0, 0.5, 0.0), 0.5).object(emissive));
+
+        compare(&scn, IntegratorType::Path);
+    }
+
+    // area sampled as lights by next event estimation and light subpaths
+    #[test]
+    fn emissive_shapes()
+    {
+        let mut scn = scene();
+        let emissive = scn.add_mat(Emissive::new(2.0, 2.0, 2.0));
+        let cylinder = scn.add_prmitive(Cylinder::new(Vec4::from3(-1.0, 0.0, 0.0), Vec4::from3(0.0, 1.0, 0.0), 0.2, 0.8, true).object(emissive));
+        let cone = scn.add_prmitive(Cone::new(Vec4::from3(0.0, 0.0, -0.5), Vec4::from3(0.0, 1.0, 0.0), 0.3, 0.6, true).object(emissive));
+        let capsule = scn.add_prmitive(Capsule::new(Vec4::from3(-0.3, 0.3, 0.8), Vec4::from3(0.3, 0.3, 0.8), 0.15).object(emissive));
+        let torus = scn.add_prmitive(Torus::new(Vec4::from3(0.7, 0.1, -0.4), Vec4::from3(0.0, 1.0, 0.0), 0.2, 0.08).object(emissive));
+        let paraboloid = scn.add_prmitive(Paraboloid::new(Vec4::from3(-0.5, 0.05, -0.8), Vec4::from3(0.0, 1.0, 0.0), 0.25, 0.3).object(emissive));
+
+        assert!(scn.light_count() == 5);
+        for &obj in [cylinder, cone, capsule, torus, paraboloid].iter()
+        {
+            for _ in 0..1000
+            {
+                let s = scn.get_object(obj).sample_area().unwrap();
+                let r = Ray::new(s.hit.point + s.hit.normal * 0.01, -s.hit.normal);
+                let mut hit = HitInfo::new();
+                assert!(scn.get_object(obj).hit(&r, &mut hit, 0.0, 1.0) && (hit.depth - 0.01).abs() < 1e-3, "sample off the surface");
+            }
+        }
+
+        compare_with(&scn, IntegratorType::Path, IntegratorType::NeePath);
+        compare(&scn, IntegratorType::Path);
+    }
+
+    // lit by analytic lights only, compared with next event estimation
+    fn lights(lights: std::vec::Vec<Light>)
+    {
+        let mut scn = scene();
+        for light in lights
+        {
+            scn.add_light(light);
+        }
+
+        compare(&scn, IntegratorType::NeePath);
+    }
+
+    #[test]
+    fn point_and_spot_lights()
+    {
+        lights(vec![
+            PointLight::new(Vec4::from3(-1.0, 2.0, 1.0), Vec4::from3(4.0, 4.0, 4.0)),
+            SpotLight::new(Vec4::from3(1.0, 2.5, 0.0), Vec4::from3(0.5, 0.0, 0.0), Vec4::from3(6.0, 6.0, 6.0), 20.0, 35.0)]);
+    }
+
+    #[test]
+    fn area_lights()
+    {
+        lights(vec![
+            RectLight::new(Vec4::from3(-0.5, 2.0, 0.0), Vec4::from3(0.5, 0.0, 0.0), Vec4::from3(0.0, 0.0, -0.5), Vec4::from3(2.0, 2.0, 2.0), false),
+            DiskLight::new(Vec4::from3(1.0, 0.3, -1.0), Vec4::from3(0.0, 0.0, 1.0), 0.3, Vec4::from3(3.0, 3.0, 3.0), true)]);
+    }
+
+    #[test]
+    fn directional_lights()
+    {
+        lights(vec![
+            DirectionalLight::new(Vec4::from3(1.0, 2.0, 1.0), Vec4::from3(1.5, 1.5, 1.5), 0.0),
+            DirectionalLight::new(Vec4::from3(-1.0, 1.0, 0.5), Vec4::from3(1.0, 1.0, 1.0), 5.0)]);
+    }
+}
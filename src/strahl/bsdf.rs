@@ -0,0 +1,133 @@
+use super::vec::*;
+use super::ray::*;
+use super::hit::*;
+use super::material::MaterialInfo;
+
+use std::ops::{BitOr, BitOrAssign};
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct BsdfFlags
+{
+    pub bits: u32
+}
+
+impl BsdfFlags
+{
+    pub const NONE: BsdfFlags = BsdfFlags{bits: 0};
+    pub const REFLECTION: BsdfFlags = BsdfFlags{bits: 1};
+    pub const TRANSMISSION: BsdfFlags = BsdfFlags{bits: 2};
+    pub const DIFFUSE: BsdfFlags = BsdfFlags{bits: 4};
+    pub const GLOSSY: BsdfFlags = BsdfFlags{bits: 8};
+    // delta distribution: can only be sampled, eval & pdf are zero
+    pub const SPECULAR: BsdfFlags = BsdfFlags{bits: 16};
+
+    pub fn contains(&self, o: BsdfFlags) -> bool
+    {
+        (self.bits & o.bits) == o.bits
+    }
+
+    pub fn intersects(&self, o: BsdfFlags) -> bool
+    {
+        (self.bits & o.bits) != 0
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.bits == 0
+    }
+
+    pub fn is_delta(&self) -> bool
+    {
+        self.intersects(BsdfFlags::SPECULAR)
+    }
+
+    // true if the bsdf has at least one lobe that can be evaluated (needed for light sampling)
+    pub fn is_non_delta(&self) -> bool
+    {
+        self.intersects(BsdfFlags::DIFFUSE | BsdfFlags::GLOSSY)
+    }
+}
+
+impl BitOr for BsdfFlags
+{
+    type Output = BsdfFlags;
+
+    fn bitor(self, o: BsdfFlags) -> BsdfFlags
+    {
+        BsdfFlags{bits: self.bits | o.bits}
+    }
+}
+
+impl BitOrAssign for BsdfFlags
+{
+    fn bitor_assign(&mut self, o: BsdfFlags)
+    {
+        self.bits |= o.bits
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct BsdfSample
+{
+    pub wi: Vec4,
+    pub f: Vec4, // for delta lobes f is pre-divided by |cos(wi)|
+    pub pdf: f32, // solid angle pdf, 1.0 for delta lobes
+    pub flags: BsdfFlags // the lobe that was sampled
+}
+
+impl BsdfSample
+{
+    pub fn new(_wi: Vec4, _f: Vec4, _pdf: f32, _flags: BsdfFlags) -> BsdfSample
+    {
+        BsdfSample{wi: _wi, f: _f, pdf: _pdf, flags: _flags}
+    }
+
+    // f * |cos| / pdf
+    pub fn weight(&self, normal: &Vec4) -> Vec4
+    {
+        self.f * self.wi.dot(normal).abs() / self.pdf
+    }
+}
+
+// all directions are in world space, normalized and pointing away from the surface
+pub trait Bsdf
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample>;
+    fn eval(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> Vec4;
+    fn pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> f32;
+    fn flags(&self, hit: &HitInfo) -> BsdfFlags;
+
+    // eval and pdf together, for bsdfs that can share the work between both
+    fn eval_pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> (Vec4, f32)
+    {
+        (self.eval(wi, wo, hit), self.pdf(wi, wo, hit))
+    }
+
+    // radiance emitted towards wo
+    fn emission(&self, _wo: &Vec4, _hit: &HitInfo) -> Vec4
+    {
+        Vec4::zero()
+    }
+}
+
+// Scatter implementation for materials that are fully described by their bsdf
+pub fn scatter_bsdf<B: Bsdf>(bsdf: &B, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
+{
+    let wo = -_r.direction.norm();
+    _out_mat.emission = bsdf.emission(&wo, _hit);
+
+    match bsdf.sample(&wo, _hit)
+    {
+        Some(s) =>
+        {
+            *_r = _hit.spawn_ray(&s.wi);
+            _out_mat.attenuation = s.weight(&_hit.normal);
+            true
+        },
+        None =>
+        {
+            _out_mat.attenuation = Vec4::zero();
+            false
+        }
+    }
+}
@@ -19,6 +19,17 @@ pub enum Mode
     Separate = 1
 }
 
+// connection of a world space point to the camera
+#[derive(Copy, Clone)]
+pub struct CameraSample
+{
+    pub x: f32,
+    pub y: f32,
+    pub wi: Vec4, // from the point towards the camera
+    pub dist: f32,
+    pub weight: f32 // We * cos / dist^2
+}
+
 use std::marker::{Send, Sync};
 pub trait Camera: Send + Sync
 {
@@ -32,6 +43,31 @@ pub trait Camera: Send + Sync
 
     // floating pixel coordinates in [widthxheight]
     fn get_ray(&self, x: f32, y: f32, channel: Channel) -> Ray;
+
+    // used to connect light paths to the camera, cameras returning None only support camera paths
+    fn position(&self) -> Option<Vec4> { None }
+
+    // floating pixel coordinates of a world space point, None if outside the image
+    fn project(&self, _p: &Vec4) -> Option<(f32, f32)> { None }
+
+    // importance We and solid angle pdf of emitting a ray in (normalized) direction dir
+    fn importance(&self, _dir: &Vec4) -> (f32, f32) { (0.0, 0.0) }
+
+    fn sample_importance(&self, p: &Vec4) -> Option<CameraSample>
+    {
+        let pos = self.position()?;
+        let (x, y) = self.project(p)?;
+
+        let to_cam = pos - p;
+        let dist = to_cam.length();
+        let wi = to_cam / dist;
+        let (we, pdf) = self.importance(&-wi);
+
+        if we <= 0.0 { return None; }
+
+        // We * cos equals the directional pdf for an ideal pinhole
+        Some(CameraSample{x: x, y: y, wi: wi, dist: dist, weight: pdf / (dist * dist)})
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -42,6 +78,8 @@ pub struct PerspectiveCamera
     // u: Vec4, 
     // v: Vec4,
     lense_radius: f32,
+    forward: Vec4,
+    image_area: f32, // area of the image plane at distance 1
     lower_left_corner: Vec4,
     horizontal: Vec4,
     vertical: Vec4,
@@ -66,6 +104,8 @@ impl PerspectiveCamera {
             // u: _u,
             // v: _v,
             lense_radius: lense_diameter / 2.0,
+            forward: -_w,
+            image_area: 4.0 * half_width * half_height,
             lower_left_corner: origin - half_width*far*_u -half_height*far*_v - far*_w,
             horizontal: 2.0*half_width*far*_u,
             vertical: 2.0*half_height*far*_v,
@@ -79,11 +119,13 @@ impl PerspectiveCamera {
     {
         let offset = self.lense_radius * random_in_unit_disk();
 
-        Ray
-        {
-            origin: self.pos + offset,
-            direction: self.lower_left_corner + s*self.horizontal + t*self.vertical - self.pos - offset
-        }
+        Ray::with_cone(self.pos + offset, self.lower_left_corner + s*self.horizontal + t*self.vertical - self.pos - offset, 0.0, self.pixel_spread())
+    }
+
+    // angle one pixel covers seen from the camera
+    fn pixel_spread(&self) -> f32
+    {
+        self.vertical.length3() / (self.height as f32 * (self.lower_left_corner - self.pos).dot(&self.forward))
     }
 }
 
@@ -94,11 +136,7 @@ impl Camera for PerspectiveCamera
         let s = x / (self.width as f32);
         let t = y / (self.height as f32);
 
-        Ray
-        {
-            origin: self.pos,
-            direction: self.lower_left_corner + s*self.horizontal + t*self.vertical - self.pos
-        }
+        Ray::with_cone(self.pos, self.lower_left_corner + s*self.horizontal + t*self.vertical - self.pos, 0.0, self.pixel_spread())
     }
 
     fn mode(&self ) -> Mode { Mode::Combined }
@@ -106,6 +144,37 @@ impl Camera for PerspectiveCamera
     fn sample_count(&self) -> u32 {self.samples}
     fn width(&self) -> u32 {self.width}
     fn height(&self) -> u32 {self.height}
+
+    fn position(&self) -> Option<Vec4> { Some(self.pos) }
+
+    fn project(&self, p: &Vec4) -> Option<(f32, f32)>
+    {
+        let dir = p - self.pos;
+        let cos = dir.dot(&self.forward);
+        if cos <= 0.0 { return None; }
+
+        // intersect the image plane spanned by horizontal & vertical
+        let plane_dist = (self.lower_left_corner - self.pos).dot(&self.forward);
+        let on_plane = self.pos + dir * (plane_dist / cos) - self.lower_left_corner;
+
+        let s = on_plane.dot(&self.horizontal) / self.horizontal.square_length();
+        let t = on_plane.dot(&self.vertical) / self.vertical.square_length();
+
+        if s < 0.0 || s > 1.0 || t < 0.0 || t > 1.0 { return None; }
+
+        Some((s * self.width as f32, t * self.height as f32))
+    }
+
+    // pinhole: We = 1 / (A cos^4), pdf = 1 / (A cos^3)
+    // https://www.pbr-book.org/3ed-2018/Light_Transport_III_Bidirectional_Methods/The_Path-Space_Measurement_Equation
+    fn importance(&self, dir: &Vec4) -> (f32, f32)
+    {
+        let cos = dir.dot(&self.forward);
+        if cos <= 0.0 || self.project(&(self.pos + dir)).is_none() { return (0.0, 0.0); }
+
+        let cos2 = cos * cos;
+        (1.0 / (self.image_area * cos2 * cos2), 1.0 / (self.image_area * cos2 * cos))
+    }
 }
 
 unsafe impl Sync for PerspectiveCamera{}
\ No newline at end of file
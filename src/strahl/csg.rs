@@ -0,0 +1,186 @@
+use super::hit::*;
+use super::ray::*;
+use super::quaternion::*;
+use super::primitives::*;
+
+use std::sync::Arc;
+
+// boolean combinations of closed objects, evaluated from the surface crossings of both operands in ray order
+// operands need outward normals, crossing a surface against its normal enters the solid
+// one sided planar shapes are culled from behind, half spaces need two sided planes (Plane::new_two_sided)
+
+const CSG_MAX_CROSSINGS: usize = 64;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CsgOp
+{
+    Union,
+    Intersection,
+    Difference // a minus b
+}
+
+// surfaces keep the material of the operand they belong to
+#[derive(Clone)]
+pub struct Csg
+{
+    op: CsgOp,
+    a: Arc<Object>,
+    b: Arc<Object>,
+    bounds: Option<BBox>
+}
+
+impl CsgOp
+{
+    fn inside(&self, a: bool, b: bool) -> bool
+    {
+        match self
+        {
+            CsgOp::Union => a || b,
+            CsgOp::Intersection => a && b,
+            CsgOp::Difference => a && !b
+        }
+    }
+
+    // bounds of the result from the operand bounds, None if unbounded
+    fn bounds(&self, a: Option<BBox>, b: Option<BBox>) -> Option<BBox>
+    {
+        match self
+        {
+            CsgOp::Union => match (a, b)
+            {
+                (Some(a), Some(b)) =>
+                {
+                    let ((alo, ahi), (blo, bhi)) = (a.corners(), b.corners());
+                    Some(BBox::from_corners(alo.min(&blo), ahi.max(&bhi)))
+                },
+                _ => None
+            },
+            CsgOp::Intersection => match (a, b)
+            {
+                (Some(a), Some(b)) =>
+                {
+                    let ((alo, ahi), (blo, bhi)) = (a.corners(), b.corners());
+                    Some(BBox::from_corners(alo.max(&blo), ahi.min(&bhi).max(&alo.max(&blo))))
+                },
+                (a, b) => a.or(b)
+            },
+            CsgOp::Difference => a
+        }
+    }
+}
+
+// side of obj the ray starts on, told by the next crossing ahead or the last one behind
+fn starts_inside(obj: &Object, r: &Ray, first: &HitInfo, found: bool, min: f32) -> bool
+{
+    if found
+    {
+        return first.normal.dot(&r.direction) > 0.0;
+    }
+
+    // nothing ahead, half spaces and rays leaving the solid for good
+    let back = Ray::new(r.point_at(min), -r.direction);
+    let mut hit = HitInfo::new();
+    obj.hit(&back, &mut hit, 0.0, std::f32::INFINITY) && hit.normal.dot(&back.direction) > 0.0
+}
+
+impl Csg
+{
+    pub fn new(_op: CsgOp, _a: Object, _b: Object) -> Csg
+    {
+        let bounds = _op.bounds(_a.bounds(), _b.bounds());
+        Csg{op: _op, a: Arc::new(_a), b: Arc::new(_b), bounds: bounds}
+    }
+
+    pub fn union(_a: Object, _b: Object) -> Csg
+    {
+        Csg::new(CsgOp::Union, _a, _b)
+    }
+
+    pub fn intersection(_a: Object, _b: Object) -> Csg
+    {
+        Csg::new(CsgOp::Intersection, _a, _b)
+    }
+
+    pub fn difference(_a: Object, _b: Object) -> Csg
+    {
+        Csg::new(CsgOp::Difference, _a, _b)
+    }
+
+    // _mat is what the scene sees for the whole object (emission, interfaces), hits report the operand materials
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Csg{obj: self.clone()}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> Option<BBox>
+    {
+        self.bounds
+    }
+}
+
+impl Hitable for Csg
+{
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        if let Some(bounds) = self.bounds
+        {
+            match bounds.clip(r)
+            {
+                Some((near, far)) => if far <= min || near >= max { return false; },
+                None => return false
+            }
+        }
+
+        // crossings past max still tell which side of an operand the ray starts on
+        let mut ha = HitInfo::new();
+        let mut hb = HitInfo::new();
+        let mut found_a = self.a.hit(r, &mut ha, min, std::f32::INFINITY);
+        let mut found_b = self.b.hit(r, &mut hb, min, std::f32::INFINITY);
+        let mut in_a = starts_inside(&self.a, r, &ha, found_a, min);
+        let mut in_b = starts_inside(&self.b, r, &hb, found_b, min);
+
+        for _ in 0..CSG_MAX_CROSSINGS
+        {
+            let take_a = found_a && (!found_b || ha.depth <= hb.depth);
+            if !take_a && !found_b
+            {
+                return false;
+            }
+
+            let hit = if take_a { ha } else { hb };
+            if hit.depth >= max
+            {
+                return false;
+            }
+
+            let before = self.op.inside(in_a, in_b);
+            let entering = hit.normal.dot(&r.direction) < 0.0;
+            if take_a { in_a = entering; } else { in_b = entering; }
+
+            if before != self.op.inside(in_a, in_b)
+            {
+                *out = hit;
+
+                // the inside of b becomes the outside of the result
+                if !take_a && self.op == CsgOp::Difference
+                {
+                    out.normal = -out.normal;
+                    out.bitangent = -out.bitangent;
+                }
+
+                return true;
+            }
+
+            if take_a
+            {
+                found_a = self.a.hit(r, &mut ha, hit.depth, std::f32::INFINITY);
+            }
+            else
+            {
+                found_b = self.b.hit(r, &mut hb, hit.depth, std::f32::INFINITY);
+            }
+        }
+
+        false
+    }
+}
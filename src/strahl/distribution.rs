@@ -0,0 +1,191 @@
+use super::random::*;
+
+// https://www.pbr-book.org/3ed-2018/Monte_Carlo_Integration/Sampling_Random_Variables#PiecewiseConstant1DDistributions
+
+// piecewise constant distribution over [0, 1)
+#[derive(Clone)]
+pub struct Distribution1D
+{
+    func: std::vec::Vec<f32>,
+    cdf: std::vec::Vec<f32>,
+    integral: f32
+}
+
+// piecewise constant distribution over [0, 1)^2, sampled as marginal rows then conditional columns
+#[derive(Clone)]
+pub struct Distribution2D
+{
+    conditional: std::vec::Vec<Distribution1D>,
+    marginal: Distribution1D
+}
+
+//######################################################################
+// Distribution1D
+//######################################################################
+
+impl Distribution1D
+{
+    pub fn new(_func: &[f32]) -> Distribution1D
+    {
+        let n = _func.len().max(1);
+        let func: std::vec::Vec<f32> = if _func.is_empty() { vec![0.0] } else { _func.iter().map(|f| f.abs()).collect() };
+
+        let mut cdf = std::vec::Vec::with_capacity(n + 1);
+        cdf.push(0.0);
+        for i in 0..n
+        {
+            let prev = cdf[i];
+            cdf.push(prev + func[i] / n as f32);
+        }
+
+        let integral = cdf[n];
+        for i in 1..n + 1
+        {
+            // all zero functions fall back to uniform
+            cdf[i] = if integral > 0.0 { cdf[i] / integral } else { i as f32 / n as f32 };
+        }
+
+        Distribution1D{func: func, cdf: cdf, integral: integral}
+    }
+
+    pub fn count(&self) -> usize
+    {
+        self.func.len()
+    }
+
+    pub fn integral(&self) -> f32
+    {
+        self.integral
+    }
+
+    // continuous sample in [0, 1), its pdf and the segment it fell into
+    pub fn sample(&self, u: f32) -> (f32, f32, usize)
+    {
+        let n = self.count();
+
+        // last cdf entry <= u
+        let mut lo = 0;
+        let mut hi = n;
+        while lo + 1 < hi
+        {
+            let mid = (lo + hi) / 2;
+            if self.cdf[mid] <= u { lo = mid; } else { hi = mid; }
+        }
+
+        let span = self.cdf[lo + 1] - self.cdf[lo];
+        let du = if span > 0.0 { (u - self.cdf[lo]) / span } else { 0.0 };
+
+        let x = ((lo as f32 + du) / n as f32).min(1.0 - std::f32::EPSILON);
+        (x, self.pdf_index(lo), lo)
+    }
+
+    fn pdf_index(&self, i: usize) -> f32
+    {
+        if self.integral > 0.0 { self.func[i] / self.integral } else { 1.0 }
+    }
+
+    pub fn pdf(&self, x: f32) -> f32
+    {
+        let i = ((x * self.count() as f32) as usize).min(self.count() - 1);
+        self.pdf_index(i)
+    }
+}
+
+//######################################################################
+// Distribution2D
+//######################################################################
+
+impl Distribution2D
+{
+    // func is row major with width columns
+    pub fn new(func: &[f32], width: usize, height: usize) -> Distribution2D
+    {
+        let conditional: std::vec::Vec<Distribution1D> = (0..height).map(|y| Distribution1D::new(&func[y * width..(y + 1) * width])).collect();
+        let marginal_func: std::vec::Vec<f32> = conditional.iter().map(|c| c.integral()).collect();
+
+        Distribution2D{marginal: Distribution1D::new(&marginal_func), conditional: conditional}
+    }
+
+    // (u, v) in [0, 1)^2 and the pdf w.r.t. uv area
+    pub fn sample(&self) -> (f32, f32, f32)
+    {
+        let (u1, u2) = (random_f32(), random_f32());
+
+        let (v, pdf_v, row) = self.marginal.sample(u1);
+        let (u, pdf_u, _) = self.conditional[row].sample(u2);
+
+        (u, v, pdf_u * pdf_v)
+    }
+
+    pub fn pdf(&self, u: f32, v: f32) -> f32
+    {
+        let row = ((v * self.conditional.len() as f32) as usize).min(self.conditional.len() - 1);
+        self.marginal.pdf(v) * self.conditional[row].pdf(u)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const WIDTH: usize = 4;
+    const HEIGHT: usize = 3;
+
+    fn distribution() -> Distribution2D
+    {
+        // includes an empty row and empty cells
+        let func = [1.0, 0.0, 3.0, 0.5,
+                    0.0, 0.0, 0.0, 0.0,
+                    2.0, 8.0, 0.0, 1.0];
+        Distribution2D::new(&func, WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn pdf_integrates_to_one()
+    {
+        let dist = distribution();
+
+        // midpoint rule on a grid that splits every cell, exact for a piecewise constant pdf
+        let n = 8;
+        let mut integral = 0.0;
+        for y in 0..HEIGHT * n
+        {
+            for x in 0..WIDTH * n
+            {
+                integral += dist.pdf((x as f32 + 0.5) / (WIDTH * n) as f32, (y as f32 + 0.5) / (HEIGHT * n) as f32);
+            }
+        }
+        integral /= (WIDTH * HEIGHT * n * n) as f32;
+
+        assert!((integral - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sample_matches_pdf()
+    {
+        let dist = distribution();
+        let samples = 100000;
+        let mut counts = [0; WIDTH * HEIGHT];
+
+        for _ in 0..samples
+        {
+            let (u, v, pdf) = dist.sample();
+            assert!(u >= 0.0 && u < 1.0 && v >= 0.0 && v < 1.0);
+            assert!(pdf > 0.0 && (pdf - dist.pdf(u, v)).abs() <= 1e-5 * pdf);
+
+            counts[(v * HEIGHT as f32) as usize * WIDTH + (u * WIDTH as f32) as usize] += 1;
+        }
+
+        // the fraction of samples in a cell is its pdf times its area
+        for y in 0..HEIGHT
+        {
+            for x in 0..WIDTH
+            {
+                let expected = dist.pdf((x as f32 + 0.5) / WIDTH as f32, (y as f32 + 0.5) / HEIGHT as f32) / (WIDTH * HEIGHT) as f32;
+                let fraction = counts[y * WIDTH + x] as f32 / samples as f32;
+                assert!((fraction - expected).abs() < 0.01);
+            }
+        }
+    }
+}
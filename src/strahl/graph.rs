@@ -0,0 +1,544 @@
+use super::hit::*;
+use super::vec::*;
+use super::texture::*;
+use super::procedural::{perlin, eval_ramp};
+use super::microfacet::fresnel_dielectric;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// node graphs in a small text format, one node per line:
+//
+//   # comment
+//   tiled = uv_transform uv 4 4 0 0 30
+//   albedo = image "floor.jpg" srgb tiled
+//   lum = luminance albedo
+//   rough = ramp lum 0 [0.2] 1 [0.8]
+//   output base_color albedo
+//
+// arguments are earlier nodes, numbers or [x y z] vectors, each output is compiled
+// into a flat register program with unused nodes dropped and constants folded
+
+const MAX_REGISTERS: usize = 64;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Op
+{
+    Const(Vec4),
+    Uv,
+    Position,
+    Normal,
+    View,
+    Image(usize), // coords
+    UvTransform([f32; 6]), // coords, 2x2 matrix and offset
+    Abs,
+    Sin,
+    Cos,
+    Floor,
+    Fract,
+    Sqrt,
+    Luminance,
+    X,
+    Y,
+    Z,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+    Pow,
+    Dot,
+    Clamp, // x, lo, hi
+    Mix, // a, b, t
+    Combine, // x, y, z
+    Fresnel, // ior
+    LayerWeight, // blend, fresnel in x and facing in y
+    Ramp(usize),
+    Noise(f32), // coords, scale
+    Checker(f32) // coords, scale
+}
+
+#[derive(Clone)]
+struct Node
+{
+    op: Op,
+    inputs: std::vec::Vec<usize>
+}
+
+#[derive(Copy, Clone)]
+struct Instr
+{
+    op: Op,
+    dst: usize,
+    inputs: [usize; 3]
+}
+
+// parsed graph file, outputs are compiled to ShaderGraphs on demand
+pub struct GraphSource
+{
+    nodes: std::vec::Vec<Node>,
+    outputs: HashMap<String, usize>,
+    images: std::vec::Vec<Arc<DynamicTexture>>,
+    ramps: std::vec::Vec<Arc<std::vec::Vec<(f32, Vec4)>>>
+}
+
+#[derive(Clone)]
+pub struct ShaderGraph
+{
+    code: std::vec::Vec<Instr>,
+    output: usize,
+    images: std::vec::Vec<Arc<DynamicTexture>>,
+    ramps: std::vec::Vec<Arc<std::vec::Vec<(f32, Vec4)>>>
+}
+
+//######################################################################
+// parsing
+//######################################################################
+
+// words, "quoted strings" and [vectors], a # outside of quotes starts a comment
+fn tokenize(line: &str) -> Result<std::vec::Vec<String>, String>
+{
+    let mut tokens = std::vec::Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek()
+    {
+        if c == '#'
+        {
+            break;
+        }
+        else if c.is_whitespace()
+        {
+            chars.next();
+        }
+        else if c == '"' || c == '['
+        {
+            let close = if c == '"' { '"' } else { ']' };
+            let mut token = String::new();
+            token.push(chars.next().unwrap());
+            loop
+            {
+                match chars.next()
+                {
+                    Some(n) => { token.push(n); if n == close { break; } },
+                    None => return Err(format!("unterminated {}", token))
+                }
+            }
+            tokens.push(token);
+        }
+        else
+        {
+            let mut token = String::new();
+            while let Some(&n) = chars.peek()
+            {
+                if n.is_whitespace() || n == '"' || n == '[' || n == '#' { break; }
+                token.push(n);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_number(token: &str) -> Result<f32, String>
+{
+    token.parse::<f32>().map_err(|_| format!("expected a number, found {}", token))
+}
+
+fn parse_vector(token: &str) -> Result<Vec4, String>
+{
+    let values: Result<std::vec::Vec<f32>, String> = token[1..token.len() - 1].split(|c: char| c.is_whitespace() || c == ',').filter(|s| !s.is_empty()).map(parse_number).collect();
+
+    match values?.as_slice()
+    {
+        [x] => Ok(Vec4::from(*x)),
+        [x, y, z] => Ok(Vec4::from3(*x, *y, *z)),
+        [x, y, z, w] => Ok(Vec4::new(*x, *y, *z, *w)),
+        _ => Err(format!("vectors have 1, 3 or 4 components: {}", token))
+    }
+}
+
+impl GraphSource
+{
+    pub fn from_path<P>(path: P) -> Result<GraphSource, String>
+    where P: AsRef<std::path::Path>
+    {
+        std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|text| GraphSource::parse(&text))
+    }
+
+    pub fn parse(text: &str) -> Result<GraphSource, String>
+    {
+        let mut source = GraphSource{nodes: std::vec::Vec::new(), outputs: HashMap::new(), images: std::vec::Vec::new(), ramps: std::vec::Vec::new()};
+        let mut names: HashMap<String, usize> = HashMap::new();
+
+        for (number, line) in text.lines().enumerate()
+        {
+            let tokens = tokenize(line).map_err(|e| format!("graph line {}: {}", number + 1, e))?;
+            if tokens.is_empty()
+            {
+                continue;
+            }
+
+            let result = if tokens[0] == "output"
+            {
+                match tokens.as_slice()
+                {
+                    [_, slot, node] => source.input(&names, node).map(|id| { source.outputs.insert(slot.clone(), id); }),
+                    _ => Err("expected output <slot> <node>".to_string())
+                }
+            }
+            else if tokens.len() >= 3 && tokens[1] == "="
+            {
+                source.node(&names, &tokens[2], &tokens[3..]).map(|id| { names.insert(tokens[0].clone(), id); })
+            }
+            else
+            {
+                Err("expected <name> = <op> <args> or output <slot> <node>".to_string())
+            };
+
+            result.map_err(|e| format!("graph line {}: {}", number + 1, e))?;
+        }
+
+        Ok(source)
+    }
+
+    fn push(&mut self, op: Op, inputs: std::vec::Vec<usize>) -> usize
+    {
+        self.nodes.push(Node{op: op, inputs: inputs});
+        self.nodes.len() - 1
+    }
+
+    // a node name or a literal, the implicit nodes can be used without defining them
+    fn input(&mut self, names: &HashMap<String, usize>, token: &str) -> Result<usize, String>
+    {
+        if let Some(id) = names.get(token)
+        {
+            return Ok(*id);
+        }
+
+        match token
+        {
+            "uv" => Ok(self.push(Op::Uv, vec![])),
+            "position" => Ok(self.push(Op::Position, vec![])),
+            "normal" => Ok(self.push(Op::Normal, vec![])),
+            "view" => Ok(self.push(Op::View, vec![])),
+            _ if token.starts_with('[') => { let v = parse_vector(token)?; Ok(self.push(Op::Const(v), vec![])) },
+            _ => match token.parse::<f32>()
+            {
+                Ok(x) => Ok(self.push(Op::Const(Vec4::from(x)), vec![])),
+                Err(_) => Err(format!("unknown node {}", token))
+            }
+        }
+    }
+
+    fn node(&mut self, names: &HashMap<String, usize>, op: &str, args: &[String]) -> Result<usize, String>
+    {
+        let arity = |n: usize| if args.len() == n { Ok(()) } else { Err(format!("{} takes {} arguments", op, n)) };
+
+        let unary = match op
+        {
+            "abs" => Some(Op::Abs), "sin" => Some(Op::Sin), "cos" => Some(Op::Cos), "floor" => Some(Op::Floor),
+            "fract" => Some(Op::Fract), "sqrt" => Some(Op::Sqrt), "luminance" => Some(Op::Luminance),
+            "x" => Some(Op::X), "y" => Some(Op::Y), "z" => Some(Op::Z),
+            "fresnel" => Some(Op::Fresnel), "layer_weight" => Some(Op::LayerWeight),
+            _ => None
+        };
+        let binary = match op
+        {
+            "add" => Some(Op::Add), "sub" => Some(Op::Sub), "mul" => Some(Op::Mul), "div" => Some(Op::Div),
+            "min" => Some(Op::Min), "max" => Some(Op::Max), "pow" => Some(Op::Pow), "dot" => Some(Op::Dot),
+            _ => None
+        };
+        let ternary = match op
+        {
+            "clamp" => Some(Op::Clamp), "mix" => Some(Op::Mix), "combine" => Some(Op::Combine),
+            _ => None
+        };
+
+        if let Some(o) = unary
+        {
+            arity(1)?;
+            let a = self.input(names, &args[0])?;
+            return Ok(self.push(o, vec![a]));
+        }
+        if let Some(o) = binary
+        {
+            arity(2)?;
+            let (a, b) = (self.input(names, &args[0])?, self.input(names, &args[1])?);
+            return Ok(self.push(o, vec![a, b]));
+        }
+        if let Some(o) = ternary
+        {
+            arity(3)?;
+            let (a, b, c) = (self.input(names, &args[0])?, self.input(names, &args[1])?, self.input(names, &args[2])?);
+            return Ok(self.push(o, vec![a, b, c]));
+        }
+
+        match op
+        {
+            "const" => {
+                arity(1)?;
+                self.input(names, &args[0])
+            },
+            "uv" | "position" | "normal" | "view" => {
+                arity(0)?;
+                self.input(names, op)
+            },
+            "image" => {
+                if args.len() < 2 || args.len() > 3 || !args[0].starts_with('"') { return Err("expected image \"<path>\" <srgb|linear> [coords]".to_string()); }
+                let load_type = match args[1].as_str()
+                {
+                    "srgb" => DynamicTextureType::sRGB,
+                    "linear" => DynamicTextureType::Linear,
+                    t => return Err(format!("unknown image type {}", t))
+                };
+                let coords = self.input(names, args.get(2).map(|s| s.as_str()).unwrap_or("uv"))?;
+
+                self.images.push(Arc::new(DynamicTexture::new(args[0].trim_matches('"'), load_type)));
+                Ok(self.push(Op::Image(self.images.len() - 1), vec![coords]))
+            },
+            "uv_transform" => {
+                arity(6)?;
+                let coords = self.input(names, &args[0])?;
+                let (sx, sy, ox, oy, deg) = (parse_number(&args[1])?, parse_number(&args[2])?, parse_number(&args[3])?, parse_number(&args[4])?, parse_number(&args[5])?);
+                let (s, c) = deg.to_radians().sin_cos();
+
+                // rotate(scale(uv)) + offset
+                Ok(self.push(Op::UvTransform([c * sx, -s * sy, s * sx, c * sy, ox, oy]), vec![coords]))
+            },
+            "ramp" => {
+                if args.len() < 3 || args.len() % 2 == 0 { return Err("expected ramp <t> <pos> <color> ...".to_string()); }
+                let t = self.input(names, &args[0])?;
+
+                let mut stops = std::vec::Vec::new();
+                for pair in args[1..].chunks(2)
+                {
+                    let color = if pair[1].starts_with('[') { parse_vector(&pair[1])? } else { Vec4::from(parse_number(&pair[1])?) };
+                    stops.push((parse_number(&pair[0])?, color));
+                }
+                stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+                self.ramps.push(Arc::new(stops));
+                Ok(self.push(Op::Ramp(self.ramps.len() - 1), vec![t]))
+            },
+            "noise" | "checker" => {
+                arity(2)?;
+                let coords = self.input(names, &args[0])?;
+                let scale = parse_number(&args[1])?;
+                Ok(self.push(if op == "noise" { Op::Noise(scale) } else { Op::Checker(scale) }, vec![coords]))
+            },
+            _ => Err(format!("unknown op {}", op))
+        }
+    }
+
+    pub fn outputs(&self) -> std::vec::Vec<&str>
+    {
+        self.outputs.keys().map(|k| k.as_str()).collect()
+    }
+
+    pub fn has_output(&self, slot: &str) -> bool
+    {
+        self.outputs.contains_key(slot)
+    }
+
+    // nodes are only ever defined after their inputs, so index order is a topological order
+    fn reachable(nodes: &[Node], root: usize) -> std::vec::Vec<bool>
+    {
+        let mut used = vec![false; nodes.len()];
+        used[root] = true;
+        for i in (0..root + 1).rev()
+        {
+            if used[i]
+            {
+                for input in &nodes[i].inputs { used[*input] = true; }
+            }
+        }
+        used
+    }
+
+    pub fn compile(&self, slot: &str) -> Result<ShaderGraph, String>
+    {
+        let root = *self.outputs.get(slot).ok_or(format!("graph has no output {}", slot))?;
+        let mut nodes = self.nodes.clone();
+        let used = GraphSource::reachable(&nodes, root);
+
+        // fold nodes that only depend on constants
+        let probe = ShaderGraph{code: vec![], output: 0, images: vec![], ramps: self.ramps.clone()};
+        let hit = HitInfo::new();
+        for i in 0..root + 1
+        {
+            let foldable = match nodes[i].op
+            {
+                Op::Const(_) | Op::Uv | Op::Position | Op::Normal | Op::View | Op::Image(_) | Op::Fresnel | Op::LayerWeight => false,
+                _ => true
+            };
+
+            if used[i] && foldable && nodes[i].inputs.iter().all(|j| if let Op::Const(_) = nodes[*j].op { true } else { false })
+            {
+                let mut values = [Vec4::zero(); 3];
+                for (k, j) in nodes[i].inputs.iter().enumerate()
+                {
+                    if let Op::Const(v) = nodes[*j].op { values[k] = v; }
+                }
+
+                nodes[i] = Node{op: Op::Const(probe.eval_op(nodes[i].op, &values, &hit)), inputs: vec![]};
+            }
+        }
+
+        // one register per remaining node
+        let used = GraphSource::reachable(&nodes, root);
+        let mut register = vec![0; nodes.len()];
+        let mut code = std::vec::Vec::new();
+        for i in 0..root + 1
+        {
+            if !used[i] { continue; }
+            if code.len() >= MAX_REGISTERS
+            {
+                return Err(format!("output {} needs more than {} nodes", slot, MAX_REGISTERS));
+            }
+
+            let mut inputs = [0; 3];
+            for (k, j) in nodes[i].inputs.iter().enumerate() { inputs[k] = register[*j]; }
+
+            register[i] = code.len();
+            code.push(Instr{op: nodes[i].op, dst: code.len(), inputs: inputs});
+        }
+
+        Ok(ShaderGraph{code: code, output: register[root], images: self.images.clone(), ramps: self.ramps.clone()})
+    }
+}
+
+//######################################################################
+// ShaderGraph
+//######################################################################
+
+impl ShaderGraph
+{
+    // magenta if the file or output is broken
+    pub fn texture<P>(path: P, slot: &str) -> Texture
+    where P: AsRef<std::path::Path>
+    {
+        match GraphSource::from_path(path).and_then(|source| source.compile(slot))
+        {
+            Ok(graph) => Texture::Graph{tex: graph},
+            Err(e) => {
+                eprintln!("{}", e);
+                ConstantTexture::from(1.0, 0.0, 1.0).texture()
+            }
+        }
+    }
+
+    pub fn instruction_count(&self) -> usize
+    {
+        self.code.len()
+    }
+
+    fn eval_op(&self, op: Op, x: &[Vec4; 3], hit: &HitInfo) -> Vec4
+    {
+        let (a, b, c) = (x[0], x[1], x[2]);
+        let map = |f: &dyn Fn(f32) -> f32| Vec4::new(f(a.x()), f(a.y()), f(a.z()), f(a.w()));
+
+        match op
+        {
+            Op::Const(v) => {v},
+            Op::Uv => {Vec4::from3(hit.u, hit.v, 0.0)},
+            Op::Position => {hit.point},
+            Op::Normal => {hit.normal},
+            Op::View => {hit.view},
+            Op::Image(i) => {
+                let mut at = *hit;
+                at.u = a.x();
+                at.v = a.y();
+                self.images[i].sample(&at)
+            },
+            Op::UvTransform(m) => {Vec4::from3(m[0] * a.x() + m[1] * a.y() + m[4], m[2] * a.x() + m[3] * a.y() + m[5], 0.0)},
+            Op::Abs => {a.abs()},
+            Op::Sin => {map(&|v| v.sin())},
+            Op::Cos => {map(&|v| v.cos())},
+            Op::Floor => {map(&|v| v.floor())},
+            Op::Fract => {map(&|v| v - v.floor())},
+            Op::Sqrt => {map(&|v| v.max(0.0).sqrt())},
+            Op::Luminance => {Vec4::from(a.luminance())},
+            Op::X => {Vec4::from(a.x())},
+            Op::Y => {Vec4::from(a.y())},
+            Op::Z => {Vec4::from(a.z())},
+            Op::Add => {a + b},
+            Op::Sub => {a - b},
+            Op::Mul => {a * b},
+            Op::Div => {Vec4::new(a.x() / b.x(), a.y() / b.y(), a.z() / b.z(), a.w() / b.w())},
+            Op::Min => {a.min(&b)},
+            Op::Max => {a.max(&b)},
+            Op::Pow => {Vec4::new(a.x().powf(b.x()), a.y().powf(b.y()), a.z().powf(b.z()), a.w().powf(b.w()))},
+            Op::Dot => {Vec4::from(a.dot3(&b))},
+            Op::Clamp => {a.clamp(&b, &c)},
+            Op::Mix => {a.lerp(&b, c.x())},
+            Op::Combine => {Vec4::from3(a.x(), b.x(), c.x())},
+            Op::Fresnel => {
+                let cos = hit.view.dot(&hit.normal);
+                let eta = if cos >= 0.0 { a.x() } else { 1.0 / a.x() };
+                Vec4::from(fresnel_dielectric(cos, eta))
+            },
+            // https://docs.blender.org/manual/en/latest/render/shader_nodes/input/layer_weight.html
+            Op::LayerWeight => {
+                let blend = a.x().max(0.0).min(1.0 - 1e-5);
+                let cos = hit.view.dot(&hit.normal);
+
+                let eta = 1.0 / (1.0 - blend);
+                let fresnel = fresnel_dielectric(cos, if cos >= 0.0 { eta } else { 1.0 / eta });
+
+                let exponent = if blend < 0.5 { 2.0 * blend } else { 0.5 / (1.0 - blend) };
+                let facing = 1.0 - cos.abs().powf(exponent);
+
+                Vec4::from3(fresnel, facing, 0.0)
+            },
+            Op::Ramp(i) => {eval_ramp(&self.ramps[i], a.x())},
+            Op::Noise(scale) => {Vec4::from(0.5 + 0.5 * perlin(&(a * scale), 0))},
+            Op::Checker(scale) => {
+                let p = a * scale;
+                Vec4::from(((p.x().floor() as i64 + p.y().floor() as i64 + p.z().floor() as i64) & 1) as f32)
+            }
+        }
+    }
+}
+
+impl Sample for ShaderGraph
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let mut registers = [Vec4::zero(); MAX_REGISTERS];
+
+        for instr in &self.code
+        {
+            let inputs = [registers[instr.inputs[0]], registers[instr.inputs[1]], registers[instr.inputs[2]]];
+            registers[instr.dst] = self.eval_op(instr.op, &inputs, hit);
+        }
+
+        registers[self.output]
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn comments_outside_quotes()
+    {
+        assert_eq!(tokenize(r#"tex = image "tex#1.png" srgb # albedo"#).unwrap(), vec!["tex", "=", "image", "\"tex#1.png\"", "srgb"]);
+        assert_eq!(tokenize("a = add 1 2#3").unwrap(), vec!["a", "=", "add", "1", "2"]);
+        assert!(tokenize("# only a comment").unwrap().is_empty());
+        assert!(tokenize(r#"a = image "tex#1.png"#).is_err());
+    }
+
+    #[test]
+    fn parse_with_comments()
+    {
+        let source = GraphSource::parse("# header\na = add 0.25 0.5 # sum\noutput roughness a # done\n").unwrap();
+        assert!(source.has_output("roughness"));
+        assert!(source.compile("roughness").unwrap().sample(&HitInfo::new()) == Vec4::from(0.75));
+    }
+}
@@ -1,6 +1,8 @@
 use super::ray::*;
 use super::vec::*;
 
+pub const RAY_EPSILON: f32 = 1e-4;
+
 #[derive(Copy, Clone)]
 pub struct HitInfo
 {
@@ -9,14 +11,29 @@ pub struct HitInfo
     pub depth: f32,
     pub u: f32,
     pub v: f32,
-    pub material: u32 // supplied by primitive
+    pub uv_scale: f32, // world units per uv unit, supplied by primitives with uvs, zero disables mip mapping
+    pub footprint: f32, // ray cone width in uv units, supplied by scene
+    pub cone_width: f32,
+    pub cone_spread: f32,
+    pub view: Vec4, // unit vector towards the ray origin, supplied by scene
+    pub tangent: Vec4, // dp/du, zero if the primitive has no uvs
+    pub bitangent: Vec4, // dp/dv
+    pub material: u32, // supplied by primitive
+    pub object: u32 // supplied by scene
 }
 
 impl HitInfo
 {
     pub fn new() -> HitInfo
     {
-        HitInfo{point: Vec4::from(0.0), normal: Vec4::from(0.0), depth: 0.0, material: 0, u: 0.0, v: 0.0}
+        HitInfo{point: Vec4::from(0.0), normal: Vec4::from(0.0), depth: 0.0, material: 0, object: 0, u: 0.0, v: 0.0, uv_scale: 0.0, footprint: 0.0, cone_width: 0.0, cone_spread: 0.0, view: Vec4::zero(), tangent: Vec4::zero(), bitangent: Vec4::zero()}
+    }
+
+    // offset the origin to the side of the surface the new direction points to, avoids self intersection
+    pub fn spawn_ray(&self, dir: &Vec4) -> Ray
+    {
+        let offset = if dir.dot(&self.normal) > 0.0 { self.normal } else { -self.normal };
+        Ray::with_cone(self.point + offset * RAY_EPSILON, *dir, self.cone_width, self.cone_spread)
     }
 }
 
@@ -0,0 +1,787 @@
+use super::vec::*;
+use super::ray::*;
+use super::hit::*;
+use super::scene::*;
+use super::bsdf::*;
+use super::random::*;
+use super::settings::*;
+use super::material::*;
+use super::microfacet::Frame;
+use super::camera::Camera;
+use super::bdpt::BidirectionalPathTracer;
+use super::photon::PhotonMapper;
+use super::medium::*;
+use super::light::LightSample;
+
+use std::marker::{Send, Sync};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// rays are unbounded, the environment is at infinity
+pub const MAX_DISTANCE: f32 = std::f32::INFINITY;
+
+// contributions to arbitrary pixels, e.g. from light paths connected to the camera, shared by all threads
+pub struct Film
+{
+    width: u32,
+    height: u32,
+    pixels: std::vec::Vec<AtomicU32> // bits of the rgb sums
+}
+
+// per path state, carried forward bounce by bounce
+pub struct RayInfo
+{
+    pub ray: Ray,
+    pub depth: u32,
+    pub diffuse_depth: u32,
+    pub specular_depth: u32,
+    pub transmission_depth: u32,
+    pub volume_depth: u32,
+    pub medium: Option<u32>, // bounded volume the ray travels in, None for the scene fog
+    pub throughput: Vec4,
+    pub radiance: Vec4,
+    pub film: Option<Arc<Film>> // not cleared by reset, splats are dropped without one
+}
+
+impl Film
+{
+    pub fn new(_width: u32, _height: u32) -> Film
+    {
+        Film{width: _width, height: _height, pixels: (0.._width * _height * 3).map(|_| AtomicU32::new(0)).collect()}
+    }
+
+    // pixel centers lie on integer coordinates like the camera rays
+    pub fn add(&self, x: f32, y: f32, color: &Vec4)
+    {
+        let (x, y) = ((x + 0.5).floor(), (y + 0.5).floor());
+        if !(x >= 0.0 && y >= 0.0 && (x as u32) < self.width && (y as u32) < self.height)
+        {
+            return;
+        }
+
+        let idx = ((y as u32 * self.width + x as u32) * 3) as usize;
+        for c in 0..3
+        {
+            let value = color.get(c);
+            if value == 0.0 { continue; }
+
+            let pixel = &self.pixels[idx + c];
+            let mut current = pixel.load(Ordering::Relaxed);
+            loop
+            {
+                let sum = (f32::from_bits(current) + value).to_bits();
+                match pixel.compare_exchange_weak(current, sum, Ordering::Relaxed, Ordering::Relaxed)
+                {
+                    Ok(_) => break,
+                    Err(actual) => current = actual
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Vec4
+    {
+        let idx = ((y * self.width + x) * 3) as usize;
+        let c = |i: usize| f32::from_bits(self.pixels[idx + i].load(Ordering::Relaxed));
+        Vec4::from3(c(0), c(1), c(2))
+    }
+}
+
+impl RayInfo
+{
+    #[inline]
+    pub fn new() -> RayInfo
+    {
+        RayInfo{
+            ray: Ray::invalid(),
+            depth: 0,
+            diffuse_depth: 0,
+            specular_depth: 0,
+            transmission_depth: 0,
+            volume_depth: 0,
+            medium: None,
+            throughput: Vec4::one(),
+            radiance: Vec4::zero(),
+            film: None
+        }
+    }
+
+    #[inline]
+    pub fn reset(&mut self, ray: &Ray)
+    {
+        self.ray = *ray;
+        self.depth = 0;
+        self.diffuse_depth = 0;
+        self.specular_depth = 0;
+        self.transmission_depth = 0;
+        self.volume_depth = 0;
+        self.medium = None; // cameras are assumed to be outside of bounded volumes
+        self.throughput = Vec4::one();
+        self.radiance = Vec4::zero();
+    }
+
+    #[inline]
+    pub fn add_emission(&mut self, emission: &Vec4)
+    {
+        self.radiance += self.throughput * emission;
+    }
+
+    // counts the bounce for the sampled lobe, returns false if a depth limit is exceeded
+    #[inline]
+    pub fn bounce(&mut self, flags: BsdfFlags, settings: &TraceSettings) -> bool
+    {
+        if settings.max_depth > 0 && self.depth >= settings.max_depth
+        {
+            return false;
+        }
+
+        if flags.contains(BsdfFlags::TRANSMISSION)
+        {
+            self.transmission_depth += 1;
+            self.transmission_depth <= settings.max_transmission_depth
+        }
+        else if flags.intersects(BsdfFlags::GLOSSY | BsdfFlags::SPECULAR)
+        {
+            self.specular_depth += 1;
+            self.specular_depth <= settings.max_specular_depth
+        }
+        else
+        {
+            self.diffuse_depth += 1;
+            self.diffuse_depth <= settings.max_diffuse_depth
+        }
+    }
+
+    // no bounce or scattering event yet, medium boundaries don't count
+    #[inline]
+    pub fn is_primary(&self) -> bool
+    {
+        self.diffuse_depth + self.specular_depth + self.transmission_depth + self.volume_depth == 0
+    }
+
+    // counts a scattering event inside a medium, returns false if a depth limit is exceeded
+    #[inline]
+    pub fn scatter(&mut self, settings: &TraceSettings) -> bool
+    {
+        if settings.max_depth > 0 && self.depth >= settings.max_depth
+        {
+            return false;
+        }
+
+        self.volume_depth += 1;
+        self.volume_depth <= settings.max_volume_depth
+    }
+
+    // unbiased path termination based on throughput, returns false if the path was terminated
+    #[inline]
+    pub fn russian_roulette(&mut self, settings: &TraceSettings) -> bool
+    {
+        if self.depth < settings.rr_depth
+        {
+            return true;
+        }
+
+        let survival = self.throughput.max_elem3().min(settings.rr_max_survival);
+
+        if random_f32() >= survival
+        {
+            return false;
+        }
+
+        self.throughput /= survival;
+        true
+    }
+}
+
+pub trait Integrator: Send + Sync
+{
+    // called once per scene before tracing, e.g. to build photon maps
+    fn preprocess(&mut self, _scn: &Scene) {}
+
+    // radiance arriving along info.ray, path statistics are left in info
+    fn li(&self, scn: &Scene, cam: &dyn Camera, info: &mut RayInfo) -> Vec4;
+}
+
+pub fn create_integrator(settings: &TraceSettings) -> Box<dyn Integrator>
+{
+    match settings.integrator
+    {
+        IntegratorType::Path => Box::new(PathTracer::new(settings)),
+        IntegratorType::NeePath => Box::new(NeePathTracer::new(settings)),
+        IntegratorType::AmbientOcclusion => Box::new(AmbientOcclusion::new(settings.ao_samples, settings.ao_distance)),
+        IntegratorType::DirectLighting => Box::new(DirectLighting::new(settings)),
+        IntegratorType::Bidirectional => Box::new(BidirectionalPathTracer::new(settings)),
+        IntegratorType::PhotonMap => Box::new(PhotonMapper::new(settings)),
+        IntegratorType::VolumePath => Box::new(VolumePathTracer::new(settings)),
+        IntegratorType::Debug{mode} => Box::new(DebugIntegrator::new(mode))
+    }
+}
+
+#[inline]
+pub fn power_heuristic(pdf: f32, other_pdf: f32) -> f32
+{
+    let a = pdf * pdf;
+    let b = other_pdf * other_pdf;
+    if a + b > 0.0 { a / (a + b) } else { 0.0 }
+}
+
+// mis weight of a light sample against bsdf or phase function sampling, delta lights can only be sampled
+#[inline]
+pub fn light_weight(light: &LightSample, other_pdf: f32) -> f32
+{
+    if light.delta { 1.0 } else { power_heuristic(light.pdf, other_pdf) }
+}
+
+// shadow rays towards directional lights end at the scene bounds
+#[inline]
+pub fn shadow_distance(light: &LightSample) -> f32
+{
+    (light.dist * (1.0 - 1e-3)).min(MAX_DISTANCE)
+}
+
+// one light sample towards an emissive object or light, weighted against bsdf sampling
+pub fn sample_direct_light(scn: &Scene, mat: &Material, hit: &HitInfo, wo: &Vec4) -> Vec4
+{
+    let light = match scn.sample_light(&hit.point)
+    {
+        Some(light) if light.pdf > 0.0 => light,
+        _ => return Vec4::zero()
+    };
+
+    let (f, pdf) = mat.eval_pdf(&light.wi, wo, hit);
+    if f == Vec4::zero() || light.le == Vec4::zero() { return Vec4::zero(); }
+
+    if scn.occluded(&hit.spawn_ray(&light.wi), shadow_distance(&light))
+    {
+        return Vec4::zero();
+    }
+
+    let weight = light_weight(&light, pdf);
+
+    f * light.le * light.wi.dot(&hit.normal).abs() * weight / light.pdf
+}
+
+//######################################################################
+// PathTracer
+//######################################################################
+
+// naive path tracer, only picks up emission by hitting it
+#[derive(Copy, Clone)]
+pub struct PathTracer
+{
+    pub settings: TraceSettings
+}
+
+impl PathTracer
+{
+    pub fn new(_settings: &TraceSettings) -> PathTracer
+    {
+        PathTracer{settings: *_settings}
+    }
+
+    // return true if terminated
+    #[inline]
+    pub fn trace(&self, r: &mut RayInfo, scn: &Scene) -> bool
+    {
+        let mut hit = HitInfo::new();
+        r.depth += 1;
+
+        let found = scn.hit(&r.ray, &mut hit, 0.0, MAX_DISTANCE);
+
+        if let Some(medium) = scn.get_medium(r.medium)
+        {
+            let ms = medium.sample(&r.ray, if found { hit.depth } else { MAX_DISTANCE });
+            r.throughput *= ms.weight;
+
+            if ms.scattered
+            {
+                if !r.scatter(&self.settings) { return true; }
+
+                // phase function sampling is perfect, the weight is 1
+                let (wi, _) = medium.phase().sample(&-r.ray.direction.norm());
+                r.ray = Ray::new(ms.point, wi);
+                return !r.russian_roulette(&self.settings);
+            }
+        }
+
+        r.add_emission(&scn.light_emission(&r.ray, if found { hit.depth } else { std::f32::INFINITY }, &r.ray.origin, r.is_primary(), |_, _| 1.0));
+
+        if found
+        {
+            let mat = scn.get_mat(hit.material);
+            let wo = -r.ray.direction.norm();
+
+            if mat.is_interface()
+            {
+                r.medium = scn.next_medium(&hit, &-wo, r.medium);
+                r.ray = hit.spawn_ray(&-wo);
+                return false;
+            }
+
+            r.add_emission(&mat.emission(&wo, &hit));
+
+            match mat.sample(&wo, &hit)
+            {
+                Some(s) if r.bounce(s.flags, &self.settings) =>
+                {
+                    r.throughput *= s.weight(&hit.normal);
+                    r.medium = scn.next_medium(&hit, &s.wi, r.medium);
+                    r.ray = hit.spawn_ray(&s.wi);
+                    !r.russian_roulette(&self.settings)
+                },
+                _ => true
+            }
+        }
+        else // missed / escaped scene
+        {
+            r.add_emission(&scn.get_miss_mat().emission(&-r.ray.direction.norm(), &hit));
+            true
+        }
+    }
+}
+
+impl Integrator for PathTracer
+{
+    fn li(&self, scn: &Scene, _cam: &dyn Camera, info: &mut RayInfo) -> Vec4
+    {
+        while !self.trace(info, scn) {}
+        info.radiance
+    }
+}
+
+//######################################################################
+// NeePathTracer
+//######################################################################
+
+// path tracer with next event estimation, emission is combined via multiple importance sampling
+#[derive(Copy, Clone)]
+pub struct NeePathTracer
+{
+    pub settings: TraceSettings
+}
+
+impl NeePathTracer
+{
+    pub fn new(_settings: &TraceSettings) -> NeePathTracer
+    {
+        NeePathTracer{settings: *_settings}
+    }
+}
+
+impl Integrator for NeePathTracer
+{
+    fn li(&self, scn: &Scene, _cam: &dyn Camera, r: &mut RayInfo) -> Vec4
+    {
+        // camera rays and delta bounces can't be light sampled, their emission is not weighted
+        let mut delta_bounce = true;
+        let mut bsdf_pdf = 0.0;
+
+        loop
+        {
+            let mut hit = HitInfo::new();
+            r.depth += 1;
+
+            let found = scn.hit(&r.ray, &mut hit, 0.0, MAX_DISTANCE);
+
+            let lights = scn.light_emission(&r.ray, if found { hit.depth } else { std::f32::INFINITY }, &r.ray.origin, r.is_primary(),
+                |_, pdf| if delta_bounce { 1.0 } else { power_heuristic(bsdf_pdf, pdf) });
+            r.add_emission(&lights);
+
+            if !found
+            {
+                r.add_emission(&scn.get_miss_mat().emission(&-r.ray.direction.norm(), &hit));
+                break;
+            }
+
+            let mat = scn.get_mat(hit.material);
+            let wo = -r.ray.direction.norm();
+
+            let mut emission = mat.emission(&wo, &hit);
+            if !delta_bounce && emission != Vec4::zero()
+            {
+                emission *= power_heuristic(bsdf_pdf, scn.light_pdf(&r.ray.origin, &-wo, hit.object));
+            }
+            r.add_emission(&emission);
+
+            if mat.flags(&hit).is_non_delta()
+            {
+                let direct = sample_direct_light(scn, mat, &hit, &wo);
+                r.add_emission(&direct);
+            }
+
+            match mat.sample(&wo, &hit)
+            {
+                Some(s) if r.bounce(s.flags, &self.settings) =>
+                {
+                    r.throughput *= s.weight(&hit.normal);
+                    r.ray = hit.spawn_ray(&s.wi);
+                    delta_bounce = s.flags.is_delta();
+                    bsdf_pdf = s.pdf;
+
+                    if !r.russian_roulette(&self.settings)
+                    {
+                        break;
+                    }
+                },
+                _ => break
+            }
+        }
+
+        r.radiance
+    }
+}
+
+//######################################################################
+// DirectLighting
+//######################################################################
+
+// emission and single bounce direct illumination, follows delta bounces only
+#[derive(Copy, Clone)]
+pub struct DirectLighting
+{
+    pub settings: TraceSettings
+}
+
+impl DirectLighting
+{
+    pub fn new(_settings: &TraceSettings) -> DirectLighting
+    {
+        DirectLighting{settings: *_settings}
+    }
+
+    // bsdf sampled counterpart of sample_direct_light, also picks up the miss material
+    fn sample_direct_bsdf(&self, scn: &Scene, mat: &Material, hit: &HitInfo, wo: &Vec4) -> Vec4
+    {
+        let s = match mat.sample(wo, hit)
+        {
+            Some(s) if !s.flags.is_delta() => s,
+            _ => return Vec4::zero()
+        };
+
+        let ray = hit.spawn_ray(&s.wi);
+        let mut light_hit = HitInfo::new();
+
+        let found = scn.hit(&ray, &mut light_hit, 0.0, MAX_DISTANCE);
+        let mut le = scn.light_emission(&ray, if found { light_hit.depth } else { std::f32::INFINITY }, &ray.origin, false, |_, pdf| power_heuristic(s.pdf, pdf));
+
+        if found
+        {
+            let light_mat = scn.get_mat(light_hit.material);
+            if light_mat.is_emissive()
+            {
+                let weight = power_heuristic(s.pdf, scn.light_pdf(&ray.origin, &s.wi, light_hit.object));
+                le += light_mat.emission(&-s.wi, &light_hit) * weight;
+            }
+        }
+        else
+        {
+            le += scn.get_miss_mat().emission(&-s.wi, &light_hit);
+        }
+
+        s.weight(&hit.normal) * le
+    }
+}
+
+impl Integrator for DirectLighting
+{
+    fn li(&self, scn: &Scene, _cam: &dyn Camera, r: &mut RayInfo) -> Vec4
+    {
+        loop
+        {
+            let mut hit = HitInfo::new();
+            r.depth += 1;
+
+            // only camera rays and delta bounces get here, lights can't be sampled for them
+            let found = scn.hit(&r.ray, &mut hit, 0.0, MAX_DISTANCE);
+            r.add_emission(&scn.light_emission(&r.ray, if found { hit.depth } else { std::f32::INFINITY }, &r.ray.origin, r.is_primary(), |_, _| 1.0));
+
+            if !found
+            {
+                r.add_emission(&scn.get_miss_mat().emission(&-r.ray.direction.norm(), &hit));
+                break;
+            }
+
+            let mat = scn.get_mat(hit.material);
+            let wo = -r.ray.direction.norm();
+            r.add_emission(&mat.emission(&wo, &hit));
+
+            if mat.flags(&hit).is_non_delta()
+            {
+                let direct = sample_direct_light(scn, mat, &hit, &wo) + self.sample_direct_bsdf(scn, mat, &hit, &wo);
+                r.add_emission(&direct);
+                break;
+            }
+
+            match mat.sample(&wo, &hit)
+            {
+                Some(s) if r.bounce(s.flags, &self.settings) =>
+                {
+                    r.throughput *= s.weight(&hit.normal);
+                    r.ray = hit.spawn_ray(&s.wi);
+                },
+                _ => break
+            }
+        }
+
+        r.radiance
+    }
+}
+
+//######################################################################
+// VolumePathTracer
+//######################################################################
+
+// next event estimation path tracer that scatters in media, shadow rays are attenuated by transmittance
+#[derive(Copy, Clone)]
+pub struct VolumePathTracer
+{
+    pub settings: TraceSettings
+}
+
+impl VolumePathTracer
+{
+    pub fn new(_settings: &TraceSettings) -> VolumePathTracer
+    {
+        VolumePathTracer{settings: *_settings}
+    }
+
+    // light sample from a point in a medium, weighted against phase function sampling
+    fn sample_light_medium(&self, scn: &Scene, phase: &HenyeyGreenstein, point: &Vec4, wo: &Vec4, medium: Option<u32>) -> Vec4
+    {
+        let light = match scn.sample_light(point)
+        {
+            Some(light) if light.pdf > 0.0 && light.le != Vec4::zero() => light,
+            _ => return Vec4::zero()
+        };
+
+        let p = phase.eval(wo, &light.wi);
+
+        let tr = scn.transmittance(&Ray::new(*point, light.wi), shadow_distance(&light), medium);
+        if tr == Vec4::zero() { return Vec4::zero(); }
+
+        light.le * tr * p * light_weight(&light, p) / light.pdf
+    }
+
+    // sample_direct_light with transmittance through media and their boundaries
+    fn sample_light_surface(&self, scn: &Scene, mat: &Material, hit: &HitInfo, wo: &Vec4, medium: Option<u32>) -> Vec4
+    {
+        let light = match scn.sample_light(&hit.point)
+        {
+            Some(light) if light.pdf > 0.0 && light.le != Vec4::zero() => light,
+            _ => return Vec4::zero()
+        };
+
+        let wi = light.wi;
+        let (f, pdf) = mat.eval_pdf(&wi, wo, hit);
+        if f == Vec4::zero() { return Vec4::zero(); }
+
+        let tr = scn.transmittance(&hit.spawn_ray(&wi), shadow_distance(&light), scn.next_medium(hit, &wi, medium));
+        if tr == Vec4::zero() { return Vec4::zero(); }
+
+        let weight = light_weight(&light, pdf);
+
+        f * light.le * tr * wi.dot(&hit.normal).abs() * weight / light.pdf
+    }
+}
+
+impl Integrator for VolumePathTracer
+{
+    fn li(&self, scn: &Scene, _cam: &dyn Camera, r: &mut RayInfo) -> Vec4
+    {
+        let mut delta_bounce = true;
+        let mut scatter_pdf = 0.0;
+        // last real scattering vertex, medium boundaries are skipped
+        let mut scatter_origin = r.ray.origin;
+
+        loop
+        {
+            let mut hit = HitInfo::new();
+            r.depth += 1;
+
+            let found = scn.hit(&r.ray, &mut hit, 0.0, MAX_DISTANCE);
+
+            if let Some(medium) = scn.get_medium(r.medium)
+            {
+                let ms = medium.sample(&r.ray, if found { hit.depth } else { MAX_DISTANCE });
+                r.throughput *= ms.weight;
+
+                if r.throughput == Vec4::zero()
+                {
+                    break;
+                }
+
+                if ms.scattered
+                {
+                    if !r.scatter(&self.settings) { break; }
+
+                    let wo = -r.ray.direction.norm();
+                    let direct = self.sample_light_medium(scn, medium.phase(), &ms.point, &wo, r.medium);
+                    r.add_emission(&direct);
+
+                    let (wi, pdf) = medium.phase().sample(&wo);
+                    r.ray = Ray::new(ms.point, wi);
+                    scatter_origin = ms.point;
+                    delta_bounce = false;
+                    scatter_pdf = pdf;
+
+                    if !r.russian_roulette(&self.settings)
+                    {
+                        break;
+                    }
+
+                    continue;
+                }
+            }
+
+            let lights = scn.light_emission(&r.ray, if found { hit.depth } else { std::f32::INFINITY }, &scatter_origin, r.is_primary(),
+                |_, pdf| if delta_bounce { 1.0 } else { power_heuristic(scatter_pdf, pdf) });
+            r.add_emission(&lights);
+
+            if !found
+            {
+                r.add_emission(&scn.get_miss_mat().emission(&-r.ray.direction.norm(), &hit));
+                break;
+            }
+
+            let mat = scn.get_mat(hit.material);
+            let wo = -r.ray.direction.norm();
+
+            if mat.is_interface()
+            {
+                r.medium = scn.next_medium(&hit, &-wo, r.medium);
+                r.ray = hit.spawn_ray(&-wo);
+                continue;
+            }
+
+            let mut emission = mat.emission(&wo, &hit);
+            if !delta_bounce && emission != Vec4::zero()
+            {
+                emission *= power_heuristic(scatter_pdf, scn.light_pdf(&scatter_origin, &-wo, hit.object));
+            }
+            r.add_emission(&emission);
+
+            if mat.flags(&hit).is_non_delta()
+            {
+                let direct = self.sample_light_surface(scn, mat, &hit, &wo, r.medium);
+                r.add_emission(&direct);
+            }
+
+            match mat.sample(&wo, &hit)
+            {
+                Some(s) if r.bounce(s.flags, &self.settings) =>
+                {
+                    r.throughput *= s.weight(&hit.normal);
+                    r.medium = scn.next_medium(&hit, &s.wi, r.medium);
+                    r.ray = hit.spawn_ray(&s.wi);
+                    scatter_origin = hit.point;
+                    delta_bounce = s.flags.is_delta();
+                    scatter_pdf = s.pdf;
+
+                    if !r.russian_roulette(&self.settings)
+                    {
+                        break;
+                    }
+                },
+                _ => break
+            }
+        }
+
+        r.radiance
+    }
+}
+
+//######################################################################
+// AmbientOcclusion
+//######################################################################
+
+#[derive(Copy, Clone)]
+pub struct AmbientOcclusion
+{
+    pub samples: u32,
+    pub distance: f32
+}
+
+impl AmbientOcclusion
+{
+    pub fn new(_samples: u32, _distance: f32) -> AmbientOcclusion
+    {
+        AmbientOcclusion{samples: _samples.max(1), distance: _distance}
+    }
+}
+
+impl Integrator for AmbientOcclusion
+{
+    fn li(&self, scn: &Scene, _cam: &dyn Camera, r: &mut RayInfo) -> Vec4
+    {
+        let mut hit = HitInfo::new();
+        r.depth += 1;
+
+        if !scn.hit(&r.ray, &mut hit, 0.0, MAX_DISTANCE)
+        {
+            return Vec4::one();
+        }
+
+        let n = if r.ray.direction.dot(&hit.normal) < 0.0 { hit.normal } else { -hit.normal };
+        let frame = Frame::new(&n);
+
+        let mut visible = 0;
+        for _ in 0..self.samples
+        {
+            let dir = frame.to_world(&random_cosine_hemisphere());
+            if !scn.occluded(&hit.spawn_ray(&dir), self.distance)
+            {
+                visible += 1;
+            }
+        }
+
+        r.depth += self.samples;
+        Vec4::from(visible as f32 / self.samples as f32)
+    }
+}
+
+//######################################################################
+// DebugIntegrator
+//######################################################################
+
+#[derive(Copy, Clone)]
+pub struct DebugIntegrator
+{
+    pub mode: DebugMode
+}
+
+impl DebugIntegrator
+{
+    pub fn new(_mode: DebugMode) -> DebugIntegrator
+    {
+        DebugIntegrator{mode: _mode}
+    }
+}
+
+impl Integrator for DebugIntegrator
+{
+    fn li(&self, scn: &Scene, _cam: &dyn Camera, r: &mut RayInfo) -> Vec4
+    {
+        r.depth += 1;
+
+        if self.mode == DebugMode::Cost
+        {
+            // there is no acceleration structure yet, the number of pierced primitives is the closest stand in
+            let cost = (scn.hit_count(&r.ray, 0.0, MAX_DISTANCE) as f32 / 8.0).min(1.0);
+            return Vec4::from3(cost, 0.0, 1.0 - cost);
+        }
+
+        let mut hit = HitInfo::new();
+        if !scn.hit(&r.ray, &mut hit, 0.0, MAX_DISTANCE)
+        {
+            return Vec4::zero();
+        }
+
+        match self.mode
+        {
+            DebugMode::Normal => (hit.normal + 1.0) * 0.5,
+            DebugMode::Uv => Vec4::from3(hit.u, hit.v, 0.0),
+            DebugMode::Depth => Vec4::from(1.0 / (1.0 + hit.depth)),
+            DebugMode::Cost => Vec4::zero()
+        }
+    }
+}
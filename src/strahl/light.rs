@@ -0,0 +1,976 @@
+use super::vec::*;
+use super::ray::*;
+use super::random::*;
+use super::microfacet::Frame;
+use super::quaternion::*;
+use super::distribution::*;
+use super::texture::{DynamicTexture, DynamicTextureType, FilterMode, WrapMode, load_linear};
+
+use std::f32::consts::PI;
+
+// https://www.pbr-book.org/3ed-2018/Light_Sources
+
+// incident illumination at a reference point
+#[derive(Copy, Clone)]
+pub struct LightSample
+{
+    pub point: Vec4,
+    pub normal: Vec4, // zero for lights without a surface
+    pub wi: Vec4, // from the reference point towards the light
+    pub dist: f32, // infinite for directional lights
+    pub le: Vec4, // radiance, for delta lights the irradiance arriving at the reference point
+    pub pdf: f32, // w.r.t. solid angle, 1.0 for delta lights
+    pub delta: bool
+}
+
+// ray leaving a light, used for light tracing
+#[derive(Copy, Clone)]
+pub struct EmissionSample
+{
+    pub point: Vec4,
+    pub normal: Vec4, // zero for lights without a surface
+    pub dir: Vec4,
+    pub le: Vec4, // radiance or intensity for point lights
+    pub pdf_pos: f32,
+    pub pdf_dir: f32
+}
+
+impl EmissionSample
+{
+    // flux carried by the sampled ray
+    pub fn power(&self) -> Vec4
+    {
+        let cos = if self.normal == Vec4::zero() { 1.0 } else { self.normal.dot(&self.dir).abs() };
+        self.le * cos / (self.pdf_pos * self.pdf_dir)
+    }
+}
+
+pub trait Emitter
+{
+    fn sample_li(&self, origin: &Vec4) -> Option<LightSample>;
+    // solid angle pdf of sample_li producing dir, zero for delta lights
+    fn pdf_li(&self, origin: &Vec4, dir: &Vec4) -> f32;
+    // distance and radiance if r hits the light before max
+    fn le(&self, r: &Ray, max: f32) -> Option<(f32, Vec4)>;
+    // lights outside of the scene (directional) don't emit rays
+    fn sample_le(&self) -> Option<EmissionSample>;
+    // position and direction densities of sample_le emitting along dir, zero for lights that don't emit rays
+    fn pdf_le(&self, dir: &Vec4) -> (f32, f32);
+    fn is_delta(&self) -> bool;
+
+    fn is_infinite(&self) -> bool
+    {
+        false
+    }
+
+    fn camera_visible(&self) -> bool
+    {
+        true
+    }
+}
+
+#[derive(Clone)]
+pub struct PointLight
+{
+    pub pos: Vec4,
+    pub intensity: Vec4
+}
+
+// https://www.pbr-book.org/3ed-2018/Light_Sources/Point_Lights#Spotlights
+#[derive(Clone)]
+pub struct SpotLight
+{
+    pub pos: Vec4,
+    pub intensity: Vec4,
+    frame: Frame,
+    cos_inner: f32, // falloff starts
+    cos_outer: f32 // cut off
+}
+
+// sun like light, delta if the angular radius is zero
+#[derive(Clone)]
+pub struct DirectionalLight
+{
+    pub dir: Vec4, // towards the light
+    pub irradiance: Vec4, // perpendicular to dir
+    pub camera_visible: bool,
+    frame: Frame,
+    cos_max: f32
+}
+
+#[derive(Clone)]
+pub struct RectLight
+{
+    pub center: Vec4,
+    pub u: Vec4, // half edges
+    pub v: Vec4,
+    pub radiance: Vec4,
+    pub two_sided: bool,
+    pub camera_visible: bool,
+    normal: Vec4,
+    area: f32
+}
+
+#[derive(Clone)]
+pub struct DiskLight
+{
+    pub center: Vec4,
+    pub radius: f32,
+    pub radiance: Vec4,
+    pub two_sided: bool,
+    pub camera_visible: bool,
+    frame: Frame
+}
+
+// point light with the angular distribution of a measured luminaire
+#[derive(Clone)]
+pub struct IesLight
+{
+    pub pos: Vec4,
+    pub intensity: Vec4, // at the peak of the profile
+    frame: Frame, // z is the nadir (vertical angle 0) of the profile
+    profile: IesProfile
+}
+
+// equirectangular radiance map at infinity, importance sampled by luminance
+#[derive(Clone)]
+pub struct EnvironmentLight
+{
+    pub strength: f32,
+    pub rotation: Quat, // environment to world
+    pub camera_visible: bool,
+    texture: DynamicTexture, // linear radiance, top row is +y
+    distribution: Distribution2D
+}
+
+//######################################################################
+// Light
+//######################################################################
+
+#[derive(Clone)]
+pub enum Light
+{
+    Point {light: PointLight},
+    Spot {light: SpotLight},
+    Directional {light: DirectionalLight},
+    Rect {light: RectLight},
+    Disk {light: DiskLight},
+    Ies {light: IesLight},
+    Environment {light: EnvironmentLight}
+}
+
+impl Emitter for Light
+{
+    fn sample_li(&self, origin: &Vec4) -> Option<LightSample>
+    {
+        match self
+        {
+            Light::Point {light} => {light.sample_li(origin)},
+            Light::Spot {light} => {light.sample_li(origin)},
+            Light::Directional {light} => {light.sample_li(origin)},
+            Light::Rect {light} => {light.sample_li(origin)},
+            Light::Disk {light} => {light.sample_li(origin)},
+            Light::Ies {light} => {light.sample_li(origin)},
+            Light::Environment {light} => {light.sample_li(origin)}
+        }
+    }
+
+    fn pdf_li(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        match self
+        {
+            Light::Point {light} => {light.pdf_li(origin, dir)},
+            Light::Spot {light} => {light.pdf_li(origin, dir)},
+            Light::Directional {light} => {light.pdf_li(origin, dir)},
+            Light::Rect {light} => {light.pdf_li(origin, dir)},
+            Light::Disk {light} => {light.pdf_li(origin, dir)},
+            Light::Ies {light} => {light.pdf_li(origin, dir)},
+            Light::Environment {light} => {light.pdf_li(origin, dir)}
+        }
+    }
+
+    fn le(&self, r: &Ray, max: f32) -> Option<(f32, Vec4)>
+    {
+        match self
+        {
+            Light::Point {light} => {light.le(r, max)},
+            Light::Spot {light} => {light.le(r, max)},
+            Light::Directional {light} => {light.le(r, max)},
+            Light::Rect {light} => {light.le(r, max)},
+            Light::Disk {light} => {light.le(r, max)},
+            Light::Ies {light} => {light.le(r, max)},
+            Light::Environment {light} => {light.le(r, max)}
+        }
+    }
+
+    fn sample_le(&self) -> Option<EmissionSample>
+    {
+        match self
+        {
+            Light::Point {light} => {light.sample_le()},
+            Light::Spot {light} => {light.sample_le()},
+            Light::Directional {light} => {light.sample_le()},
+            Light::Rect {light} => {light.sample_le()},
+            Light::Disk {light} => {light.sample_le()},
+            Light::Ies {light} => {light.sample_le()},
+            Light::Environment {light} => {light.sample_le()}
+        }
+    }
+
+    fn pdf_le(&self, dir: &Vec4) -> (f32, f32)
+    {
+        match self
+        {
+            Light::Point {light} => {light.pdf_le(dir)},
+            Light::Spot {light} => {light.pdf_le(dir)},
+            Light::Directional {light} => {light.pdf_le(dir)},
+            Light::Rect {light} => {light.pdf_le(dir)},
+            Light::Disk {light} => {light.pdf_le(dir)},
+            Light::Ies {light} => {light.pdf_le(dir)},
+            Light::Environment {light} => {light.pdf_le(dir)}
+        }
+    }
+
+    fn is_delta(&self) -> bool
+    {
+        match self
+        {
+            Light::Point {light} => {light.is_delta()},
+            Light::Spot {light} => {light.is_delta()},
+            Light::Directional {light} => {light.is_delta()},
+            Light::Rect {light} => {light.is_delta()},
+            Light::Disk {light} => {light.is_delta()},
+            Light::Ies {light} => {light.is_delta()},
+            Light::Environment {light} => {light.is_delta()}
+        }
+    }
+
+    fn is_infinite(&self) -> bool
+    {
+        match self
+        {
+            Light::Directional {..} => {true},
+            Light::Environment {..} => {true},
+            _ => {false}
+        }
+    }
+
+    fn camera_visible(&self) -> bool
+    {
+        match self
+        {
+            Light::Directional {light} => {light.camera_visible},
+            Light::Rect {light} => {light.camera_visible},
+            Light::Disk {light} => {light.camera_visible},
+            Light::Environment {light} => {light.camera_visible},
+            _ => {true}
+        }
+    }
+}
+
+impl Light
+{
+    // hides the light from camera rays, it still illuminates the scene. no effect on delta lights
+    pub fn set_camera_visible(&mut self, visible: bool)
+    {
+        match self
+        {
+            Light::Directional {light} => {light.camera_visible = visible},
+            Light::Rect {light} => {light.camera_visible = visible},
+            Light::Disk {light} => {light.camera_visible = visible},
+            Light::Environment {light} => {light.camera_visible = visible},
+            _ => {}
+        }
+    }
+}
+
+// shared by point like lights, intensity towards the reference point
+fn sample_point(pos: &Vec4, origin: &Vec4, intensity: Vec4) -> Option<LightSample>
+{
+    let to_light = pos - origin;
+    let dist2 = to_light.square_length3();
+    if dist2 <= 0.0 || intensity == Vec4::zero() { return None; }
+
+    let dist = dist2.sqrt();
+    Some(LightSample{point: *pos, normal: Vec4::zero(), wi: to_light / dist, dist: dist, le: intensity / dist2, pdf: 1.0, delta: true})
+}
+
+// shared by area lights, converts a uniformly sampled point to solid angle
+fn sample_area(point: Vec4, normal: Vec4, area: f32, origin: &Vec4, radiance: Vec4, two_sided: bool) -> Option<LightSample>
+{
+    let to_light = point - origin;
+    let dist2 = to_light.square_length3();
+    if dist2 <= 0.0 { return None; }
+
+    let dist = dist2.sqrt();
+    let wi = to_light / dist;
+    let cos = -wi.dot(&normal);
+
+    if cos.abs() <= 1e-6 || (!two_sided && cos < 0.0) { return None; }
+
+    Some(LightSample{point: point, normal: normal, wi: wi, dist: dist, le: radiance, pdf: dist2 / (cos.abs() * area), delta: false})
+}
+
+// densities of sample_area_emission
+fn pdf_area_emission(normal: &Vec4, area: f32, dir: &Vec4, two_sided: bool) -> (f32, f32)
+{
+    let cos = normal.dot(dir);
+    (1.0 / area, if two_sided { cos.abs() / (2.0 * PI) } else { cos.max(0.0) / PI })
+}
+
+// cosine weighted emission from a uniformly sampled point
+fn sample_area_emission(point: Vec4, normal: Vec4, area: f32, radiance: Vec4, two_sided: bool) -> Option<EmissionSample>
+{
+    let (n, side_pdf) = if !two_sided { (normal, 1.0) } else if random_f32() < 0.5 { (normal, 0.5) } else { (-normal, 0.5) };
+    let dir = Frame::new(&n).to_world(&random_cosine_hemisphere());
+    let cos = dir.dot(&n);
+    if cos <= 0.0 { return None; }
+
+    Some(EmissionSample{point: point, normal: normal, dir: dir, le: radiance, pdf_pos: 1.0 / area, pdf_dir: side_pdf * cos / PI})
+}
+
+//######################################################################
+// PointLight
+//######################################################################
+
+impl PointLight
+{
+    pub fn new(_pos: Vec4, _intensity: Vec4) -> Light
+    {
+        Light::Point{light: PointLight{pos: _pos, intensity: _intensity}}
+    }
+}
+
+impl Emitter for PointLight
+{
+    fn sample_li(&self, origin: &Vec4) -> Option<LightSample>
+    {
+        sample_point(&self.pos, origin, self.intensity)
+    }
+
+    fn pdf_li(&self, _origin: &Vec4, _dir: &Vec4) -> f32 { 0.0 }
+    fn le(&self, _r: &Ray, _max: f32) -> Option<(f32, Vec4)> { None }
+
+    fn sample_le(&self) -> Option<EmissionSample>
+    {
+        Some(EmissionSample{point: self.pos, normal: Vec4::zero(), dir: random_on_unit_sphere(), le: self.intensity, pdf_pos: 1.0, pdf_dir: 1.0 / (4.0 * PI)})
+    }
+
+    fn pdf_le(&self, _dir: &Vec4) -> (f32, f32) { (1.0, 1.0 / (4.0 * PI)) }
+
+    fn is_delta(&self) -> bool { true }
+}
+
+//######################################################################
+// SpotLight
+//######################################################################
+
+impl SpotLight
+{
+    // angles in degrees from the spot axis
+    pub fn new(_pos: Vec4, _target: Vec4, _intensity: Vec4, _falloff_start: f32, _cutoff: f32) -> Light
+    {
+        let cos_outer = _cutoff.to_radians().cos();
+        let cos_inner = _falloff_start.min(_cutoff).to_radians().cos();
+
+        Light::Spot{light: SpotLight{
+            pos: _pos,
+            intensity: _intensity,
+            frame: Frame::new(&(_target - _pos).norm3()),
+            cos_inner: cos_inner,
+            cos_outer: cos_outer}}
+    }
+
+    fn falloff(&self, dir: &Vec4) -> f32
+    {
+        let cos = dir.dot(&self.frame.n);
+
+        if cos < self.cos_outer { return 0.0; }
+        if cos >= self.cos_inner { return 1.0; }
+
+        let delta = (cos - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        (delta * delta) * (delta * delta)
+    }
+}
+
+impl Emitter for SpotLight
+{
+    fn sample_li(&self, origin: &Vec4) -> Option<LightSample>
+    {
+        let dir = (origin - self.pos).norm3();
+        sample_point(&self.pos, origin, self.intensity * self.falloff(&dir))
+    }
+
+    fn pdf_li(&self, _origin: &Vec4, _dir: &Vec4) -> f32 { 0.0 }
+    fn le(&self, _r: &Ray, _max: f32) -> Option<(f32, Vec4)> { None }
+
+    fn sample_le(&self) -> Option<EmissionSample>
+    {
+        let dir = self.frame.to_world(&random_in_cone(self.cos_outer));
+        let pdf_dir = 1.0 / (2.0 * PI * (1.0 - self.cos_outer));
+
+        Some(EmissionSample{point: self.pos, normal: Vec4::zero(), dir: dir, le: self.intensity * self.falloff(&dir), pdf_pos: 1.0, pdf_dir: pdf_dir})
+    }
+
+    fn pdf_le(&self, dir: &Vec4) -> (f32, f32)
+    {
+        (1.0, if dir.dot(&self.frame.n) < self.cos_outer { 0.0 } else { 1.0 / (2.0 * PI * (1.0 - self.cos_outer)) })
+    }
+
+    fn is_delta(&self) -> bool { true }
+}
+
+//######################################################################
+// DirectionalLight
+//######################################################################
+
+impl DirectionalLight
+{
+    // the sun has an angular radius of about 0.27 degrees
+    pub fn new(_dir: Vec4, _irradiance: Vec4, _angular_radius: f32) -> Light
+    {
+        let dir = _dir.norm3();
+
+        Light::Directional{light: DirectionalLight{
+            dir: dir,
+            irradiance: _irradiance,
+            camera_visible: true,
+            frame: Frame::new(&dir),
+            cos_max: _angular_radius.max(0.0).to_radians().cos()}}
+    }
+
+    // uniform radiance of the disc that produces the irradiance
+    fn radiance(&self) -> Vec4
+    {
+        self.irradiance / (PI * (1.0 - self.cos_max * self.cos_max))
+    }
+
+    fn cone_pdf(&self) -> f32
+    {
+        1.0 / (2.0 * PI * (1.0 - self.cos_max))
+    }
+}
+
+impl Emitter for DirectionalLight
+{
+    fn sample_li(&self, _origin: &Vec4) -> Option<LightSample>
+    {
+        if self.is_delta()
+        {
+            return Some(LightSample{point: Vec4::zero(), normal: Vec4::zero(), wi: self.dir, dist: std::f32::INFINITY, le: self.irradiance, pdf: 1.0, delta: true});
+        }
+
+        let wi = self.frame.to_world(&random_in_cone(self.cos_max));
+        Some(LightSample{point: Vec4::zero(), normal: Vec4::zero(), wi: wi, dist: std::f32::INFINITY, le: self.radiance(), pdf: self.cone_pdf(), delta: false})
+    }
+
+    fn pdf_li(&self, _origin: &Vec4, dir: &Vec4) -> f32
+    {
+        if self.is_delta() || dir.dot(&self.dir) < self.cos_max { 0.0 } else { self.cone_pdf() }
+    }
+
+    // only escaping rays can see the disc
+    fn le(&self, r: &Ray, max: f32) -> Option<(f32, Vec4)>
+    {
+        if self.is_delta() || max < std::f32::INFINITY || r.direction.norm3().dot(&self.dir) < self.cos_max
+        {
+            return None;
+        }
+
+        Some((std::f32::INFINITY, self.radiance()))
+    }
+
+    fn sample_le(&self) -> Option<EmissionSample> { None }
+    fn pdf_le(&self, _dir: &Vec4) -> (f32, f32) { (0.0, 0.0) }
+
+    fn is_delta(&self) -> bool
+    {
+        self.cos_max >= 1.0
+    }
+}
+
+//######################################################################
+// RectLight
+//######################################################################
+
+impl RectLight
+{
+    // _u and _v are the half edges, the light faces along _u x _v
+    pub fn new(_center: Vec4, _u: Vec4, _v: Vec4, _radiance: Vec4, _two_sided: bool) -> Light
+    {
+        let n = _u.cross3(&_v);
+
+        Light::Rect{light: RectLight{
+            center: _center,
+            u: _u,
+            v: _v,
+            radiance: _radiance,
+            two_sided: _two_sided,
+            camera_visible: true,
+            normal: n.norm3(),
+            area: 4.0 * n.length3()}}
+    }
+
+    fn intersect(&self, r: &Ray, max: f32) -> Option<f32>
+    {
+        let denom = self.normal.dot(&r.direction);
+        if denom.abs() < 1e-8 { return None; }
+
+        let t = self.normal.dot(&(self.center - r.origin)) / denom;
+        if t <= 0.0 || t >= max { return None; }
+
+        let local = r.point_at(t) - self.center;
+        let s = local.dot(&self.u) / self.u.square_length3();
+        let q = local.dot(&self.v) / self.v.square_length3();
+
+        if s.abs() <= 1.0 && q.abs() <= 1.0 { Some(t) } else { None }
+    }
+
+    fn emits(&self, dir: &Vec4) -> bool
+    {
+        self.two_sided || dir.dot(&self.normal) > 0.0
+    }
+}
+
+impl Emitter for RectLight
+{
+    fn sample_li(&self, origin: &Vec4) -> Option<LightSample>
+    {
+        let (s, q) = random_tuple(-1.0, 1.0);
+        sample_area(self.center + self.u * s + self.v * q, self.normal, self.area, origin, self.radiance, self.two_sided)
+    }
+
+    fn pdf_li(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        match self.intersect(&Ray::new(*origin, *dir), std::f32::INFINITY)
+        {
+            Some(t) => t * t / (dir.dot(&self.normal).abs() * self.area),
+            None => 0.0
+        }
+    }
+
+    fn le(&self, r: &Ray, max: f32) -> Option<(f32, Vec4)>
+    {
+        let t = self.intersect(r, max)?;
+        if !self.emits(&-r.direction) { return None; }
+        Some((t, self.radiance))
+    }
+
+    fn sample_le(&self) -> Option<EmissionSample>
+    {
+        let (s, q) = random_tuple(-1.0, 1.0);
+        sample_area_emission(self.center + self.u * s + self.v * q, self.normal, self.area, self.radiance, self.two_sided)
+    }
+
+    fn pdf_le(&self, dir: &Vec4) -> (f32, f32)
+    {
+        pdf_area_emission(&self.normal, self.area, dir, self.two_sided)
+    }
+
+    fn is_delta(&self) -> bool { false }
+}
+
+//######################################################################
+// DiskLight
+//######################################################################
+
+impl DiskLight
+{
+    pub fn new(_center: Vec4, _normal: Vec4, _radius: f32, _radiance: Vec4, _two_sided: bool) -> Light
+    {
+        Light::Disk{light: DiskLight{
+            center: _center,
+            radius: _radius,
+            radiance: _radiance,
+            two_sided: _two_sided,
+            camera_visible: true,
+            frame: Frame::new(&_normal.norm3())}}
+    }
+
+    fn area(&self) -> f32
+    {
+        PI * self.radius * self.radius
+    }
+
+    fn intersect(&self, r: &Ray, max: f32) -> Option<f32>
+    {
+        let n = self.frame.n;
+        let denom = n.dot(&r.direction);
+        if denom.abs() < 1e-8 { return None; }
+
+        let t = n.dot(&(self.center - r.origin)) / denom;
+        if t <= 0.0 || t >= max { return None; }
+
+        if (r.point_at(t) - self.center).square_length3() <= self.radius * self.radius { Some(t) } else { None }
+    }
+
+    fn sample_point(&self) -> Vec4
+    {
+        let (x, y) = random_in_unit_disk2();
+        self.center + self.frame.to_world(&Vec4::from3(x * self.radius, y * self.radius, 0.0))
+    }
+}
+
+impl Emitter for DiskLight
+{
+    fn sample_li(&self, origin: &Vec4) -> Option<LightSample>
+    {
+        sample_area(self.sample_point(), self.frame.n, self.area(), origin, self.radiance, self.two_sided)
+    }
+
+    fn pdf_li(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        match self.intersect(&Ray::new(*origin, *dir), std::f32::INFINITY)
+        {
+            Some(t) => t * t / (dir.dot(&self.frame.n).abs() * self.area()),
+            None => 0.0
+        }
+    }
+
+    fn le(&self, r: &Ray, max: f32) -> Option<(f32, Vec4)>
+    {
+        let t = self.intersect(r, max)?;
+        if !self.two_sided && r.direction.dot(&self.frame.n) >= 0.0 { return None; }
+        Some((t, self.radiance))
+    }
+
+    fn sample_le(&self) -> Option<EmissionSample>
+    {
+        sample_area_emission(self.sample_point(), self.frame.n, self.area(), self.radiance, self.two_sided)
+    }
+
+    fn pdf_le(&self, dir: &Vec4) -> (f32, f32)
+    {
+        pdf_area_emission(&self.frame.n, self.area(), dir, self.two_sided)
+    }
+
+    fn is_delta(&self) -> bool { false }
+}
+
+//######################################################################
+// IesLight
+//######################################################################
+
+impl IesLight
+{
+    // _dir is the nadir of the profile, horizontal angle 0 lies along the tangent Frame picks for it
+    pub fn new(_pos: Vec4, _dir: Vec4, _intensity: Vec4, _profile: IesProfile) -> Light
+    {
+        Light::Ies{light: IesLight{pos: _pos, intensity: _intensity, frame: Frame::new(&_dir.norm3()), profile: _profile}}
+    }
+
+    pub fn from_path<P>(_pos: Vec4, _dir: Vec4, _intensity: Vec4, path: P) -> Light
+    where P: AsRef<std::path::Path>
+    {
+        IesLight::new(_pos, _dir, _intensity, IesProfile::from_path(path))
+    }
+
+    fn intensity_towards(&self, dir: &Vec4) -> Vec4
+    {
+        let local = self.frame.to_local(dir);
+        let vertical = local.z().max(-1.0).min(1.0).acos().to_degrees();
+        let mut horizontal = local.y().atan2(local.x()).to_degrees();
+        if horizontal < 0.0 { horizontal += 360.0; }
+
+        self.intensity * self.profile.eval(vertical, horizontal)
+    }
+}
+
+impl Emitter for IesLight
+{
+    fn sample_li(&self, origin: &Vec4) -> Option<LightSample>
+    {
+        let dir = (origin - self.pos).norm3();
+        sample_point(&self.pos, origin, self.intensity_towards(&dir))
+    }
+
+    fn pdf_li(&self, _origin: &Vec4, _dir: &Vec4) -> f32 { 0.0 }
+    fn le(&self, _r: &Ray, _max: f32) -> Option<(f32, Vec4)> { None }
+
+    fn sample_le(&self) -> Option<EmissionSample>
+    {
+        let dir = random_on_unit_sphere();
+        Some(EmissionSample{point: self.pos, normal: Vec4::zero(), dir: dir, le: self.intensity_towards(&dir), pdf_pos: 1.0, pdf_dir: 1.0 / (4.0 * PI)})
+    }
+
+    fn pdf_le(&self, _dir: &Vec4) -> (f32, f32) { (1.0, 1.0 / (4.0 * PI)) }
+
+    fn is_delta(&self) -> bool { true }
+}
+
+//######################################################################
+// EnvironmentLight
+//######################################################################
+
+impl EnvironmentLight
+{
+    // .hdr files are read as linear radiance, other formats are decoded according to _type
+    pub fn from_path<P>(path: P, _strength: f32, _rotation: Quat, _type: DynamicTextureType) -> Light
+    where P: AsRef<std::path::Path>
+    {
+        let (width, height, pixels) = load_linear(path.as_ref(), _type);
+        EnvironmentLight::new(width, height, pixels, _strength, _rotation)
+    }
+
+    pub fn new(_width: usize, _height: usize, _pixels: std::vec::Vec<Vec4>, _strength: f32, _rotation: Quat) -> Light
+    {
+        assert_eq!(_pixels.len(), _width * _height);
+
+        // bilinear lookups reach into the neighbouring texels, so each texel takes the brightest of its neighbourhood
+        // to keep the pdf non zero wherever the filtered radiance is, rows near the poles cover less solid angle
+        let mut func = std::vec::Vec::with_capacity(_pixels.len());
+        for y in 0.._height
+        {
+            let sin_theta = (PI * (y as f32 + 0.5) / _height as f32).sin();
+            for x in 0.._width
+            {
+                let mut lum = 0.0f32;
+                for ny in y.max(1) - 1..(y + 2).min(_height)
+                {
+                    for dx in 0..3
+                    {
+                        let nx = (x + _width + dx - 1) % _width;
+                        lum = lum.max(_pixels[ny * _width + nx].luminance());
+                    }
+                }
+                func.push(lum * sin_theta);
+            }
+        }
+
+        Light::Environment{light: EnvironmentLight{
+            strength: _strength,
+            rotation: _rotation,
+            camera_visible: true,
+            distribution: Distribution2D::new(&func, _width, _height),
+            texture: DynamicTexture::from_texels(_width, _height, _pixels, DynamicTextureType::Linear, FilterMode::Bilinear, WrapMode::LatLong)}}
+    }
+
+    // same mapping as Sphere::uv seen from the center
+    fn to_uv(&self, dir: &Vec4) -> (f32, f32, f32)
+    {
+        let d = self.rotation.inverse_rotate_unit(dir).norm3();
+        let y = d.y().max(-1.0).min(1.0);
+        let u = (d.z().atan2(d.x()) + PI) / (2.0 * PI);
+        let v = y.acos() / PI;
+
+        (u, v, (1.0 - y * y).max(0.0).sqrt())
+    }
+
+    fn from_uv(&self, u: f32, v: f32) -> (Vec4, f32)
+    {
+        let phi = u * 2.0 * PI - PI;
+        let theta = v * PI;
+        let sin_theta = theta.sin();
+
+        let d = Vec4::from3(sin_theta * phi.cos(), theta.cos(), sin_theta * phi.sin());
+        (self.rotation.rotate_unit(&d), sin_theta)
+    }
+
+    fn lookup(&self, u: f32, v: f32) -> Vec4
+    {
+        self.texture.lookup(u, v) * self.strength
+    }
+}
+
+impl Emitter for EnvironmentLight
+{
+    fn sample_li(&self, _origin: &Vec4) -> Option<LightSample>
+    {
+        let (u, v, pdf_uv) = self.distribution.sample();
+        let (wi, sin_theta) = self.from_uv(u, v);
+
+        if pdf_uv <= 0.0 || sin_theta <= 0.0 { return None; }
+
+        // uv to solid angle
+        let pdf = pdf_uv / (2.0 * PI * PI * sin_theta);
+        Some(LightSample{point: Vec4::zero(), normal: Vec4::zero(), wi: wi, dist: std::f32::INFINITY, le: self.lookup(u, v), pdf: pdf, delta: false})
+    }
+
+    fn pdf_li(&self, _origin: &Vec4, dir: &Vec4) -> f32
+    {
+        let (u, v, sin_theta) = self.to_uv(dir);
+        if sin_theta <= 0.0 { return 0.0; }
+
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+
+    // only escaping rays see the environment
+    fn le(&self, r: &Ray, max: f32) -> Option<(f32, Vec4)>
+    {
+        if max < std::f32::INFINITY { return None; }
+
+        let (u, v, _) = self.to_uv(&r.direction);
+        Some((std::f32::INFINITY, self.lookup(u, v)))
+    }
+
+    fn sample_le(&self) -> Option<EmissionSample> { None }
+    fn pdf_le(&self, _dir: &Vec4) -> (f32, f32) { (0.0, 0.0) }
+    fn is_delta(&self) -> bool { false }
+}
+
+//######################################################################
+// IesProfile
+//######################################################################
+
+// type C photometric data of an IES LM-63 file, normalized to a peak of 1
+#[derive(Clone)]
+pub struct IesProfile
+{
+    vertical: std::vec::Vec<f32>, // degrees, 0 = nadir
+    horizontal: std::vec::Vec<f32>,
+    candela: std::vec::Vec<f32> // horizontal major
+}
+
+impl IesProfile
+{
+    pub fn isotropic() -> IesProfile
+    {
+        IesProfile{vertical: vec![0.0, 180.0], horizontal: vec![0.0], candela: vec![1.0, 1.0]}
+    }
+
+    pub fn from_path<P>(path: P) -> IesProfile
+    where P: AsRef<std::path::Path>
+    {
+        let parsed = std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|text| IesProfile::parse(&text));
+
+        match parsed
+        {
+            Ok(profile) => profile,
+            Err(e) => {
+                eprintln!("{}", e);
+                IesProfile::isotropic()
+            }
+        }
+    }
+
+    // http://lumen.iee.put.poznan.pl/kw/iesna.txt
+    pub fn parse(text: &str) -> Result<IesProfile, String>
+    {
+        let tilt = text.find("TILT=").ok_or("ies: missing TILT")?;
+        let mut lines = text[tilt..].lines();
+        let tilt_line = lines.next().unwrap_or("");
+
+        let rest: std::vec::Vec<&str> = lines.collect();
+        let mut values = rest.iter().flat_map(|l| l.split(|c: char| c.is_whitespace() || c == ',')).filter(|s| !s.is_empty()).map(|s| s.parse::<f32>());
+        let mut next = || -> Result<f32, String> { values.next().ok_or("ies: unexpected end of data".to_string())?.map_err(|e| e.to_string()) };
+
+        // every value takes at least a character, larger counts can only come from a corrupt file
+        let count = |v: f32| -> Result<usize, String> {
+            if v >= 0.0 && v <= text.len() as f32 && v.fract() == 0.0 { Ok(v as usize) } else { Err(format!("ies: bad count {}", v)) }
+        };
+
+        if tilt_line.trim() == "TILT=INCLUDE"
+        {
+            // lamp to luminaire geometry, angle count, angles and multiplying factors
+            next()?;
+            let angles = count(next()?)?;
+            for _ in 0..2 * angles { next()?; }
+        }
+        else if tilt_line.trim() != "TILT=NONE"
+        {
+            return Err("ies: external TILT files are not supported".to_string());
+        }
+
+        let _lamps = next()?;
+        let _lumens = next()?;
+        let multiplier = next()?;
+        let vertical_count = count(next()?)?;
+        let horizontal_count = count(next()?)?;
+        let photometric_type = next()? as u32;
+        for _ in 0..4 { next()?; } // units, width, length, height
+        for _ in 0..3 { next()?; } // ballast factor, future use, input watts
+
+        if photometric_type != 1
+        {
+            return Err("ies: only type C photometry is supported".to_string());
+        }
+
+        if vertical_count == 0 || horizontal_count == 0
+        {
+            return Err("ies: empty profile".to_string());
+        }
+
+        let mut vertical = std::vec::Vec::with_capacity(vertical_count);
+        for _ in 0..vertical_count { vertical.push(next()?); }
+
+        let mut horizontal = std::vec::Vec::with_capacity(horizontal_count);
+        for _ in 0..horizontal_count { horizontal.push(next()?); }
+
+        let total = vertical_count.checked_mul(horizontal_count).filter(|n| *n <= text.len()).ok_or("ies: size overflow".to_string())?;
+        let mut candela = std::vec::Vec::with_capacity(total);
+        for _ in 0..total { candela.push(next()? * multiplier); }
+
+        let peak = candela.iter().cloned().fold(0.0, f32::max);
+        if peak > 0.0
+        {
+            for c in candela.iter_mut() { *c /= peak; }
+        }
+
+        Ok(IesProfile{vertical: vertical, horizontal: horizontal, candela: candela})
+    }
+
+    // index and blend factor of angle in the sorted angles, None outside of the measured range
+    fn locate(angles: &[f32], angle: f32) -> Option<(usize, f32)>
+    {
+        if angles.len() == 1 { return Some((0, 0.0)); }
+        if angle < angles[0] || angle > angles[angles.len() - 1] { return None; }
+
+        let i = angles.iter().rposition(|a| *a <= angle).unwrap_or(0).min(angles.len() - 2);
+        let span = angles[i + 1] - angles[i];
+        Some((i, if span > 0.0 { (angle - angles[i]) / span } else { 0.0 }))
+    }
+
+    fn value(&self, h: usize, v: usize) -> f32
+    {
+        self.candela[h * self.vertical.len() + v]
+    }
+
+    pub fn eval(&self, vertical: f32, horizontal: f32) -> f32
+    {
+        // the last horizontal angle determines the symmetry of the measurement
+        let last = self.horizontal[self.horizontal.len() - 1];
+        let horizontal = if last <= 0.0 { 0.0 }
+            else if last <= 90.0 { let h = horizontal % 180.0; if h > 90.0 { 180.0 - h } else { h } }
+            else if last <= 180.0 { if horizontal > 180.0 { 360.0 - horizontal } else { horizontal } }
+            else { horizontal };
+
+        let (v, fv) = match IesProfile::locate(&self.vertical, vertical) { Some(l) => l, None => return 0.0 };
+        let (h, fh) = match IesProfile::locate(&self.horizontal, horizontal) { Some(l) => l, None => return 0.0 };
+
+        let v1 = (v + 1).min(self.vertical.len() - 1);
+        let h1 = (h + 1).min(self.horizontal.len() - 1);
+
+        let a = self.value(h, v) * (1.0 - fv) + self.value(h, v1) * fv;
+        let b = self.value(h1, v) * (1.0 - fv) + self.value(h1, v1) * fv;
+        a * (1.0 - fh) + b * fh
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const HEADER: &str = "IESNA:LM-63-2002\nTILT=NONE\n";
+
+    #[test]
+    fn ies_profile()
+    {
+        let text = format!("{}1 1000 1 3 1 1 1 0 0 0\n1 1 100\n0 45 90\n0\n200 100 0\n", HEADER);
+        let profile = IesProfile::parse(&text).unwrap();
+
+        assert!((profile.eval(0.0, 0.0) - 1.0).abs() < 1e-6);
+        assert!((profile.eval(22.5, 123.0) - 0.75).abs() < 1e-6);
+        assert!(profile.eval(135.0, 0.0) == 0.0);
+    }
+
+    #[test]
+    fn ies_bad_counts()
+    {
+        for counts in ["4294967296 4294967296", "-1 1", "1.5 1", "3000000000 3000000000"].iter()
+        {
+            let text = format!("{}1 1000 1 {} 1 1 1 0 0 0\n1 1 100\n0\n0\n1\n", HEADER, counts);
+            assert!(IesProfile::parse(&text).is_err(), "{} must be rejected", counts);
+        }
+    }
+}
@@ -3,6 +3,11 @@ use super::ray::*;
 use super::hit::*;
 use super::random::*;
 use super::texture::*;
+use super::microfacet::*;
+use super::bsdf::*;
+use super::sky::*;
+use super::graph::GraphSource;
+use std::f32::consts::PI;
 
 #[derive(Copy, Clone)]
 pub struct MaterialInfo
@@ -50,6 +55,71 @@ pub struct Background
     pub strength: f32
 }
 
+// analytic daylight as miss material, the sun disc is a separate light
+#[derive(Clone)]
+pub struct Sky
+{
+    pub model: SkyModel,
+    pub strength: f32
+}
+
+// https://blog.selfshadow.com/publications/s2015-shading-course/burley/s2015_pbs_disney_bsdf_notes.pdf
+#[derive(Clone)]
+pub struct Principled
+{
+    pub base_color: Texture,
+    pub metallic: Texture,
+    pub roughness: Texture,
+    pub specular: Texture,
+    pub specular_tint: Texture,
+    pub sheen: Texture,
+    pub sheen_tint: Texture,
+    pub clearcoat: Texture,
+    pub clearcoat_gloss: Texture,
+    pub transmission: Texture,
+    pub subsurface: Texture,
+    pub ior: f32
+}
+
+// perturbation of the shading normal
+#[derive(Clone)]
+pub enum ShadingNormal
+{
+    Tangent {tex: Texture, strength: f32}, // tangent space normal map, rgb in [0, 1]
+    Bump {tex: Texture, scale: f32} // height map, x channel times scale is the displacement in world units
+}
+
+// wraps a material and shades it with a perturbed normal
+#[derive(Clone)]
+pub struct NormalMapped
+{
+    pub base: Box<Material>,
+    pub normal: ShadingNormal
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum AlphaMode
+{
+    Stochastic, // kept with probability opacity, partial coverage averages out over samples
+    Threshold(f32) // kept if opacity reaches the cutoff
+}
+
+// wraps a material and cuts out the texels where opacity is low
+#[derive(Clone)]
+pub struct AlphaMasked
+{
+    pub base: Box<Material>,
+    pub opacity: Texture,
+    pub channel: usize, // 3 reads the alpha channel
+    pub mode: AlphaMode
+}
+
+// boundary of a participating medium, rays pass through unchanged
+#[derive(Clone)]
+pub struct Interface
+{
+}
+
 pub trait Scatter
 {
     fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool;
@@ -65,7 +135,12 @@ pub enum Material
     Lambertian {mat: Lambertian},
     Emissive {mat: Emissive},
     Metal {mat: Metal},
-    Background {mat: Background}
+    Background {mat: Background},
+    Sky {mat: Sky},
+    Principled {mat: Principled},
+    Interface {mat: Interface},
+    NormalMapped {mat: NormalMapped},
+    AlphaMasked {mat: AlphaMasked}
 }
 
 impl Scatter for Material
@@ -77,15 +152,153 @@ impl Scatter for Material
             Material::Lambertian {mat} => {mat.scatter(_r, &_hit, _out_mat)},
             Material::Emissive {mat} => {mat.scatter(_r, &_hit, _out_mat)},
             Material::Metal {mat} => {mat.scatter(_r, &_hit, _out_mat)},
-            Material::Background {mat} => {mat.scatter(_r, &_hit, _out_mat)}
+            Material::Background {mat} => {mat.scatter(_r, &_hit, _out_mat)},
+            Material::Sky {mat} => {mat.scatter(_r, &_hit, _out_mat)},
+            Material::Principled {mat} => {mat.scatter(_r, &_hit, _out_mat)},
+            Material::Interface {mat} => {mat.scatter(_r, &_hit, _out_mat)},
+            Material::NormalMapped {mat} => {mat.scatter(_r, &_hit, _out_mat)},
+            Material::AlphaMasked {mat} => {mat.scatter(_r, &_hit, _out_mat)}
         };
 
         scattered
     }
 }
 
+impl Bsdf for Material
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample>
+    {
+        match self
+        {
+            Material::Lambertian {mat} => {mat.sample(wo, hit)},
+            Material::Emissive {mat} => {mat.sample(wo, hit)},
+            Material::Metal {mat} => {mat.sample(wo, hit)},
+            Material::Background {mat} => {mat.sample(wo, hit)},
+            Material::Sky {mat} => {mat.sample(wo, hit)},
+            Material::Principled {mat} => {mat.sample(wo, hit)},
+            Material::Interface {mat} => {mat.sample(wo, hit)},
+            Material::NormalMapped {mat} => {mat.sample(wo, hit)},
+            Material::AlphaMasked {mat} => {mat.sample(wo, hit)}
+        }
+    }
+
+    fn eval(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        match self
+        {
+            Material::Lambertian {mat} => {mat.eval(wi, wo, hit)},
+            Material::Emissive {mat} => {mat.eval(wi, wo, hit)},
+            Material::Metal {mat} => {mat.eval(wi, wo, hit)},
+            Material::Background {mat} => {mat.eval(wi, wo, hit)},
+            Material::Sky {mat} => {mat.eval(wi, wo, hit)},
+            Material::Principled {mat} => {mat.eval(wi, wo, hit)},
+            Material::Interface {mat} => {mat.eval(wi, wo, hit)},
+            Material::NormalMapped {mat} => {mat.eval(wi, wo, hit)},
+            Material::AlphaMasked {mat} => {mat.eval(wi, wo, hit)}
+        }
+    }
+
+    fn pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> f32
+    {
+        match self
+        {
+            Material::Lambertian {mat} => {mat.pdf(wi, wo, hit)},
+            Material::Emissive {mat} => {mat.pdf(wi, wo, hit)},
+            Material::Metal {mat} => {mat.pdf(wi, wo, hit)},
+            Material::Background {mat} => {mat.pdf(wi, wo, hit)},
+            Material::Sky {mat} => {mat.pdf(wi, wo, hit)},
+            Material::Principled {mat} => {mat.pdf(wi, wo, hit)},
+            Material::Interface {mat} => {mat.pdf(wi, wo, hit)},
+            Material::NormalMapped {mat} => {mat.pdf(wi, wo, hit)},
+            Material::AlphaMasked {mat} => {mat.pdf(wi, wo, hit)}
+        }
+    }
+
+    fn eval_pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> (Vec4, f32)
+    {
+        match self
+        {
+            Material::Lambertian {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::Emissive {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::Metal {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::Background {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::Sky {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::Principled {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::Interface {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::NormalMapped {mat} => {mat.eval_pdf(wi, wo, hit)},
+            Material::AlphaMasked {mat} => {mat.eval_pdf(wi, wo, hit)}
+        }
+    }
+
+    fn flags(&self, hit: &HitInfo) -> BsdfFlags
+    {
+        match self
+        {
+            Material::Lambertian {mat} => {mat.flags(hit)},
+            Material::Emissive {mat} => {mat.flags(hit)},
+            Material::Metal {mat} => {mat.flags(hit)},
+            Material::Background {mat} => {mat.flags(hit)},
+            Material::Sky {mat} => {mat.flags(hit)},
+            Material::Principled {mat} => {mat.flags(hit)},
+            Material::Interface {mat} => {mat.flags(hit)},
+            Material::NormalMapped {mat} => {mat.flags(hit)},
+            Material::AlphaMasked {mat} => {mat.flags(hit)}
+        }
+    }
+
+    fn emission(&self, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        match self
+        {
+            Material::Lambertian {mat} => {mat.emission(wo, hit)},
+            Material::Emissive {mat} => {mat.emission(wo, hit)},
+            Material::Metal {mat} => {mat.emission(wo, hit)},
+            Material::Background {mat} => {mat.emission(wo, hit)},
+            Material::Sky {mat} => {mat.emission(wo, hit)},
+            Material::Principled {mat} => {mat.emission(wo, hit)},
+            Material::Interface {mat} => {mat.emission(wo, hit)},
+            Material::NormalMapped {mat} => {mat.emission(wo, hit)},
+            Material::AlphaMasked {mat} => {mat.emission(wo, hit)}
+        }
+    }
+}
+
 impl Material
 {
+    pub fn is_emissive(&self) -> bool
+    {
+        match self
+        {
+            Material::Emissive {..} => {true}
+            Material::NormalMapped {mat} => {mat.base.is_emissive()}
+            Material::AlphaMasked {mat} => {mat.base.is_emissive()}
+            _ => {false}
+        }
+    }
+
+    // media boundaries are skipped by shadow rays and don't count as bounces
+    pub fn is_interface(&self) -> bool
+    {
+        match self
+        {
+            Material::Interface {..} => {true}
+            Material::NormalMapped {mat} => {mat.base.is_interface()}
+            Material::AlphaMasked {mat} => {mat.base.is_interface()}
+            _ => {false}
+        }
+    }
+
+    // false if the hit falls on a cut out texel and intersection should continue behind it
+    pub fn alpha_test(&self, hit: &HitInfo) -> bool
+    {
+        match self
+        {
+            Material::AlphaMasked {mat} => {mat.alpha_test(hit)}
+            Material::NormalMapped {mat} => {mat.base.alpha_test(hit)}
+            _ => {true}
+        }
+    }
+
     pub fn get_lambertian(&mut self) -> &mut Lambertian
     {
         match self
@@ -121,6 +334,15 @@ impl Material
             _ => {panic!()}
         }
     }
+
+    pub fn get_principled(&mut self) -> &mut Principled
+    {
+        match self
+        {
+            Material::Principled {mat} => {mat}
+            _ => {panic!()}
+        }
+    }
 }
 
 //######################################################################
@@ -139,20 +361,50 @@ impl Lambertian
     {
         Material::Lambertian{mat: Lambertian{albedo: Texture::DynamicTexture{tex: DynamicTexture::new(path, _type)}}}
     }
+
+    pub fn from_texture(_albedo: Texture) -> Material
+    {
+        Material::Lambertian{mat: Lambertian{albedo: _albedo}}
+    }
 }
 
 impl Scatter for Lambertian
 {
     fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
     {
-        let target = _hit.point + _hit.normal + random_in_unit_sphere();
+        scatter_bsdf(self, _r, _hit, _out_mat)
+    }
+}
 
-        *_r = Ray::new(_hit.point, (target - _hit.point).norm());
+impl Bsdf for Lambertian
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample>
+    {
+        // two sided, sample the hemisphere wo is in
+        let n = if wo.dot(&hit.normal) >= 0.0 { hit.normal } else { -hit.normal };
+        let wi = Frame::new(&n).to_world(&random_cosine_hemisphere());
+        let cos = wi.dot(&n);
 
-        _out_mat.attenuation = self.albedo.sample(_hit);
-        _out_mat.emission = Vec4::zero();
+        if cos <= 0.0 { return None; }
 
-        true
+        Some(BsdfSample::new(wi, self.albedo.sample(hit) / PI, cos / PI, BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION))
+    }
+
+    fn eval(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        if wi.dot(&hit.normal) * wo.dot(&hit.normal) <= 0.0 { return Vec4::zero(); }
+        self.albedo.sample(hit) / PI
+    }
+
+    fn pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> f32
+    {
+        if wi.dot(&hit.normal) * wo.dot(&hit.normal) <= 0.0 { return 0.0; }
+        wi.dot(&hit.normal).abs() / PI
+    }
+
+    fn flags(&self, _hit: &HitInfo) -> BsdfFlags
+    {
+        BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION
     }
 }
 
@@ -192,12 +444,26 @@ impl Scatter for Emissive
     }
 }
 
+impl Bsdf for Emissive
+{
+    fn sample(&self, _wo: &Vec4, _hit: &HitInfo) -> Option<BsdfSample> { None }
+    fn eval(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> Vec4 { Vec4::zero() }
+    fn pdf(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> f32 { 0.0 }
+    fn flags(&self, _hit: &HitInfo) -> BsdfFlags { BsdfFlags::NONE }
+
+    fn emission(&self, _wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        self.emissive.sample(hit) * self.strength
+    }
+}
+
 //######################################################################
 // Metal
 //######################################################################
 
 impl Metal
 {
+    // _roughness is the radius of the fuzz added to the mirror direction, 0 is a perfect mirror
     pub fn new(r: f32, g: f32, b: f32, _roughness: f32) -> Material
     {
         Material::Metal{mat: Metal{albedo: ConstantTexture::from(r, g, b).texture(), roughness: _roughness}}
@@ -210,23 +476,98 @@ impl Metal
     }
 }
 
+impl Metal
+{
+    fn is_delta(&self) -> bool
+    {
+        self.roughness <= 1e-4
+    }
+
+    fn normal(&self, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        if wo.dot(&hit.normal) >= 0.0 { hit.normal } else { -hit.normal }
+    }
+
+    // density of wi when the mirror direction is fuzzed by a uniform point in a ball of radius roughness:
+    // the ray along wi runs through the ball from t1 to t2, so pdf = (t2^3 - t1^3) / (4 PI roughness^3)
+    fn fuzz_pdf(&self, wi: &Vec4, reflected: &Vec4) -> f32
+    {
+        let f = self.roughness;
+        let b = wi.dot(reflected);
+        let disc = b * b - 1.0 + f * f;
+        if disc <= 0.0 { return 0.0; }
+
+        let s = disc.sqrt();
+        let t2 = b + s;
+        if t2 <= 0.0 { return 0.0; }
+
+        let t1 = (b - s).max(0.0);
+        (t2 * t2 * t2 - t1 * t1 * t1) / (4.0 * PI * f * f * f)
+    }
+}
+
 impl Scatter for Metal
 {
     fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
     {
-        let mut target = _r.direction.reflect(&_hit.normal);
-        
-        if self.roughness > 0.0
+        scatter_bsdf(self, _r, _hit, _out_mat)
+    }
+}
+
+// the mirror direction is fuzzed by roughness * (point in unit ball), directions below the surface are absorbed
+impl Bsdf for Metal
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample>
+    {
+        let n = self.normal(wo, hit);
+        let reflected = (-*wo).reflect(&n);
+        let albedo = self.albedo.sample(hit);
+
+        if self.is_delta()
         {
-            target += self.roughness * random_in_unit_sphere();
-        }  
+            let cos = reflected.dot(&n);
+            if cos <= 0.0 { return None; }
+
+            return Some(BsdfSample::new(reflected, albedo / cos, 1.0, BsdfFlags::SPECULAR | BsdfFlags::REFLECTION));
+        }
+
+        let target = reflected + self.roughness * random_on_unit_sphere() * random_f32().cbrt();
+        if target.length() < 1e-6 { return None; }
+
+        let wi = target.norm();
+        let cos = wi.dot(&n);
+        if cos <= 0.0 { return None; }
+
+        let pdf = self.fuzz_pdf(&wi, &reflected);
+        if pdf <= 0.0 { return None; }
+
+        Some(BsdfSample::new(wi, albedo * pdf / cos, pdf, BsdfFlags::GLOSSY | BsdfFlags::REFLECTION))
+    }
+
+    fn eval(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        if self.is_delta() { return Vec4::zero(); }
+
+        let n = self.normal(wo, hit);
+        let cos = wi.dot(&n);
+        if cos <= 0.0 { return Vec4::zero(); }
+
+        self.albedo.sample(hit) * self.fuzz_pdf(wi, &(-*wo).reflect(&n)) / cos
+    }
+
+    fn pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> f32
+    {
+        if self.is_delta() { return 0.0; }
 
-        *_r = Ray::new(_hit.point, target.norm());
+        let n = self.normal(wo, hit);
+        if wi.dot(&n) <= 0.0 { return 0.0; }
 
-        _out_mat.attenuation = self.albedo.sample(_hit);
-        _out_mat.emission = Vec4::zero();
+        self.fuzz_pdf(wi, &(-*wo).reflect(&n))
+    }
 
-        _r.direction.dot(&_hit.normal) > 0.0
+    fn flags(&self, _hit: &HitInfo) -> BsdfFlags
+    {
+        if self.is_delta() { BsdfFlags::SPECULAR | BsdfFlags::REFLECTION } else { BsdfFlags::GLOSSY | BsdfFlags::REFLECTION }
     }
 }
 
@@ -252,9 +593,766 @@ impl Scatter for Background
 {
     fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
     {
-        let t = _r.direction.norm().y() + 1.0;
-        _out_mat.emission = (Vec4::from(1.0-t) + t * self.color.sample(_hit)) * self.strength;
+        _out_mat.emission = self.emission(&-_r.direction, _hit);
         _out_mat.attenuation = Vec4::one();
         false
     }
 }
+
+impl Bsdf for Background
+{
+    fn sample(&self, _wo: &Vec4, _hit: &HitInfo) -> Option<BsdfSample> { None }
+    fn eval(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> Vec4 { Vec4::zero() }
+    fn pdf(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> f32 { 0.0 }
+    fn flags(&self, _hit: &HitInfo) -> BsdfFlags { BsdfFlags::NONE }
+
+    // wo points back along the escaped ray
+    fn emission(&self, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        let t = -wo.norm().y() + 1.0;
+        (Vec4::from(1.0-t) + t * self.color.sample(hit)) * self.strength
+    }
+}
+
+//######################################################################
+// Sky
+//######################################################################
+
+impl Sky
+{
+    pub fn new(_model: SkyModel, _strength: f32) -> Material
+    {
+        Material::Sky{mat: Sky{model: _model, strength: _strength}}
+    }
+}
+
+impl Scatter for Sky
+{
+    fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
+    {
+        _out_mat.emission = self.emission(&-_r.direction, _hit);
+        _out_mat.attenuation = Vec4::one();
+        false
+    }
+}
+
+impl Bsdf for Sky
+{
+    fn sample(&self, _wo: &Vec4, _hit: &HitInfo) -> Option<BsdfSample> { None }
+    fn eval(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> Vec4 { Vec4::zero() }
+    fn pdf(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> f32 { 0.0 }
+    fn flags(&self, _hit: &HitInfo) -> BsdfFlags { BsdfFlags::NONE }
+
+    // wo points back along the escaped ray
+    fn emission(&self, wo: &Vec4, _hit: &HitInfo) -> Vec4
+    {
+        self.model.radiance(&-*wo) * self.strength
+    }
+}
+
+//######################################################################
+// NormalMapped
+//######################################################################
+
+impl NormalMapped
+{
+    pub fn new(_base: Material, _normal: ShadingNormal) -> Material
+    {
+        Material::NormalMapped{mat: NormalMapped{base: Box::new(_base), normal: _normal}}
+    }
+
+    pub fn from_path<P>(_base: Material, path: P, _strength: f32) -> Material
+    where P: AsRef<std::path::Path>
+    {
+        NormalMapped::new(_base, ShadingNormal::Tangent{tex: DynamicTexture::texture(path, DynamicTextureType::Linear), strength: _strength})
+    }
+
+    pub fn bump_from_path<P>(_base: Material, path: P, _scale: f32) -> Material
+    where P: AsRef<std::path::Path>
+    {
+        NormalMapped::new(_base, ShadingNormal::Bump{tex: DynamicTexture::texture(path, DynamicTextureType::Linear), scale: _scale})
+    }
+
+    // hit with the shading normal, on the same side as the geometric one
+    fn shading_hit(&self, hit: &HitInfo) -> HitInfo
+    {
+        let n = hit.normal;
+        let mut shading = *hit;
+
+        let perturbed = match &self.normal
+        {
+            ShadingNormal::Tangent {tex, strength} =>
+            {
+                // gram schmidt, the bitangent keeps the handedness of the uv mapping
+                let frame = if hit.tangent.square_length3() > 0.0
+                {
+                    let t = (hit.tangent - n * n.dot(&hit.tangent)).norm3();
+                    let b = n.cross3(&t);
+                    let b = if b.dot(&hit.bitangent) < 0.0 { -b } else { b };
+                    Frame{t: t, b: b, n: n}
+                }
+                else
+                {
+                    Frame::new(&n)
+                };
+
+                let m = tex.sample(hit) * 2.0 - 1.0;
+                let local = Vec4::from3(m.x() * strength, m.y() * strength, m.z().max(0.0));
+                frame.to_world(&local)
+            },
+            // https://www.pbr-book.org/3ed-2018/Materials/Bump_Mapping
+            ShadingNormal::Bump {tex, scale} =>
+            {
+                if hit.tangent.square_length3() <= 0.0 || hit.bitangent.square_length3() <= 0.0
+                {
+                    return shading;
+                }
+
+                // finite differences over roughly the ray footprint
+                let du = (0.5 * hit.footprint).max(1e-4);
+                let height = |u: f32, v: f32| { let mut h = *hit; h.u = u; h.v = v; tex.sample(&h).x() * scale };
+
+                let h = height(hit.u, hit.v);
+                let dhdu = (height(hit.u + du, hit.v) - h) / du;
+                let dhdv = (height(hit.u, hit.v + du) - h) / du;
+
+                let dpdu = hit.tangent + n * dhdu;
+                let dpdv = hit.bitangent + n * dhdv;
+                let bumped = dpdu.cross3(&dpdv);
+                if bumped.dot(&n) < 0.0 { -bumped } else { bumped }
+            }
+        };
+
+        if perturbed.square_length3() > 0.0
+        {
+            shading.normal = perturbed.norm3();
+        }
+        shading
+    }
+
+    // shading and geometric normal have to agree on reflection vs transmission, or light leaks through
+    fn consistent(hit: &HitInfo, shading: &HitInfo, wi: &Vec4, wo: &Vec4) -> bool
+    {
+        let geometric = wi.dot(&hit.normal) * wo.dot(&hit.normal);
+        let shaded = wi.dot(&shading.normal) * wo.dot(&shading.normal);
+        geometric * shaded > 0.0
+    }
+
+    // the integrators multiply with the geometric cosine, swap it for the shading one
+    fn cos_ratio(hit: &HitInfo, shading: &HitInfo, wi: &Vec4) -> f32
+    {
+        wi.dot(&shading.normal).abs() / wi.dot(&hit.normal).abs().max(1e-4)
+    }
+}
+
+impl Scatter for NormalMapped
+{
+    fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
+    {
+        scatter_bsdf(self, _r, _hit, _out_mat)
+    }
+}
+
+impl Bsdf for NormalMapped
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample>
+    {
+        let shading = self.shading_hit(hit);
+        let mut s = self.base.sample(wo, &shading)?;
+
+        if !NormalMapped::consistent(hit, &shading, &s.wi, wo)
+        {
+            return None;
+        }
+
+        s.f *= NormalMapped::cos_ratio(hit, &shading, &s.wi);
+        Some(s)
+    }
+
+    fn eval(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        let shading = self.shading_hit(hit);
+        if !NormalMapped::consistent(hit, &shading, wi, wo)
+        {
+            return Vec4::zero();
+        }
+
+        self.base.eval(wi, wo, &shading) * NormalMapped::cos_ratio(hit, &shading, wi)
+    }
+
+    fn pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> f32
+    {
+        let shading = self.shading_hit(hit);
+        if !NormalMapped::consistent(hit, &shading, wi, wo)
+        {
+            return 0.0;
+        }
+
+        self.base.pdf(wi, wo, &shading)
+    }
+
+    fn eval_pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> (Vec4, f32)
+    {
+        let shading = self.shading_hit(hit);
+        if !NormalMapped::consistent(hit, &shading, wi, wo)
+        {
+            return (Vec4::zero(), 0.0);
+        }
+
+        let (f, pdf) = self.base.eval_pdf(wi, wo, &shading);
+        (f * NormalMapped::cos_ratio(hit, &shading, wi), pdf)
+    }
+
+    fn flags(&self, hit: &HitInfo) -> BsdfFlags
+    {
+        self.base.flags(hit)
+    }
+
+    fn emission(&self, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        self.base.emission(wo, hit)
+    }
+}
+
+//######################################################################
+// AlphaMasked
+//######################################################################
+
+impl AlphaMasked
+{
+    pub fn new(_base: Material, _opacity: Texture, _channel: usize, _mode: AlphaMode) -> Material
+    {
+        Material::AlphaMasked{mat: AlphaMasked{base: Box::new(_base), opacity: _opacity, channel: _channel.min(3), mode: _mode}}
+    }
+
+    // alpha channel of an image
+    pub fn from_path<P>(_base: Material, path: P, _mode: AlphaMode) -> Material
+    where P: AsRef<std::path::Path>
+    {
+        AlphaMasked::new(_base, DynamicTexture::texture(path, DynamicTextureType::Linear), 3, _mode)
+    }
+
+    pub fn alpha_test(&self, hit: &HitInfo) -> bool
+    {
+        let alpha = self.opacity.sample(hit).get(self.channel);
+
+        match self.mode
+        {
+            AlphaMode::Stochastic => {alpha >= 1.0 || (alpha > 0.0 && random_f32() < alpha)},
+            AlphaMode::Threshold(cutoff) => {alpha >= cutoff}
+        }
+    }
+}
+
+impl Scatter for AlphaMasked
+{
+    fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
+    {
+        self.base.scatter(_r, _hit, _out_mat)
+    }
+}
+
+// surviving hits are shaded as fully opaque
+impl Bsdf for AlphaMasked
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample> { self.base.sample(wo, hit) }
+    fn eval(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> Vec4 { self.base.eval(wi, wo, hit) }
+    fn pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> f32 { self.base.pdf(wi, wo, hit) }
+    fn eval_pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> (Vec4, f32) { self.base.eval_pdf(wi, wo, hit) }
+    fn flags(&self, hit: &HitInfo) -> BsdfFlags { self.base.flags(hit) }
+    fn emission(&self, wo: &Vec4, hit: &HitInfo) -> Vec4 { self.base.emission(wo, hit) }
+}
+
+//######################################################################
+// Interface
+//######################################################################
+
+impl Interface
+{
+    pub fn new() -> Material
+    {
+        Material::Interface{mat: Interface{}}
+    }
+}
+
+impl Scatter for Interface
+{
+    fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
+    {
+        scatter_bsdf(self, _r, _hit, _out_mat)
+    }
+}
+
+impl Bsdf for Interface
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample>
+    {
+        let wi = -*wo;
+        Some(BsdfSample::new(wi, Vec4::from(1.0 / wi.dot(&hit.normal).abs().max(1e-6)), 1.0, self.flags(hit)))
+    }
+
+    fn eval(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> Vec4 { Vec4::zero() }
+    fn pdf(&self, _wi: &Vec4, _wo: &Vec4, _hit: &HitInfo) -> f32 { 0.0 }
+
+    fn flags(&self, _hit: &HitInfo) -> BsdfFlags
+    {
+        BsdfFlags::SPECULAR | BsdfFlags::TRANSMISSION
+    }
+}
+
+//######################################################################
+// Principled
+//######################################################################
+
+// texture inputs resolved at a hit point
+struct PrincipledParams
+{
+    base_color: Vec4,
+    spec_color: Vec4,
+    sheen_color: Vec4,
+    metallic: f32,
+    roughness: f32,
+    alpha: f32,
+    clearcoat: f32,
+    clearcoat_alpha: f32,
+    transmission: f32,
+    subsurface: f32,
+    eta: f32, // eta_t / eta_i of the side wo is on
+    diffuse_weight: f32,
+    specular_weight: f32,
+    // lobe selection probabilities: diffuse, specular, clearcoat, transmission
+    lobe_pdf: [f32; 4]
+}
+
+impl Principled
+{
+    pub fn new(r: f32, g: f32, b: f32, _metallic: f32, _roughness: f32) -> Material
+    {
+        Principled::from_texture(ConstantTexture::from(r, g, b).texture(), _metallic, _roughness)
+    }
+
+    pub fn from_path<P>(path: P, _type: DynamicTextureType, _metallic: f32, _roughness: f32) -> Material
+    where P: AsRef<std::path::Path>
+    {
+        Principled::from_texture(Texture::DynamicTexture{tex: DynamicTexture::new(path, _type)}, _metallic, _roughness)
+    }
+
+    pub fn from_texture(_base_color: Texture, _metallic: f32, _roughness: f32) -> Material
+    {
+        Material::Principled{mat: Principled{
+            base_color: _base_color,
+            metallic: ConstantTexture::scalar(_metallic).texture(),
+            roughness: ConstantTexture::scalar(_roughness).texture(),
+            specular: ConstantTexture::scalar(0.5).texture(),
+            specular_tint: ConstantTexture::scalar(0.0).texture(),
+            sheen: ConstantTexture::scalar(0.0).texture(),
+            sheen_tint: ConstantTexture::scalar(0.5).texture(),
+            clearcoat: ConstantTexture::scalar(0.0).texture(),
+            clearcoat_gloss: ConstantTexture::scalar(1.0).texture(),
+            transmission: ConstantTexture::scalar(0.0).texture(),
+            subsurface: ConstantTexture::scalar(0.0).texture(),
+            ior: 1.5
+        }}
+    }
+
+    // inputs are driven by the graph outputs named like the fields, the rest keeps the defaults
+    pub fn from_graph<P>(path: P) -> Material
+    where P: AsRef<std::path::Path>
+    {
+        let mut material = Principled::new(0.8, 0.8, 0.8, 0.0, 0.5);
+        let source = match GraphSource::from_path(path)
+        {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{}", e);
+                return material;
+            }
+        };
+
+        if let Material::Principled{mat} = &mut material
+        {
+            let mut inputs: [(&str, &mut Texture); 11] = [
+                ("base_color", &mut mat.base_color), ("metallic", &mut mat.metallic), ("roughness", &mut mat.roughness),
+                ("specular", &mut mat.specular), ("specular_tint", &mut mat.specular_tint), ("sheen", &mut mat.sheen),
+                ("sheen_tint", &mut mat.sheen_tint), ("clearcoat", &mut mat.clearcoat), ("clearcoat_gloss", &mut mat.clearcoat_gloss),
+                ("transmission", &mut mat.transmission), ("subsurface", &mut mat.subsurface)];
+
+            let mut unknown: std::vec::Vec<&str> = source.outputs().into_iter().filter(|name| inputs.iter().all(|(slot, _)| slot != name)).collect();
+            unknown.sort();
+            for name in unknown
+            {
+                eprintln!("graph output {} is not a principled input and is ignored", name);
+            }
+
+            for (slot, tex) in inputs.iter_mut()
+            {
+                if !source.has_output(slot) { continue; }
+                match source.compile(slot)
+                {
+                    Ok(graph) => **tex = Texture::Graph{tex: graph},
+                    Err(e) => eprintln!("{}", e)
+                }
+            }
+        }
+
+        material
+    }
+
+    // local frame around the normal facing wo, eta relative to the side wo is on
+    fn frame(&self, wo: &Vec4, _hit: &HitInfo) -> (Frame, f32)
+    {
+        if wo.dot(&_hit.normal) >= 0.0
+        {
+            (Frame::new(&_hit.normal), self.ior)
+        }
+        else
+        {
+            (Frame::new(&-_hit.normal), 1.0 / self.ior)
+        }
+    }
+
+    fn params(&self, _hit: &HitInfo, _eta: f32) -> PrincipledParams
+    {
+        let base = self.base_color.sample(_hit);
+        let metallic = self.metallic.sample(_hit).x().max(0.0).min(1.0);
+        let roughness = self.roughness.sample(_hit).x().max(0.0).min(1.0);
+        let transmission = self.transmission.sample(_hit).x().max(0.0).min(1.0);
+        let clearcoat = self.clearcoat.sample(_hit).x().max(0.0);
+
+        let lum = base.luminance();
+        let tint = if lum > 0.0 { base / lum } else { Vec4::one() };
+
+        let dielectric_spec = self.specular.sample(_hit).x() * 0.08 * Vec4::one().lerp(&tint, self.specular_tint.sample(_hit).x());
+        let spec_color = dielectric_spec.lerp(&base, metallic);
+        let sheen_color = self.sheen.sample(_hit).x() * Vec4::one().lerp(&tint, self.sheen_tint.sample(_hit).x());
+
+        let diffuse_weight = (1.0 - metallic) * (1.0 - transmission);
+        let specular_weight = 1.0 - (1.0 - metallic) * transmission;
+        let transmission_weight = (1.0 - metallic) * transmission;
+
+        let mut lobe_pdf = [
+            diffuse_weight,
+            specular_weight * (0.5 + 0.5 * spec_color.luminance().min(1.0)),
+            0.25 * clearcoat,
+            transmission_weight];
+
+        let total: f32 = lobe_pdf.iter().sum();
+        for p in lobe_pdf.iter_mut() { *p /= total; }
+
+        PrincipledParams
+        {
+            base_color: base,
+            spec_color: spec_color,
+            sheen_color: sheen_color,
+            metallic: metallic,
+            roughness: roughness,
+            alpha: (roughness * roughness).max(1e-3),
+            clearcoat: clearcoat,
+            clearcoat_alpha: 0.1 + (0.001 - 0.1) * self.clearcoat_gloss.sample(_hit).x(),
+            transmission: transmission,
+            subsurface: self.subsurface.sample(_hit).x(),
+            eta: _eta,
+            diffuse_weight: diffuse_weight,
+            specular_weight: specular_weight,
+            lobe_pdf: lobe_pdf
+        }
+    }
+}
+
+impl PrincipledParams
+{
+    // generalized half vector of a refraction pair, oriented towards +z
+    fn refraction_half(&self, wo: &Vec4, wi: &Vec4) -> Vec4
+    {
+        let h = (wo + self.eta * wi).norm();
+        if h.z() < 0.0 { -h } else { h }
+    }
+
+    // local directions, wo.z > 0
+    fn eval(&self, wo: &Vec4, wi: &Vec4) -> Vec4
+    {
+        let cos_o = wo.z();
+        let cos_i = wi.z();
+
+        if cos_i == 0.0 || cos_o <= 0.0 { return Vec4::zero(); }
+
+        let transmission_weight = (1.0 - self.metallic) * self.transmission;
+
+        if cos_i < 0.0
+        {
+            if transmission_weight <= 0.0 { return Vec4::zero(); }
+
+            let h = self.refraction_half(wo, wi);
+            let wo_h = wo.dot(&h);
+            let wi_h = wi.dot(&h);
+
+            if wo_h <= 0.0 || wi_h >= 0.0 { return Vec4::zero(); }
+
+            let f = fresnel_dielectric(wo_h, self.eta);
+            let denom = wo_h + self.eta * wi_h;
+            let btdf = (1.0 - f) * ggx_d(&h, self.alpha) * ggx_g(wi, wo, &h, self.alpha) * (wi_h * wo_h).abs() / ((cos_i * cos_o).abs() * denom * denom);
+
+            // tint is applied on entry and exit
+            return transmission_weight * btdf * self.base_color.sqrt3();
+        }
+
+        let h = (wo + wi).norm();
+        let wi_h = wi.dot(&h);
+        let fl = schlick_weight(cos_i);
+        let fv = schlick_weight(cos_o);
+        let fh = schlick_weight(wi_h);
+
+        let mut f = Vec4::zero();
+
+        if self.diffuse_weight > 0.0
+        {
+            // burley diffuse with retro-reflection and hanrahan-krueger inspired subsurface approximation
+            let fd90 = 0.5 + 2.0 * wi_h * wi_h * self.roughness;
+            let fd = (1.0 + (fd90 - 1.0) * fl) * (1.0 + (fd90 - 1.0) * fv);
+
+            let fss90 = wi_h * wi_h * self.roughness;
+            let fss = (1.0 + (fss90 - 1.0) * fl) * (1.0 + (fss90 - 1.0) * fv);
+            let ss = 1.25 * (fss * (1.0 / (cos_i + cos_o) - 0.5) + 0.5);
+
+            let diffuse = self.base_color * (fd + (ss - fd) * self.subsurface) / PI;
+            f += self.diffuse_weight * (diffuse + fh * self.sheen_color);
+        }
+
+        let d = ggx_d(&h, self.alpha);
+        let g = ggx_g(wi, wo, &h, self.alpha);
+        f += self.specular_weight * fresnel_schlick(&self.spec_color, wi_h) * d * g / (4.0 * cos_i * cos_o);
+
+        if self.clearcoat > 0.0
+        {
+            let dc = gtr1_d(&h, self.clearcoat_alpha);
+            let gc = ggx_g(wi, wo, &h, 0.25);
+            let fc = 0.04 + 0.96 * fh;
+            f += Vec4::from(0.25 * self.clearcoat * dc * gc * fc / (4.0 * cos_i * cos_o));
+        }
+
+        if transmission_weight > 0.0
+        {
+            let fr = fresnel_dielectric(wo.dot(&h), self.eta);
+            f += Vec4::from(transmission_weight * fr * d * g / (4.0 * cos_i * cos_o));
+        }
+
+        f
+    }
+
+    fn pdf(&self, wo: &Vec4, wi: &Vec4) -> f32
+    {
+        if wo.z() <= 0.0 || wi.z() == 0.0 { return 0.0; }
+
+        let [p_diffuse, p_specular, p_clearcoat, p_transmission] = self.lobe_pdf;
+
+        if wi.z() < 0.0
+        {
+            if p_transmission <= 0.0 { return 0.0; }
+
+            let h = self.refraction_half(wo, wi);
+            let wo_h = wo.dot(&h);
+            let wi_h = wi.dot(&h);
+
+            if wo_h <= 0.0 || wi_h >= 0.0 { return 0.0; }
+
+            let f = fresnel_dielectric(wo_h, self.eta);
+            let denom = wo_h + self.eta * wi_h;
+            let jacobian = self.eta * self.eta * wi_h.abs() / (denom * denom);
+
+            return p_transmission * (1.0 - f) * ggx_pdf(&h, self.alpha) * jacobian;
+        }
+
+        let h = (wo + wi).norm();
+        let jacobian = 1.0 / (4.0 * wo.dot(&h));
+
+        let mut pdf = p_diffuse * wi.z() / PI;
+        pdf += p_specular * ggx_pdf(&h, self.alpha) * jacobian;
+        pdf += p_clearcoat * gtr1_pdf(&h, self.clearcoat_alpha) * jacobian;
+
+        if p_transmission > 0.0
+        {
+            pdf += p_transmission * fresnel_dielectric(wo.dot(&h), self.eta) * ggx_pdf(&h, self.alpha) * jacobian;
+        }
+
+        pdf
+    }
+
+    // returns local wi, bsdf value, pdf and the sampled lobe
+    fn sample(&self, wo: &Vec4) -> Option<(Vec4, Vec4, f32, BsdfFlags)>
+    {
+        let [p_diffuse, p_specular, p_clearcoat, _] = self.lobe_pdf;
+        let (u1, u2) = (random_f32(), random_f32());
+        let lobe = random_f32();
+
+        let (wi, flags) = if lobe < p_diffuse
+        {
+            (random_cosine_hemisphere(), BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION)
+        }
+        else if lobe < p_diffuse + p_specular
+        {
+            let m = ggx_sample(self.alpha, u1, u2);
+            ((2.0 * wo.dot(&m) * m - wo).norm(), BsdfFlags::GLOSSY | BsdfFlags::REFLECTION)
+        }
+        else if lobe < p_diffuse + p_specular + p_clearcoat
+        {
+            let m = gtr1_sample(self.clearcoat_alpha, u1, u2);
+            ((2.0 * wo.dot(&m) * m - wo).norm(), BsdfFlags::GLOSSY | BsdfFlags::REFLECTION)
+        }
+        else
+        {
+            let m = ggx_sample(self.alpha, u1, u2);
+            let wo_m = wo.dot(&m);
+            if wo_m <= 0.0 { return None; }
+
+            if random_f32() < fresnel_dielectric(wo_m, self.eta)
+            {
+                ((2.0 * wo_m * m - wo).norm(), BsdfFlags::GLOSSY | BsdfFlags::REFLECTION)
+            }
+            else
+            {
+                (refract(wo, &m, 1.0 / self.eta)?, BsdfFlags::GLOSSY | BsdfFlags::TRANSMISSION)
+            }
+        };
+
+        // microfacet reflections can end up below and refractions above the surface, pdf would count them to the other lobe
+        if wi.z() == 0.0 || (wi.z() > 0.0) != flags.contains(BsdfFlags::REFLECTION) { return None; }
+
+        let pdf = self.pdf(wo, &wi);
+        if pdf <= 0.0 { return None; }
+
+        Some((wi, self.eval(wo, &wi), pdf, flags))
+    }
+}
+
+impl Scatter for Principled
+{
+    fn scatter(&self, _r: &mut Ray, _hit: &HitInfo, _out_mat: &mut MaterialInfo) -> bool
+    {
+        scatter_bsdf(self, _r, _hit, _out_mat)
+    }
+}
+
+impl Bsdf for Principled
+{
+    fn sample(&self, wo: &Vec4, hit: &HitInfo) -> Option<BsdfSample>
+    {
+        let (frame, eta) = self.frame(wo, hit);
+        let (wi, f, pdf, flags) = self.params(hit, eta).sample(&frame.to_local(wo))?;
+        Some(BsdfSample::new(frame.to_world(&wi), f, pdf, flags))
+    }
+
+    fn eval(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> Vec4
+    {
+        let (frame, eta) = self.frame(wo, hit);
+        self.params(hit, eta).eval(&frame.to_local(wo), &frame.to_local(wi))
+    }
+
+    fn pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> f32
+    {
+        let (frame, eta) = self.frame(wo, hit);
+        self.params(hit, eta).pdf(&frame.to_local(wo), &frame.to_local(wi))
+    }
+
+    fn eval_pdf(&self, wi: &Vec4, wo: &Vec4, hit: &HitInfo) -> (Vec4, f32)
+    {
+        let (frame, eta) = self.frame(wo, hit);
+        let params = self.params(hit, eta);
+        let (wo_local, wi_local) = (frame.to_local(wo), frame.to_local(wi));
+        (params.eval(&wo_local, &wi_local), params.pdf(&wo_local, &wi_local))
+    }
+
+    // the lobes only depend on metallic and transmission, the other inputs are not sampled
+    fn flags(&self, hit: &HitInfo) -> BsdfFlags
+    {
+        let metallic = self.metallic.sample(hit).x().max(0.0).min(1.0);
+        let transmission = self.transmission.sample(hit).x().max(0.0).min(1.0);
+
+        let mut flags = BsdfFlags::GLOSSY | BsdfFlags::REFLECTION;
+        if (1.0 - metallic) * (1.0 - transmission) > 0.0 { flags |= BsdfFlags::DIFFUSE; }
+        if (1.0 - metallic) * transmission > 0.0 { flags |= BsdfFlags::TRANSMISSION; }
+        flags
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn close(a: &Vec4, b: &Vec4) -> bool
+    {
+        (*a - *b).length() <= 1e-3 * (1.0 + b.length())
+    }
+
+    // samples agree with eval and pdf, pdf > 0 wherever f > 0 and the pdf integrates to the probability of a sample
+    fn check_bsdf(mat: &Material, wo: &Vec4)
+    {
+        let mut hit = HitInfo::new();
+        hit.normal = Vec4::from3(0.0, 0.0, 1.0);
+
+        let n = 50000;
+        let mut sampled = 0;
+        for _ in 0..n
+        {
+            if let Some(s) = mat.sample(wo, &hit)
+            {
+                sampled += 1;
+                assert!(!s.flags.is_delta() && s.pdf > 0.0);
+
+                let f = mat.eval(&s.wi, wo, &hit);
+                let pdf = mat.pdf(&s.wi, wo, &hit);
+                assert!((pdf - s.pdf).abs() <= 1e-3 * s.pdf);
+                assert!(close(&s.f, &f));
+                assert!(close(&s.weight(&hit.normal), &(f * s.wi.z().abs() / pdf)));
+
+                let (f_both, pdf_both) = mat.eval_pdf(&s.wi, wo, &hit);
+                assert!(close(&f_both, &f) && (pdf_both - pdf).abs() <= 1e-3 * pdf);
+            }
+        }
+
+        // midpoint rule on a grid that is uniform in z and phi, every cell has the same solid angle
+        let (nz, nphi) = (200, 400);
+        let mut integral = 0.0;
+        for i in 0..nz
+        {
+            let z = 1.0 - 2.0 * (i as f32 + 0.5) / nz as f32;
+            let r = (1.0 - z * z).sqrt();
+            for j in 0..nphi
+            {
+                let phi = 2.0 * PI * (j as f32 + 0.5) / nphi as f32;
+                let wi = Vec4::from3(r * phi.cos(), r * phi.sin(), z);
+                let f = mat.eval(&wi, wo, &hit);
+                let pdf = mat.pdf(&wi, wo, &hit);
+                if f.x() > 0.0 || f.y() > 0.0 || f.z() > 0.0 { assert!(pdf > 0.0); }
+                integral += pdf;
+            }
+        }
+        integral *= 4.0 * PI / (nz * nphi) as f32;
+
+        let expected = sampled as f32 / n as f32;
+        assert!((integral - expected).abs() < 0.01, "pdf integrates to {} but {} of the samples are valid", integral, expected);
+    }
+
+    fn dielectric(_roughness: f32) -> Material
+    {
+        let mut mat = Principled::new(1.0, 1.0, 1.0, 0.0, _roughness);
+        if let Material::Principled{mat} = &mut mat
+        {
+            mat.transmission = ConstantTexture::scalar(1.0).texture();
+        }
+        mat
+    }
+
+    #[test]
+    fn bsdf_sample_matches_eval()
+    {
+        let wo = Vec4::from3(0.6, 0.0, 0.8);
+
+        check_bsdf(&Lambertian::new(0.8, 0.5, 0.2), &wo);
+        check_bsdf(&Metal::new(0.9, 0.5, 0.5, 0.5), &wo);
+        check_bsdf(&Metal::new(1.0, 1.0, 1.0, 2.3), &wo);
+        check_bsdf(&dielectric(0.7), &wo);
+        check_bsdf(&dielectric(0.7), &-wo);
+        check_bsdf(&Principled::new(0.8, 0.5, 0.2, 0.3, 0.7), &wo);
+    }
+}
@@ -0,0 +1,469 @@
+use super::vec::*;
+use super::ray::*;
+use super::random::*;
+use super::microfacet::Frame;
+
+use std::f32::consts::PI;
+
+// https://www.pbr-book.org/3ed-2018/Light_Transport_II_Volume_Rendering
+
+#[derive(Copy, Clone)]
+pub struct HenyeyGreenstein
+{
+    pub g: f32 // asymmetry: -1 back scattering, 0 isotropic, 1 forward scattering
+}
+
+#[derive(Clone)]
+pub struct HomogeneousMedium
+{
+    pub sigma_a: Vec4, // absorption per unit distance
+    pub sigma_s: Vec4, // scattering per unit distance
+    pub phase: HenyeyGreenstein
+}
+
+// density grid spanning an axis aligned box, sigma_t has to be achromatic for delta tracking
+#[derive(Clone)]
+pub struct GridMedium
+{
+    pub sigma_t: f32, // extinction at density 1
+    pub albedo: Vec4, // sigma_s / sigma_t
+    pub phase: HenyeyGreenstein,
+    min: Vec4,
+    max: Vec4,
+    res: (usize, usize, usize),
+    density: std::vec::Vec<f32>,
+    max_density: f32
+}
+
+// result of sampling a free flight distance along a ray
+#[derive(Copy, Clone)]
+pub struct MediumSample
+{
+    pub scattered: bool, // false if the ray passed through to t_max
+    pub t: f32,
+    pub point: Vec4,
+    pub weight: Vec4 // transmittance * sigma_s / pdf, multiplies the path throughput
+}
+
+pub trait Participate
+{
+    // samples a real scattering event in [0, t_max) along r
+    fn sample(&self, r: &Ray, t_max: f32) -> MediumSample;
+    fn transmittance(&self, r: &Ray, t_max: f32) -> Vec4;
+    fn phase(&self) -> &HenyeyGreenstein;
+}
+
+//######################################################################
+// Medium
+//######################################################################
+
+#[derive(Clone)]
+pub enum Medium
+{
+    Homogeneous {med: HomogeneousMedium},
+    Grid {med: GridMedium}
+}
+
+impl Participate for Medium
+{
+    fn sample(&self, r: &Ray, t_max: f32) -> MediumSample
+    {
+        match self
+        {
+            Medium::Homogeneous {med} => {med.sample(r, t_max)},
+            Medium::Grid {med} => {med.sample(r, t_max)}
+        }
+    }
+
+    fn transmittance(&self, r: &Ray, t_max: f32) -> Vec4
+    {
+        match self
+        {
+            Medium::Homogeneous {med} => {med.transmittance(r, t_max)},
+            Medium::Grid {med} => {med.transmittance(r, t_max)}
+        }
+    }
+
+    fn phase(&self) -> &HenyeyGreenstein
+    {
+        match self
+        {
+            Medium::Homogeneous {med} => {med.phase()},
+            Medium::Grid {med} => {med.phase()}
+        }
+    }
+}
+
+impl MediumSample
+{
+    fn passed(t_max: f32, _weight: Vec4) -> MediumSample
+    {
+        MediumSample{scattered: false, t: t_max, point: Vec4::zero(), weight: _weight}
+    }
+}
+
+//######################################################################
+// HenyeyGreenstein
+//######################################################################
+
+impl HenyeyGreenstein
+{
+    pub fn new(_g: f32) -> HenyeyGreenstein
+    {
+        HenyeyGreenstein{g: _g.max(-0.99).min(0.99)}
+    }
+
+    // wo points back along the incoming ray, wi away from the scattering point
+    pub fn eval(&self, wo: &Vec4, wi: &Vec4) -> f32
+    {
+        let cos = -wo.dot(wi);
+        let g2 = self.g * self.g;
+        let denom = 1.0 + g2 - 2.0 * self.g * cos;
+
+        (1.0 - g2) / (4.0 * PI * denom * denom.max(0.0).sqrt())
+    }
+
+    // importance samples the phase function, returns wi and its pdf (which equals eval)
+    pub fn sample(&self, wo: &Vec4) -> (Vec4, f32)
+    {
+        let (u1, u2) = random_tuple(0.0, 1.0);
+
+        let cos = if self.g.abs() < 1e-3
+        {
+            1.0 - 2.0 * u1
+        }
+        else
+        {
+            let g2 = self.g * self.g;
+            let s = (1.0 - g2) / (1.0 - self.g + 2.0 * self.g * u1);
+            (1.0 + g2 - s * s) / (2.0 * self.g)
+        };
+
+        let sin = (1.0 - cos * cos).max(0.0).sqrt();
+        let phi = 2.0 * PI * u2;
+
+        // around the direction of travel
+        let wi = Frame::new(&-*wo).to_world(&Vec4::from3(sin * phi.cos(), sin * phi.sin(), cos.max(-1.0).min(1.0)));
+        (wi, self.eval(wo, &wi))
+    }
+}
+
+//######################################################################
+// HomogeneousMedium
+//######################################################################
+
+impl HomogeneousMedium
+{
+    pub fn new(_sigma_a: Vec4, _sigma_s: Vec4, _g: f32) -> Medium
+    {
+        Medium::Homogeneous{med: HomogeneousMedium{sigma_a: _sigma_a, sigma_s: _sigma_s, phase: HenyeyGreenstein::new(_g)}}
+    }
+
+    // grey fog with the given extinction per unit distance and single scattering albedo
+    pub fn fog(_density: f32, _albedo: f32, _g: f32) -> Medium
+    {
+        HomogeneousMedium::new(Vec4::from(_density * (1.0 - _albedo)), Vec4::from(_density * _albedo), _g)
+    }
+
+    fn sigma_t(&self) -> Vec4
+    {
+        self.sigma_a + self.sigma_s
+    }
+}
+
+fn average3(v: &Vec4) -> f32
+{
+    (v.x() + v.y() + v.z()) / 3.0
+}
+
+impl Participate for HomogeneousMedium
+{
+    // spectral mis: distance sampled with a randomly chosen channel, pdf averaged over all three
+    fn sample(&self, r: &Ray, t_max: f32) -> MediumSample
+    {
+        let len = r.direction.length3();
+        let sigma_t = self.sigma_t();
+
+        let channel = ((random_f32() * 3.0) as usize).min(2);
+        let st = sigma_t.get(channel);
+        let dist = if st > 0.0 { -(1.0 - random_f32()).ln() / st } else { std::f32::INFINITY };
+        let t = dist / len;
+
+        if t < t_max
+        {
+            let tr = (-sigma_t * dist).exp();
+            let pdf = average3(&(sigma_t * tr));
+            if pdf <= 0.0 { return MediumSample::passed(t_max, Vec4::zero()); }
+
+            return MediumSample{scattered: true, t: t, point: r.point_at(t), weight: tr * self.sigma_s / pdf};
+        }
+
+        let tr = (-sigma_t * (t_max * len)).exp();
+        let pdf = average3(&tr);
+        MediumSample::passed(t_max, if pdf > 0.0 { tr / pdf } else { Vec4::zero() })
+    }
+
+    fn transmittance(&self, r: &Ray, t_max: f32) -> Vec4
+    {
+        (-self.sigma_t() * (t_max * r.direction.length3())).exp()
+    }
+
+    fn phase(&self) -> &HenyeyGreenstein
+    {
+        &self.phase
+    }
+}
+
+//######################################################################
+// GridMedium
+//######################################################################
+
+impl GridMedium
+{
+    // density is laid out x fastest, then y, then z
+    pub fn new(_min: Vec4, _max: Vec4, _res: (usize, usize, usize), _density: std::vec::Vec<f32>, _sigma_t: f32, _albedo: Vec4, _g: f32) -> Medium
+    {
+        assert_eq!(_density.len(), _res.0 * _res.1 * _res.2);
+
+        let max_density = _density.iter().cloned().fold(0.0, f32::max);
+        Medium::Grid{med: GridMedium{
+            sigma_t: _sigma_t,
+            albedo: _albedo,
+            phase: HenyeyGreenstein::new(_g),
+            min: _min,
+            max: _max,
+            res: _res,
+            density: _density,
+            max_density: max_density}}
+    }
+
+    // evaluates f at the voxel centers, f gets positions in world space
+    pub fn from_fn<F>(_min: Vec4, _max: Vec4, _res: (usize, usize, usize), _sigma_t: f32, _albedo: Vec4, _g: f32, f: F) -> Medium
+    where F: Fn(&Vec4) -> f32
+    {
+        let (nx, ny, nz) = _res;
+        let extent = _max - _min;
+        let mut density = std::vec::Vec::with_capacity(nx * ny * nz);
+
+        for z in 0..nz
+        {
+            for y in 0..ny
+            {
+                for x in 0..nx
+                {
+                    let p = _min + extent * Vec4::from3((x as f32 + 0.5) / nx as f32, (y as f32 + 0.5) / ny as f32, (z as f32 + 0.5) / nz as f32);
+                    density.push(f(&p).max(0.0));
+                }
+            }
+        }
+
+        GridMedium::new(_min, _max, _res, density, _sigma_t, _albedo, _g)
+    }
+
+    fn voxel(&self, x: i32, y: i32, z: i32) -> f32
+    {
+        let (nx, ny, nz) = self.res;
+        if x < 0 || y < 0 || z < 0 || x >= nx as i32 || y >= ny as i32 || z >= nz as i32
+        {
+            return 0.0;
+        }
+
+        self.density[(z as usize * ny + y as usize) * nx + x as usize]
+    }
+
+    // trilinear lookup at a world space position
+    pub fn density_at(&self, p: &Vec4) -> f32
+    {
+        let (nx, ny, nz) = self.res;
+        let g = (p - self.min) / (self.max - self.min) * Vec4::from3(nx as f32, ny as f32, nz as f32) - 0.5;
+        let (gx, gy, gz) = g.extract_xyz();
+
+        let (x, y, z) = (gx.floor(), gy.floor(), gz.floor());
+        let (fx, fy, fz) = (gx - x, gy - y, gz - z);
+        let (x, y, z) = (x as i32, y as i32, z as i32);
+
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+        let d00 = lerp(self.voxel(x, y, z), self.voxel(x + 1, y, z), fx);
+        let d10 = lerp(self.voxel(x, y + 1, z), self.voxel(x + 1, y + 1, z), fx);
+        let d01 = lerp(self.voxel(x, y, z + 1), self.voxel(x + 1, y, z + 1), fx);
+        let d11 = lerp(self.voxel(x, y + 1, z + 1), self.voxel(x + 1, y + 1, z + 1), fx);
+
+        lerp(lerp(d00, d10, fy), lerp(d01, d11, fy), fz)
+    }
+
+    // parametric overlap of the ray with the grid bounds
+    fn clip(&self, r: &Ray, t_max: f32) -> Option<(f32, f32)>
+    {
+        let mut t0 = 0.0f32;
+        let mut t1 = t_max;
+
+        for axis in 0..3
+        {
+            let inv = 1.0 / r.direction.get(axis);
+            let mut near = (self.min.get(axis) - r.origin.get(axis)) * inv;
+            let mut far = (self.max.get(axis) - r.origin.get(axis)) * inv;
+            if near > far { std::mem::swap(&mut near, &mut far); }
+
+            // nan from 0 * inf means the ray is parallel to and on the slab
+            if !near.is_nan() { t0 = t0.max(near); }
+            if !far.is_nan() { t1 = t1.min(far); }
+            if t0 > t1 { return None; }
+        }
+
+        Some((t0, t1))
+    }
+}
+
+impl Participate for GridMedium
+{
+    // delta tracking: tentative collisions against the majorant are accepted with density / max_density
+    fn sample(&self, r: &Ray, t_max: f32) -> MediumSample
+    {
+        let majorant = self.max_density * self.sigma_t * r.direction.length3();
+        let (t0, t1) = match self.clip(r, t_max)
+        {
+            Some(range) if majorant > 0.0 => range,
+            _ => return MediumSample::passed(t_max, Vec4::one())
+        };
+
+        let mut t = t0;
+        loop
+        {
+            t -= (1.0 - random_f32()).ln() / majorant;
+            if t >= t1
+            {
+                return MediumSample::passed(t_max, Vec4::one());
+            }
+
+            let point = r.point_at(t);
+            if self.density_at(&point) > random_f32() * self.max_density
+            {
+                return MediumSample{scattered: true, t: t, point: point, weight: self.albedo};
+            }
+        }
+    }
+
+    // ratio tracking
+    fn transmittance(&self, r: &Ray, t_max: f32) -> Vec4
+    {
+        let majorant = self.max_density * self.sigma_t * r.direction.length3();
+        let (t0, t1) = match self.clip(r, t_max)
+        {
+            Some(range) if majorant > 0.0 => range,
+            _ => return Vec4::one()
+        };
+
+        let mut tr = 1.0f32;
+        let mut t = t0;
+        loop
+        {
+            t -= (1.0 - random_f32()).ln() / majorant;
+            if t >= t1 || tr <= 0.0
+            {
+                return Vec4::from(tr.max(0.0));
+            }
+
+            tr *= 1.0 - self.density_at(&r.point_at(t)) / self.max_density;
+        }
+    }
+
+    fn phase(&self) -> &HenyeyGreenstein
+    {
+        &self.phase
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn henyey_greenstein_pdf()
+    {
+        let wo = Vec4::from3(0.0, 0.6, -0.8);
+
+        for &g in [-0.7, 0.0, 0.3, 0.9].iter()
+        {
+            let phase = HenyeyGreenstein::new(g);
+
+            // depends on the angle only, midpoint rule over its cosine
+            let n = 100000;
+            let mut integral = 0.0;
+            for i in 0..n
+            {
+                let cos = -1.0 + 2.0 * (i as f32 + 0.5) / n as f32;
+                let wi = Frame::new(&-wo).to_world(&Vec4::from3((1.0 - cos * cos).sqrt(), 0.0, cos));
+                integral += phase.eval(&wo, &wi) * 2.0 * PI * 2.0 / n as f32;
+            }
+            assert!((integral - 1.0).abs() < 1e-3, "g {} integrates to {}", g, integral);
+
+            // the mean cosine of henyey greenstein is g
+            let samples = 100000;
+            let mut mean_cos = 0.0;
+            for _ in 0..samples
+            {
+                let (wi, pdf) = phase.sample(&wo);
+                assert!((pdf - phase.eval(&wo, &wi)).abs() <= 1e-4 * pdf);
+                mean_cos += -wo.dot(&wi) / samples as f32;
+            }
+            assert!((mean_cos - g).abs() < 0.02, "g {} mean cosine {}", g, mean_cos);
+        }
+    }
+
+    #[test]
+    fn homogeneous_weights()
+    {
+        let sigma_a = Vec4::from3(0.2, 0.5, 1.0);
+        let sigma_s = Vec4::from3(0.8, 0.4, 0.1);
+        let medium = HomogeneousMedium::new(sigma_a, sigma_s, 0.0);
+        let r = Ray::new(Vec4::zero(), Vec4::from3(0.0, 0.0, 2.0));
+        let t_max = 0.5;
+
+        // passing through estimates transmittance, scattering estimates sigma_s / sigma_t * (1 - transmittance)
+        let samples = 200000;
+        let (mut passed, mut scattered) = (Vec4::zero(), Vec4::zero());
+        for _ in 0..samples
+        {
+            let s = medium.sample(&r, t_max);
+            if s.scattered { scattered += s.weight; } else { passed += s.weight; }
+        }
+        passed = passed / samples as f32;
+        scattered = scattered / samples as f32;
+
+        let tr = medium.transmittance(&r, t_max);
+        let single = sigma_s / (sigma_a + sigma_s) * (Vec4::one() - tr);
+        for c in 0..3
+        {
+            assert!((passed.get(c) - tr.get(c)).abs() < 0.01, "channel {} passed {} transmittance {}", c, passed.get(c), tr.get(c));
+            assert!((scattered.get(c) - single.get(c)).abs() < 0.01, "channel {} scattered {} expected {}", c, scattered.get(c), single.get(c));
+        }
+    }
+
+    #[test]
+    fn grid_constant_density()
+    {
+        // constant density with a denser corner voxel so the majorant is above it, the ray stays away from the corner and the borders
+        let res = 8;
+        let mut density = vec![0.5; res * res * res];
+        density[0] = 2.0;
+        let medium = GridMedium::new(Vec4::zero(), Vec4::one(), (res, res, res), density, 2.0, Vec4::one(), 0.0);
+
+        let r = Ray::new(Vec4::from3(0.5, 0.5, 0.5), Vec4::from3(1.0, 0.0, 0.0));
+        let t_max = 0.3;
+        let expected = (-2.0f32 * 0.5 * t_max).exp();
+
+        let samples = 50000;
+        let mut tr = 0.0;
+        let mut passed = 0;
+        for _ in 0..samples
+        {
+            tr += medium.transmittance(&r, t_max).x() / samples as f32;
+            if !medium.sample(&r, t_max).scattered { passed += 1; }
+        }
+
+        assert!((tr - expected).abs() < 0.01, "ratio tracking {} expected {}", tr, expected);
+        assert!((passed as f32 / samples as f32 - expected).abs() < 0.01, "delta tracking {} expected {}", passed as f32 / samples as f32, expected);
+    }
+}
@@ -0,0 +1,153 @@
+use super::vec::*;
+use std::f32::consts::PI;
+
+// shading frame, local z = normal
+#[derive(Copy, Clone)]
+pub struct Frame
+{
+    pub t: Vec4,
+    pub b: Vec4,
+    pub n: Vec4
+}
+
+impl Frame
+{
+    pub fn new(_normal: &Vec4) -> Frame
+    {
+        let (_t, _b) = _normal.orthonormal_basis();
+        Frame{t: _t, b: _b, n: *_normal}
+    }
+
+    pub fn to_local(&self, v: &Vec4) -> Vec4
+    {
+        Vec4::from3(v.dot(&self.t), v.dot(&self.b), v.dot(&self.n))
+    }
+
+    pub fn to_world(&self, v: &Vec4) -> Vec4
+    {
+        v.x() * self.t + v.y() * self.b + v.z() * self.n
+    }
+}
+
+#[inline]
+pub fn schlick_weight(cos_theta: f32) -> f32
+{
+    let m = (1.0 - cos_theta).max(0.0).min(1.0);
+    let m2 = m * m;
+    m2 * m2 * m
+}
+
+#[inline]
+pub fn fresnel_schlick(f0: &Vec4, cos_theta: f32) -> Vec4
+{
+    f0.lerp(&Vec4::one(), schlick_weight(cos_theta))
+}
+
+// unpolarized fresnel reflectance for a dielectric interface, eta = eta_t / eta_i
+pub fn fresnel_dielectric(cos_theta_i: f32, eta: f32) -> f32
+{
+    let cos_i = cos_theta_i.abs().min(1.0);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+
+    if sin2_t >= 1.0
+    {
+        return 1.0; // total internal reflection
+    }
+
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let rs = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    let rp = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+
+    0.5 * (rs * rs + rp * rp)
+}
+
+// refract local direction wo around microfacet normal m (same hemisphere as wo), eta = eta_i / eta_t
+pub fn refract(wo: &Vec4, m: &Vec4, eta: f32) -> Option<Vec4>
+{
+    let cos_i = wo.dot(m);
+    let sin2_t = eta * eta * (1.0 - cos_i * cos_i).max(0.0);
+
+    if sin2_t >= 1.0
+    {
+        return None;
+    }
+
+    let cos_t = (1.0 - sin2_t).sqrt();
+    Some((-eta * wo + (eta * cos_i - cos_t) * m).norm())
+}
+
+//######################################################################
+// GGX / Trowbridge-Reitz
+//######################################################################
+
+// https://www.cs.cornell.edu/~srm/publications/EGSR07-btdf.pdf
+pub fn ggx_d(m: &Vec4, alpha: f32) -> f32
+{
+    let cos2 = m.z() * m.z();
+    if m.z() <= 0.0 { return 0.0; }
+
+    let a2 = alpha * alpha;
+    let d = cos2 * (a2 - 1.0) + 1.0;
+    a2 / (PI * d * d)
+}
+
+pub fn ggx_g1(v: &Vec4, m: &Vec4, alpha: f32) -> f32
+{
+    let cos = v.z();
+    if v.dot(m) * cos <= 0.0 { return 0.0; }
+
+    let cos2 = cos * cos;
+    let tan2 = (1.0 - cos2).max(0.0) / cos2;
+    2.0 / (1.0 + (1.0 + alpha * alpha * tan2).sqrt())
+}
+
+pub fn ggx_g(wi: &Vec4, wo: &Vec4, m: &Vec4, alpha: f32) -> f32
+{
+    ggx_g1(wi, m, alpha) * ggx_g1(wo, m, alpha)
+}
+
+// samples a microfacet normal proportional to D(m) * cos(theta_m)
+pub fn ggx_sample(alpha: f32, u1: f32, u2: f32) -> Vec4
+{
+    let tan2 = alpha * alpha * u1 / (1.0 - u1).max(1e-6);
+    let cos = 1.0 / (1.0 + tan2).sqrt();
+    let sin = (1.0 - cos * cos).max(0.0).sqrt();
+    let phi = 2.0 * PI * u2;
+
+    Vec4::from3(sin * phi.cos(), sin * phi.sin(), cos)
+}
+
+pub fn ggx_pdf(m: &Vec4, alpha: f32) -> f32
+{
+    ggx_d(m, alpha) * m.z().abs()
+}
+
+//######################################################################
+// GTR1 (clearcoat)
+//######################################################################
+
+// https://disney-animation.s3.amazonaws.com/library/s2012_pbs_disney_brdf_notes_v2.pdf
+pub fn gtr1_d(m: &Vec4, alpha: f32) -> f32
+{
+    if m.z() <= 0.0 { return 0.0; }
+    if alpha >= 1.0 { return 1.0 / PI; }
+
+    let a2 = alpha * alpha;
+    let t = 1.0 + (a2 - 1.0) * m.z() * m.z();
+    (a2 - 1.0) / (PI * a2.ln() * t)
+}
+
+pub fn gtr1_sample(alpha: f32, u1: f32, u2: f32) -> Vec4
+{
+    let a2 = alpha * alpha;
+    let cos = if alpha >= 1.0 { (1.0 - u1).sqrt() } else { ((1.0 - a2.powf(1.0 - u1)) / (1.0 - a2)).max(0.0).sqrt() };
+    let sin = (1.0 - cos * cos).max(0.0).sqrt();
+    let phi = 2.0 * PI * u2;
+
+    Vec4::from3(sin * phi.cos(), sin * phi.sin(), cos)
+}
+
+pub fn gtr1_pdf(m: &Vec4, alpha: f32) -> f32
+{
+    gtr1_d(m, alpha) * m.z().abs()
+}
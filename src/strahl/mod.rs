@@ -7,10 +7,26 @@ pub mod quaternion;
 pub mod ray;
 pub mod hit;
 pub mod primitives;
+pub mod shapes;
+pub mod sdf;
+pub mod csg;
 pub mod camera;
 pub mod scene;
 pub mod material;
+pub mod microfacet;
+pub mod bsdf;
+pub mod settings;
+pub mod integrator;
+pub mod bdpt;
+pub mod photon;
+pub mod medium;
+pub mod light;
+pub mod distribution;
+pub mod sky;
+pub mod sky_data;
 pub mod random;
 pub mod texture;
+pub mod procedural;
+pub mod graph;
 pub mod tonemap;
 pub mod as3dcamera;
\ No newline at end of file
@@ -0,0 +1,295 @@
+use super::vec::*;
+use super::hit::*;
+use super::scene::*;
+use super::bsdf::*;
+use super::settings::*;
+use super::material::*;
+use super::ray::*;
+use super::camera::Camera;
+use super::light::Emitter;
+use super::integrator::*;
+
+use rayon::prelude::*;
+use std::f32::consts::PI;
+
+// http://graphics.stanford.edu/~henrik/papers/ewr7/egwr96.pdf
+
+#[derive(Copy, Clone)]
+pub struct Photon
+{
+    pub point: Vec4,
+    pub wi: Vec4, // towards the surface the photon came from
+    pub power: Vec4,
+    axis: usize // split axis of the kd-tree node
+}
+
+//######################################################################
+// PhotonMap
+//######################################################################
+
+// balanced kd-tree stored in place, the median of each slice is the node
+pub struct PhotonMap
+{
+    photons: std::vec::Vec<Photon>
+}
+
+impl PhotonMap
+{
+    pub fn new() -> PhotonMap
+    {
+        PhotonMap{photons: std::vec::Vec::new()}
+    }
+
+    pub fn from_photons(_photons: std::vec::Vec<Photon>) -> PhotonMap
+    {
+        let mut map = PhotonMap{photons: _photons};
+        PhotonMap::build(&mut map.photons[..]);
+        map
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.photons.is_empty()
+    }
+
+    fn build(photons: &mut [Photon])
+    {
+        if photons.len() <= 1
+        {
+            return;
+        }
+
+        // split along the largest extent
+        let mut min = photons[0].point;
+        let mut max = photons[0].point;
+        for p in photons.iter()
+        {
+            min = min.min(&p.point);
+            max = max.max(&p.point);
+        }
+
+        let extent = max - min;
+        let axis = if extent.x() >= extent.y() && extent.x() >= extent.z() { 0 } else if extent.y() >= extent.z() { 1 } else { 2 };
+
+        photons.sort_unstable_by(|a, b| a.point.get(axis).partial_cmp(&b.point.get(axis)).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mid = photons.len() / 2;
+        photons[mid].axis = axis;
+
+        let (left, right) = photons.split_at_mut(mid);
+        PhotonMap::build(left);
+        PhotonMap::build(&mut right[1..]);
+    }
+
+    fn gather_range<'a>(photons: &'a [Photon], point: &Vec4, radius2: f32, out: &mut std::vec::Vec<(f32, &'a Photon)>)
+    {
+        if photons.is_empty()
+        {
+            return;
+        }
+
+        let mid = photons.len() / 2;
+        let node = &photons[mid];
+
+        let dist2 = (node.point - point).square_length3();
+        if dist2 <= radius2
+        {
+            out.push((dist2, node));
+        }
+
+        if photons.len() == 1
+        {
+            return;
+        }
+
+        let diff = point.get(node.axis) - node.point.get(node.axis);
+        let (near, far) = if diff < 0.0 { (&photons[..mid], &photons[mid + 1..]) } else { (&photons[mid + 1..], &photons[..mid]) };
+
+        PhotonMap::gather_range(near, point, radius2, out);
+        if diff * diff <= radius2
+        {
+            PhotonMap::gather_range(far, point, radius2, out);
+        }
+    }
+
+    // up to max_count nearest photons within radius, returns the squared radius actually covered
+    pub fn gather(&self, point: &Vec4, radius: f32, max_count: usize) -> (std::vec::Vec<&Photon>, f32)
+    {
+        let mut found = std::vec::Vec::new();
+        let mut radius2 = radius * radius;
+
+        PhotonMap::gather_range(&self.photons[..], point, radius2, &mut found);
+
+        if found.len() > max_count && max_count > 0
+        {
+            found.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            found.truncate(max_count);
+            radius2 = found[max_count - 1].0;
+        }
+
+        (found.into_iter().map(|(_, p)| p).collect(), radius2)
+    }
+}
+
+//######################################################################
+// PhotonMapper
+//######################################################################
+
+// path tracer with next event estimation, caustics (light -> specular+ -> surface) come from a photon map instead
+pub struct PhotonMapper
+{
+    pub settings: TraceSettings,
+    pub caustics: PhotonMap
+}
+
+impl PhotonMapper
+{
+    pub fn new(_settings: &TraceSettings) -> PhotonMapper
+    {
+        PhotonMapper{settings: *_settings, caustics: PhotonMap::new()}
+    }
+
+    // follows one photon from a light through delta bounces, stored at the first surface that can gather it
+    fn trace_photon(&self, scn: &Scene, scale: f32) -> Option<Photon>
+    {
+        let emission = scn.sample_emission()?;
+
+        let mut power = emission.power() * scale;
+        let offset = if emission.normal.dot(&emission.dir) > 0.0 { emission.normal } else { -emission.normal };
+        let mut ray = Ray::new(emission.point + offset * RAY_EPSILON, emission.dir);
+
+        for depth in 0..self.settings.max_specular_depth + 1
+        {
+            let mut hit = HitInfo::new();
+            if !scn.hit(&ray, &mut hit, 0.0, MAX_DISTANCE)
+            {
+                return None;
+            }
+
+            let mat = scn.get_mat(hit.material);
+            let wo = -ray.direction.norm();
+
+            if mat.flags(&hit).is_non_delta()
+            {
+                // direct illumination is handled by light sampling
+                if depth == 0 { return None; }
+                return Some(Photon{point: hit.point, wi: wo, power: power, axis: 0});
+            }
+
+            match mat.sample(&wo, &hit)
+            {
+                Some(s) if s.flags.is_delta() =>
+                {
+                    power *= s.weight(&hit.normal);
+                    ray = hit.spawn_ray(&s.wi);
+                },
+                _ => return None
+            }
+        }
+
+        None
+    }
+
+    // density estimate of the caustic photons around hit
+    fn estimate_caustics(&self, mat: &Material, hit: &HitInfo, wo: &Vec4) -> Vec4
+    {
+        let (photons, radius2) = self.caustics.gather(&hit.point, self.settings.photon_radius, self.settings.photon_gather as usize);
+        if photons.is_empty() || radius2 <= 0.0
+        {
+            return Vec4::zero();
+        }
+
+        let mut flux = Vec4::zero();
+        for p in photons
+        {
+            flux += mat.eval(&p.wi, wo, hit) * p.power;
+        }
+
+        flux / (PI * radius2)
+    }
+}
+
+impl Integrator for PhotonMapper
+{
+    fn preprocess(&mut self, scn: &Scene)
+    {
+        let count = self.settings.photon_count;
+        let scale = 1.0 / count as f32;
+
+        let photons: std::vec::Vec<Photon> = (0..count).into_par_iter().filter_map(|_| self.trace_photon(scn, scale)).collect();
+
+        self.caustics = PhotonMap::from_photons(photons);
+    }
+
+    fn li(&self, scn: &Scene, _cam: &dyn Camera, r: &mut RayInfo) -> Vec4
+    {
+        let mut delta_bounce = true;
+        let mut bsdf_pdf = 0.0;
+        // once the path has gathered photons, emission reached through delta bounces is already in the map
+        let mut gathered = false;
+
+        loop
+        {
+            let mut hit = HitInfo::new();
+            r.depth += 1;
+
+            let found = scn.hit(&r.ray, &mut hit, 0.0, MAX_DISTANCE);
+
+            let lights = scn.light_emission(&r.ray, if found { hit.depth } else { std::f32::INFINITY }, &r.ray.origin, r.is_primary(),
+                |light, pdf| if delta_bounce && gathered && !light.is_infinite() { 0.0 } else if delta_bounce { 1.0 } else { power_heuristic(bsdf_pdf, pdf) });
+            r.add_emission(&lights);
+
+            if !found
+            {
+                // the miss material does not emit photons, keep it
+                r.add_emission(&scn.get_miss_mat().emission(&-r.ray.direction.norm(), &hit));
+                break;
+            }
+
+            let mat = scn.get_mat(hit.material);
+            let wo = -r.ray.direction.norm();
+
+            let mut emission = mat.emission(&wo, &hit);
+            if delta_bounce && gathered
+            {
+                emission = Vec4::zero();
+            }
+            else if !delta_bounce && emission != Vec4::zero()
+            {
+                emission *= power_heuristic(bsdf_pdf, scn.light_pdf(&r.ray.origin, &-wo, hit.object));
+            }
+            r.add_emission(&emission);
+
+            if mat.flags(&hit).is_non_delta()
+            {
+                let direct = sample_direct_light(scn, mat, &hit, &wo) + self.estimate_caustics(mat, &hit, &wo);
+                r.add_emission(&direct);
+                gathered = true;
+            }
+
+            match mat.sample(&wo, &hit)
+            {
+                Some(s) if r.bounce(s.flags, &self.settings) =>
+                {
+                    r.throughput *= s.weight(&hit.normal);
+                    r.ray = hit.spawn_ray(&s.wi);
+                    delta_bounce = s.flags.is_delta();
+                    bsdf_pdf = s.pdf;
+
+                    if !r.russian_roulette(&self.settings)
+                    {
+                        break;
+                    }
+                },
+                _ => break
+            }
+        }
+
+        r.radiance
+    }
+}
@@ -2,8 +2,33 @@ use super::hit::*;
 use super::vec::*;
 use super::ray::*;
 use super::quaternion::*;
+use super::microfacet::Frame;
+use super::random::*;
+use super::shapes::*;
+use super::sdf::Sdf;
+use super::csg::Csg;
 
-use packed_simd::{m16x4, f32x4};
+// 1 + 2 gamma(3), bounds the rounding error of the slab distances
+const SLAB_ROUNDING: f32 = 1.0 + 6.0 * std::f32::EPSILON;
+
+// point on a surface sampled as seen from a reference point
+#[derive(Copy, Clone)]
+pub struct SurfaceSample
+{
+    pub hit: HitInfo,
+    pub pdf: f32 // w.r.t. solid angle at the reference point
+}
+
+pub trait SampleSurface
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>;
+    // solid angle pdf of sampling direction dir (normalized) from origin
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32;
+
+    // uniform point on the surface, pdf w.r.t. area
+    fn sample_area(&self) -> Option<SurfaceSample>;
+    fn area(&self) -> f32;
+}
 
 #[derive(Copy, Clone)]
 pub struct Sphere
@@ -18,42 +43,122 @@ pub struct Plane
 {
     pos: Vec4,
     normal: Vec4,
-    //compute_uv: bool
+    tangent: Vec4, // u direction, orthogonal to the normal
+    bitangent: Vec4,
+    tile: f32, // world units per uv unit
+    compute_uv: bool,
+    two_sided: bool
 }
 
 #[derive(Copy, Clone)]
 pub struct BBox
 {
     center: Vec4,
-    dimensions: Vec4,
-    inv_dimensions: Vec4
+    dimensions: Vec4, // half extents
+    inv_dimensions: Vec4,
+    compute_uv: bool
+}
+
+// parallelogram spanned by two half edges, the front faces along u x v
+#[derive(Copy, Clone)]
+pub struct Rect
+{
+    center: Vec4,
+    u: Vec4,
+    v: Vec4,
+    normal: Vec4,
+    area: f32,
+    two_sided: bool // one sided shapes are invisible from behind and only emit to the front
+}
+
+// annulus if inner_radius > 0
+#[derive(Copy, Clone)]
+pub struct Disk
+{
+    center: Vec4,
+    frame: Frame,
+    radius: f32,
+    inner_radius: f32,
+    two_sided: bool
+}
+
+// box rotated around its center
+#[derive(Copy, Clone)]
+pub struct OrientedBox
+{
+    bbox: BBox,
+    rotation: Quat
 }
 
 //######################################################################
 // Object
 //######################################################################
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub enum Primitive
 {
     Sphere {obj: Sphere},
     Plane {obj: Plane},
-    BBox {obj: BBox}
+    BBox {obj: BBox},
+    OrientedBox {obj: OrientedBox},
+    Rect {obj: Rect},
+    Disk {obj: Disk},
+    Cylinder {obj: Cylinder},
+    Cone {obj: Cone},
+    Torus {obj: Torus},
+    Capsule {obj: Capsule},
+    Paraboloid {obj: Paraboloid},
+    Sdf {obj: Sdf},
+    Csg {obj: Csg}
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Object
 {
     pub prim: Primitive,
     pub mat: u32,
-    pub rot: Quat
+    pub rot: Quat,
+    pub medium: Option<u32> // fills the interior, needs a closed surface with outward normals
 }
 
 impl Object
 {
     pub fn new(_prim: &Primitive, _mat: u32, _rot: Quat) -> Object
     {
-        Object{mat: _mat, rot: _rot, prim: *_prim}
+        Object{mat: _mat, rot: _rot, prim: _prim.clone(), medium: None}
+    }
+
+    // whether emission leaves both sides of the surface, closed shapes count as two sided
+    pub fn two_sided(&self) -> bool
+    {
+        match &self.prim
+        {
+            Primitive::Rect{obj} => { obj.two_sided },
+            Primitive::Disk{obj} => { obj.two_sided },
+            Primitive::Plane{obj} => { obj.two_sided },
+            _ => { true }
+        }
+    }
+
+    // world space bounds, None for unbounded primitives
+    pub fn bounds(&self) -> Option<BBox>
+    {
+        match &self.prim
+        {
+            Primitive::Sphere{obj} => { Some(BBox::new(obj.pos, Vec4::from3(obj.radius, obj.radius, obj.radius))) },
+            Primitive::Plane{..} => { None },
+            Primitive::BBox{obj} => { Some(*obj) },
+            Primitive::OrientedBox{obj} => { Some(obj.bounds()) },
+            Primitive::Rect{obj} => { Some(obj.bounds()) },
+            Primitive::Disk{obj} => { Some(obj.bounds()) },
+            Primitive::Cylinder{obj} => { Some(obj.bounds()) },
+            Primitive::Cone{obj} => { Some(obj.bounds()) },
+            Primitive::Torus{obj} => { Some(obj.bounds()) },
+            Primitive::Capsule{obj} => { Some(obj.bounds()) },
+            Primitive::Paraboloid{obj} => { Some(obj.bounds()) },
+            Primitive::Sdf{obj} => { Some(obj.bounds()) },
+            Primitive::Csg{obj} => { obj.bounds() }
+        }
     }
 }
 
@@ -63,25 +168,118 @@ impl Hitable for Object
 
         //let r = _r.rotate(&self.rot);
         let r = _r;
+        out.uv_scale = 0.0;
+        out.tangent = Vec4::zero();
+        out.bitangent = Vec4::zero();
 
-        let hit = match self.prim 
+        let hit = match &self.prim 
         {
             Primitive::Sphere{obj} => { obj.hit(&r, out, min, max) },
             Primitive::Plane{obj} => { obj.hit(&r, out, min, max) },
-            Primitive::BBox{obj} => { obj.hit(&r, out, min, max) }
+            Primitive::BBox{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::OrientedBox{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Rect{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Disk{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Cylinder{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Cone{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Torus{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Capsule{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Paraboloid{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Sdf{obj} => { obj.hit(&r, out, min, max) },
+            Primitive::Csg{obj} => { obj.hit(&r, out, min, max) }
         };
 
         if hit
         {
             //out.point = self.rot.inverse_rotate_unit(&out.point);
             //out.normal = self.rot.inverse_rotate_unit(&out.normal).norm3();
-            out.material = self.mat;
+            // csg surfaces keep the material of their operand
+            match &self.prim
+            {
+                Primitive::Csg{..} => {},
+                _ => { out.material = self.mat; }
+            }
         }
 
         hit
     }
 }
 
+impl SampleSurface for Object
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        let sample = match &self.prim
+        {
+            Primitive::Sphere{obj} => { obj.sample_surface(origin) },
+            Primitive::Rect{obj} => { obj.sample_surface(origin) },
+            Primitive::Disk{obj} => { obj.sample_surface(origin) },
+            Primitive::Cylinder{obj} => { obj.sample_surface(origin) },
+            Primitive::Cone{obj} => { obj.sample_surface(origin) },
+            Primitive::Capsule{obj} => { obj.sample_surface(origin) },
+            Primitive::Torus{obj} => { obj.sample_surface(origin) },
+            Primitive::Paraboloid{obj} => { obj.sample_surface(origin) },
+            Primitive::Plane{..} => { None }, // infinite
+            _ => { None }
+        };
+
+        sample.map(|mut s| { s.hit.material = self.mat; s })
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        match &self.prim
+        {
+            Primitive::Sphere{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Rect{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Disk{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Cylinder{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Cone{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Capsule{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Torus{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Paraboloid{obj} => { obj.surface_pdf(origin, dir) },
+            Primitive::Plane{..} => { 0.0 },
+            _ => { 0.0 }
+        }
+    }
+
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let sample = match &self.prim
+        {
+            Primitive::Sphere{obj} => { obj.sample_area() },
+            Primitive::Rect{obj} => { obj.sample_area() },
+            Primitive::Disk{obj} => { obj.sample_area() },
+            Primitive::Cylinder{obj} => { obj.sample_area() },
+            Primitive::Cone{obj} => { obj.sample_area() },
+            Primitive::Capsule{obj} => { obj.sample_area() },
+            Primitive::Torus{obj} => { obj.sample_area() },
+            Primitive::Paraboloid{obj} => { obj.sample_area() },
+            Primitive::Plane{..} => { None },
+            _ => { None }
+        };
+
+        sample.map(|mut s| { s.hit.material = self.mat; s })
+    }
+
+    fn area(&self) -> f32
+    {
+        match &self.prim
+        {
+            Primitive::Sphere{obj} => { obj.area() },
+            Primitive::Rect{obj} => { obj.area() },
+            Primitive::Disk{obj} => { obj.area() },
+            Primitive::Cylinder{obj} => { obj.area() },
+            Primitive::Cone{obj} => { obj.area() },
+            Primitive::Capsule{obj} => { obj.area() },
+            Primitive::Torus{obj} => { obj.area() },
+            Primitive::Paraboloid{obj} => { obj.area() },
+            Primitive::Plane{..} => { std::f32::INFINITY },
+            _ => { 0.0 }
+        }
+    }
+}
+
 //######################################################################
 // Sphere
 //######################################################################
@@ -135,14 +333,7 @@ impl Hitable for Sphere {
 
                 if self.compute_uv
                 {
-                    //out.u = out.normal.x().atan2(out.normal.z()) / (std::f32::consts::PI * 2.0) + 0.5;
-                    //out.v = out.normal.z() * 0.5 + 0.5;
-
-                    let p = out.normal;
-                    let phi = p.z().atan2(p.x());
-                    let theta = p.y().asin();
-                    out.u = (phi + std::f32::consts::PI) / (std::f32::consts::PI * 2.0);
-                    out.v = 1.0 - (theta + std::f32::consts::PI * 0.5) * std::f32::consts::FRAC_1_PI;
+                    self.uv(out);
                 }
             }
 
@@ -153,6 +344,357 @@ impl Hitable for Sphere {
     }
 }
 
+impl Sphere
+{
+    fn uv(&self, out: &mut HitInfo)
+    {
+        //out.u = out.normal.x().atan2(out.normal.z()) / (std::f32::consts::PI * 2.0) + 0.5;
+        //out.v = out.normal.z() * 0.5 + 0.5;
+
+        let p = out.normal;
+        let phi = p.z().atan2(p.x());
+        let theta = p.y().max(-1.0).min(1.0).asin();
+        out.u = (phi + std::f32::consts::PI) / (std::f32::consts::PI * 2.0);
+        out.v = 1.0 - (theta + std::f32::consts::PI * 0.5) * std::f32::consts::FRAC_1_PI;
+
+        // u spans 2 pi r cos(theta), v spans pi r
+        out.uv_scale = std::f32::consts::PI * self.radius * (2.0 * theta.cos().max(1e-3)).sqrt();
+
+        // derivatives of the point w.r.t. u and v, dp/du vanishes at the poles
+        let (sin_t, cos_t) = theta.sin_cos();
+        let (sin_p, cos_p) = phi.sin_cos();
+        let pi = std::f32::consts::PI;
+        out.tangent = Vec4::from3(-cos_t * sin_p, 0.0, cos_t * cos_p) * (2.0 * pi * self.radius);
+        out.bitangent = Vec4::from3(sin_t * cos_p, -cos_t, sin_t * sin_p) * (pi * self.radius);
+    }
+
+    fn surface_hit(&self, point: Vec4, depth: f32) -> HitInfo
+    {
+        let mut hit = HitInfo::new();
+        hit.point = point;
+        hit.normal = (point - self.pos) / self.radius;
+        hit.depth = depth;
+
+        if self.compute_uv
+        {
+            self.uv(&mut hit);
+        }
+
+        hit
+    }
+}
+
+// https://www.pbr-book.org/3ed-2018/Light_Transport_I_Surface_Reflection/Sampling_Light_Sources
+impl SampleSurface for Sphere
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        let to_center = self.pos - origin;
+        let dist2 = to_center.square_length();
+        let r2 = self.radius * self.radius;
+
+        if dist2 <= r2
+        {
+            // inside: uniform by area, converted to solid angle
+            let point = self.pos + random_on_unit_sphere() * self.radius;
+            let to_point = point - origin;
+            let depth = to_point.length();
+            let hit = self.surface_hit(point, depth);
+            let cos = hit.normal.dot(&(to_point / depth)).abs();
+
+            if cos < 1e-6 { return None; }
+
+            return Some(SurfaceSample{hit: hit, pdf: depth * depth / (cos * self.area())});
+        }
+
+        // uniform cone of directions subtended by the sphere
+        let dist = dist2.sqrt();
+        let cos_max = (1.0 - r2 / dist2).max(0.0).sqrt();
+        let cos = 1.0 - random_f32() * (1.0 - cos_max);
+        let sin2 = (1.0 - cos * cos).max(0.0);
+        let phi = 2.0 * std::f32::consts::PI * random_f32();
+
+        let dir = Frame::new(&(to_center / dist)).to_world(&Vec4::from3(sin2.sqrt() * phi.cos(), sin2.sqrt() * phi.sin(), cos));
+        let depth = (dist * cos - (r2 - dist2 * sin2).max(0.0).sqrt()).max(0.0);
+
+        Some(SurfaceSample{hit: self.surface_hit(origin + dir * depth, depth), pdf: 1.0 / (2.0 * std::f32::consts::PI * (1.0 - cos_max))})
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        let mut hit = HitInfo::new();
+        if !self.hit(&Ray::new(*origin, *dir), &mut hit, 0.0, std::f32::MAX)
+        {
+            return 0.0;
+        }
+
+        let dist2 = (self.pos - origin).square_length();
+        let r2 = self.radius * self.radius;
+
+        if dist2 <= r2
+        {
+            let cos = hit.normal.dot(dir).abs();
+            return hit.depth * hit.depth / (cos * self.area()).max(1e-12);
+        }
+
+        let cos_max = (1.0 - r2 / dist2).max(0.0).sqrt();
+        1.0 / (2.0 * std::f32::consts::PI * (1.0 - cos_max))
+    }
+
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        Some(SurfaceSample{hit: self.surface_hit(self.pos + random_on_unit_sphere() * self.radius, 0.0), pdf: 1.0 / self.area()})
+    }
+
+    fn area(&self) -> f32
+    {
+        4.0 * std::f32::consts::PI * self.radius * self.radius
+    }
+}
+
+//######################################################################
+// planar shapes
+//######################################################################
+
+// converts a uniform area sample to solid angle at origin, None if origin is behind a one sided shape
+pub fn area_sample(hit: HitInfo, area: f32, origin: &Vec4, two_sided: bool) -> Option<SurfaceSample>
+{
+    let to_point = hit.point - origin;
+    let dist2 = to_point.square_length3();
+    if dist2 <= 0.0 { return None; }
+
+    let dist = dist2.sqrt();
+    let cos = -hit.normal.dot(&to_point) / dist;
+    if cos.abs() <= 1e-6 || (!two_sided && cos < 0.0) { return None; }
+
+    let mut hit = hit;
+    hit.depth = dist;
+    Some(SurfaceSample{hit: hit, pdf: dist2 / (cos.abs() * area)})
+}
+
+// distance along r to the plane through center, culls back faces of one sided shapes
+fn planar_depth(r: &Ray, center: &Vec4, normal: &Vec4, two_sided: bool, min: f32, max: f32) -> Option<f32>
+{
+    let denom = normal.dot(&r.direction);
+    if denom == 0.0 || (!two_sided && denom > 0.0) { return None; }
+
+    let depth = normal.dot(&(center - r.origin)) / denom;
+    if depth > min && depth < max { Some(depth) } else { None }
+}
+
+// solid angle pdf of area_sample towards the first surface along dir
+pub fn area_pdf<T: Hitable>(shape: &T, area: f32, origin: &Vec4, dir: &Vec4) -> f32
+{
+    let mut hit = HitInfo::new();
+    if !shape.hit(&Ray::new(*origin, *dir), &mut hit, 0.0, std::f32::MAX)
+    {
+        return 0.0;
+    }
+
+    hit.depth * hit.depth / (hit.normal.dot(dir).abs() * area).max(1e-12)
+}
+
+//######################################################################
+// Rect
+//######################################################################
+
+impl Rect
+{
+    // _u and _v are the half edges, uvs run from center - u - v to center + u + v
+    pub fn new(_center: Vec4, _u: Vec4, _v: Vec4, _two_sided: bool) -> Rect
+    {
+        let n = _u.cross3(&_v);
+        Rect{center: _center, u: _u, v: _v, normal: n.norm3(), area: 4.0 * n.length3(), two_sided: _two_sided}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Rect{obj: *self}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        BBox::new(self.center, self.u.abs() + self.v.abs())
+    }
+
+    // edge coordinates of a point in the plane, both in [-1, 1] inside
+    fn local(&self, point: &Vec4) -> (f32, f32)
+    {
+        let d = point - self.center;
+        (d.dot(&self.u) / self.u.square_length3(), d.dot(&self.v) / self.v.square_length3())
+    }
+
+    fn fill(&self, out: &mut HitInfo, s: f32, q: f32)
+    {
+        out.normal = self.normal;
+        out.u = 0.5 + 0.5 * s;
+        out.v = 0.5 + 0.5 * q;
+        out.uv_scale = 0.5 * self.area.sqrt();
+        out.tangent = self.u * 2.0;
+        out.bitangent = self.v * 2.0;
+    }
+}
+
+impl Hitable for Rect
+{
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let depth = match planar_depth(r, &self.center, &self.normal, self.two_sided, min, max)
+        {
+            Some(d) => d,
+            None => return false
+        };
+
+        let point = r.point_at(depth);
+        let (s, q) = self.local(&point);
+        if s.abs() > 1.0 || q.abs() > 1.0
+        {
+            return false;
+        }
+
+        out.depth = depth;
+        out.point = point;
+        self.fill(out, s, q);
+        true
+    }
+}
+
+impl SampleSurface for Rect
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        area_sample(self.sample_area()?.hit, self.area, origin, self.two_sided)
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        area_pdf(self, self.area, origin, dir)
+    }
+
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let (s, q) = random_tuple(-1.0, 1.0);
+        let mut hit = HitInfo::new();
+        hit.point = self.center + self.u * s + self.v * q;
+        self.fill(&mut hit, s, q);
+
+        Some(SurfaceSample{hit: hit, pdf: 1.0 / self.area})
+    }
+
+    fn area(&self) -> f32
+    {
+        self.area
+    }
+}
+
+//######################################################################
+// Disk
+//######################################################################
+
+impl Disk
+{
+    pub fn new(_center: Vec4, _normal: Vec4, _radius: f32, _two_sided: bool) -> Disk
+    {
+        Disk::annulus(_center, _normal, _radius, 0.0, _two_sided)
+    }
+
+    // ring between _inner_radius and _radius
+    pub fn annulus(_center: Vec4, _normal: Vec4, _radius: f32, _inner_radius: f32, _two_sided: bool) -> Disk
+    {
+        Disk{center: _center, frame: Frame::new(&_normal.norm3()), radius: _radius, inner_radius: _inner_radius.max(0.0).min(_radius), two_sided: _two_sided}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Disk{obj: *self}, _mat, Quat::new())
+    }
+
+    // extent of a circle along each axis is radius * sin of the angle to the normal
+    pub fn bounds(&self) -> BBox
+    {
+        let n = self.frame.n;
+        let e = (Vec4::one() - n * n).max(&Vec4::zero()).sqrt() * self.radius;
+        BBox::new(self.center, e)
+    }
+
+    // u is the angle around the normal, v runs from the inner to the outer radius
+    fn fill(&self, out: &mut HitInfo)
+    {
+        let local = self.frame.to_local(&(out.point - self.center));
+        let dist = (local.x() * local.x() + local.y() * local.y()).sqrt();
+        let phi = local.y().atan2(local.x());
+        let width = self.radius - self.inner_radius;
+        let pi = std::f32::consts::PI;
+
+        let (sin, cos) = phi.sin_cos();
+        let radial = self.frame.t * cos + self.frame.b * sin;
+        let around = self.frame.b * cos - self.frame.t * sin;
+
+        out.normal = self.frame.n;
+        out.u = (phi + pi) / (2.0 * pi);
+        out.v = if width > 0.0 { (dist - self.inner_radius) / width } else { 0.0 };
+        out.uv_scale = (2.0 * pi * dist.max(1e-3 * self.radius) * width).sqrt();
+        out.tangent = around * (2.0 * pi * dist);
+        out.bitangent = radial * width;
+    }
+}
+
+impl Hitable for Disk
+{
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let depth = match planar_depth(r, &self.center, &self.frame.n, self.two_sided, min, max)
+        {
+            Some(d) => d,
+            None => return false
+        };
+
+        let point = r.point_at(depth);
+        let dist2 = (point - self.center).square_length3();
+        if dist2 > self.radius * self.radius || dist2 < self.inner_radius * self.inner_radius
+        {
+            return false;
+        }
+
+        out.depth = depth;
+        out.point = point;
+        self.fill(out);
+        true
+    }
+}
+
+impl SampleSurface for Disk
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        area_sample(self.sample_area()?.hit, self.area(), origin, self.two_sided)
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        area_pdf(self, self.area(), origin, dir)
+    }
+
+    // uniform in area between the two radii
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let r2 = self.inner_radius * self.inner_radius;
+        let dist = (r2 + random_f32() * (self.radius * self.radius - r2)).sqrt();
+        let phi = 2.0 * std::f32::consts::PI * random_f32();
+
+        let mut hit = HitInfo::new();
+        hit.point = self.center + self.frame.to_world(&Vec4::from3(dist * phi.cos(), dist * phi.sin(), 0.0));
+        self.fill(&mut hit);
+
+        Some(SurfaceSample{hit: hit, pdf: 1.0 / self.area()})
+    }
+
+    fn area(&self) -> f32
+    {
+        std::f32::consts::PI * (self.radius * self.radius - self.inner_radius * self.inner_radius)
+    }
+}
+
 //######################################################################
 // Plane
 //######################################################################
@@ -161,7 +703,29 @@ impl Plane
 {
     pub fn new(_pos: Vec4, _normal: Vec4) -> Plane
     {
-        Plane{pos: _pos, normal: _normal}
+        let n = _normal.norm3();
+        let frame = Frame::new(&n);
+        Plane{pos: _pos, normal: n, tangent: frame.t, bitangent: frame.b, tile: 1.0, compute_uv: false, two_sided: false}
+    }
+
+    // hit from both sides, the normal keeps its orientation like the sphere's
+    pub fn new_two_sided(_pos: Vec4, _normal: Vec4) -> Plane
+    {
+        Plane{two_sided: true, ..Plane::new(_pos, _normal)}
+    }
+
+    // uvs repeat every _tile world units along _tangent and normal x _tangent, measured from _pos
+    pub fn new_with_uv(_pos: Vec4, _normal: Vec4, _tangent: Vec4, _tile: f32, _two_sided: bool) -> Plane
+    {
+        let n = _normal.norm3();
+        let mut t = _tangent - n * _tangent.dot(&n);
+        if t.length3() < 1e-6
+        {
+            t = Frame::new(&n).t;
+        }
+
+        let t = t.norm3();
+        Plane{pos: _pos, normal: n, tangent: t, bitangent: n.cross3(&t), tile: _tile, compute_uv: true, two_sided: _two_sided}
     }
 
     pub fn object(&self, _mat: u32) -> Object
@@ -174,22 +738,28 @@ impl Hitable for Plane
 {
     fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
     {
-        let denom = self.normal.dot(&r.direction);
-
-        if denom > 0.0 
+        // one sided planes are only hit from the front like rects and disks
+        let depth = match planar_depth(r, &self.pos, &self.normal, self.two_sided, min, max)
         {
-            let depth = (self.pos - r.origin).dot(&self.normal) / denom;
+            Some(d) => d,
+            None => return false
+        };
 
-            if depth > min && depth < max
-            {
-                out.depth = depth;
-                out.normal = self.normal;
-                out.point = r.point_at(out.depth);
-                return true;
-            }
+        out.depth = depth;
+        out.normal = self.normal;
+        out.point = r.point_at(out.depth);
+
+        if self.compute_uv
+        {
+            let d = out.point - self.pos;
+            out.u = d.dot(&self.tangent) / self.tile;
+            out.v = d.dot(&self.bitangent) / self.tile;
+            out.uv_scale = self.tile;
+            out.tangent = self.tangent * self.tile;
+            out.bitangent = self.bitangent * self.tile;
         }
 
-        false
+        true
     }
 }
 
@@ -201,46 +771,509 @@ impl BBox
 {
     pub fn new(_center: Vec4, _dimensions: Vec4) -> BBox
     {
-        BBox{center: _center, dimensions: _dimensions, inv_dimensions: 1.0 / _dimensions}
+        BBox{center: _center, dimensions: _dimensions, inv_dimensions: 1.0 / _dimensions, compute_uv: false}
+    }
+
+    // each face spans the whole [0, 1] square
+    pub fn new_with_uv(_center: Vec4, _dimensions: Vec4) -> BBox
+    {
+        BBox{center: _center, dimensions: _dimensions, inv_dimensions: 1.0 / _dimensions, compute_uv: true}
+    }
+
+    // box spanning the two corners
+    pub fn from_corners(_min: Vec4, _max: Vec4) -> BBox
+    {
+        BBox::new((_min + _max) * 0.5, (_max - _min) * 0.5)
+    }
+
+    // min and max corner
+    pub fn corners(&self) -> (Vec4, Vec4)
+    {
+        (self.center - self.dimensions, self.center + self.dimensions)
     }
 
     pub fn object(&self, _mat: u32) -> Object
     {
         Object::new(&Primitive::BBox{obj: *self}, _mat, Quat::new())
     }
+
+    // entry and exit distance along r and the axis of the face each happens on
+    // https://jcgt.org/published/0002/02/02/paper.pdf
+    fn slabs(&self, r: &Ray) -> Option<(f32, usize, f32, usize)>
+    {
+        let o = r.origin - self.center;
+        let (mut near, mut near_axis) = (-std::f32::INFINITY, 0);
+        let (mut far, mut far_axis) = (std::f32::INFINITY, 0);
+
+        for i in 0..3
+        {
+            let inv = 1.0 / r.direction.get(i);
+            let mut t0 = (-self.dimensions.get(i) - o.get(i)) * inv;
+            let mut t1 = (self.dimensions.get(i) - o.get(i)) * inv;
+            if t0 > t1
+            {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // rounding of the exit keeps grazing rays from slipping through edges
+            t1 *= SLAB_ROUNDING;
+
+            // nan from a ray parallel to and exactly on a face plane fails both tests and leaves the slab open
+            if t0 > near { near = t0; near_axis = i; }
+            if t1 < far { far = t1; far_axis = i; }
+        }
+
+        if near > far
+        {
+            return None;
+        }
+
+        Some((near, near_axis, far, far_axis))
+    }
+
+    // parametric span of r inside the box
+    pub fn clip(&self, r: &Ray) -> Option<(f32, f32)>
+    {
+        self.slabs(r).map(|(near, _, far, _)| (near, far))
+    }
+
+    // outward normal of the face on axis, on the side the point lies
+    fn face_hit(&self, r: &Ray, out: &mut HitInfo, depth: f32, axis: usize, side: f32)
+    {
+        out.depth = depth;
+        out.point = r.point_at(depth);
+
+        let mut n = Vec4::zero();
+        n.set(side, axis);
+        out.normal = n;
+
+        if self.compute_uv
+        {
+            self.uv(out, axis, side);
+        }
+    }
+
+    // u runs counter clockwise around the face axis, v downwards
+    fn uv(&self, out: &mut HitInfo, axis: usize, side: f32)
+    {
+        let local = (out.point - self.center) * self.inv_dimensions;
+
+        let (i, j) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut du = Vec4::zero();
+        let mut dv = Vec4::zero();
+        du.set(2.0 * self.dimensions.get(i) * side, i);
+        dv.set(-2.0 * self.dimensions.get(j), j);
+
+        out.u = (0.5 + 0.5 * local.get(i) * side).max(0.0).min(1.0);
+        out.v = (0.5 - 0.5 * local.get(j)).max(0.0).min(1.0);
+        out.uv_scale = 2.0 * (self.dimensions.get(i) * self.dimensions.get(j)).abs().sqrt();
+        out.tangent = du;
+        out.bitangent = dv;
+    }
 }
 
 impl Hitable for BBox
 {
-    // http://www.jcgt.org/published/0007/03/04/paper-lowres.pdf
+    // entering hits face against the ray, hits from inside leave through the exit face, normals point outwards
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let (near, near_axis, far, far_axis) = match self.slabs(r)
+        {
+            Some(t) => t,
+            None => return false
+        };
+
+        if near > min && near < max
+        {
+            let side = if r.direction.get(near_axis) > 0.0 { -1.0 } else { 1.0 };
+            self.face_hit(r, out, near, near_axis, side);
+            return true;
+        }
+
+        if far > min && far < max
+        {
+            let side = if r.direction.get(far_axis) > 0.0 { 1.0 } else { -1.0 };
+            self.face_hit(r, out, far, far_axis, side);
+            return true;
+        }
+
+        false
+    }
+}
+
+//######################################################################
+// OrientedBox
+//######################################################################
+
+impl OrientedBox
+{
+    // _rotation turns the box around its center
+    pub fn new(_center: Vec4, _dimensions: Vec4, _rotation: Quat) -> OrientedBox
+    {
+        OrientedBox{bbox: BBox::new(_center, _dimensions), rotation: _rotation}
+    }
+
+    pub fn new_with_uv(_center: Vec4, _dimensions: Vec4, _rotation: Quat) -> OrientedBox
+    {
+        OrientedBox{bbox: BBox::new_with_uv(_center, _dimensions), rotation: _rotation}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::OrientedBox{obj: *self}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        let d = self.bbox.dimensions;
+        let mut e = Vec4::zero();
+        for axis in [Vec4::from3(d.x(), 0.0, 0.0), Vec4::from3(0.0, d.y(), 0.0), Vec4::from3(0.0, 0.0, d.z())].iter()
+        {
+            e = e + self.rotation.rotate_unit(axis).abs();
+        }
+
+        BBox::new(self.bbox.center, e)
+    }
+}
+
+impl Hitable for OrientedBox
+{
+    // axis aligned test in box space, rotations keep distances so the depth carries over
     fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
     {
-        let ray_origin = r.origin - self.center;
+        let c = self.bbox.center;
+        let local = Ray::with_cone(c + self.rotation.inverse_rotate_unit(&(r.origin - c)), self.rotation.inverse_rotate_unit(&r.direction), r.cone_width, r.cone_spread);
+
+        if !self.bbox.hit(&local, out, min, max)
+        {
+            return false;
+        }
+
+        out.point = r.point_at(out.depth);
+        out.normal = self.rotation.rotate_unit(&out.normal);
+        out.tangent = self.rotation.rotate_unit(&out.tangent);
+        out.bitangent = self.rotation.rotate_unit(&out.bitangent);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // u and v directions of each face as (axis, sign) in box space: u runs counter clockwise around the face axis, v downwards
+    const FACES: [(usize, f32, usize, f32, usize, f32); 6] = [
+        (0, 1.0, 1, 1.0, 2, -1.0),
+        (0, -1.0, 1, -1.0, 2, -1.0),
+        (1, 1.0, 2, 1.0, 0, -1.0),
+        (1, -1.0, 2, -1.0, 0, -1.0),
+        (2, 1.0, 0, 1.0, 1, -1.0),
+        (2, -1.0, 0, -1.0, 1, -1.0)];
+
+    struct Lcg(u64);
+
+    impl Lcg
+    {
+        fn next(&mut self) -> f32
+        {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+
+        fn range(&mut self, a: f32, b: f32) -> f32
+        {
+            a + (b - a) * self.next()
+        }
+
+        fn vec(&mut self, a: f32, b: f32) -> Vec4
+        {
+            Vec4::from3(self.range(a, b), self.range(a, b), self.range(a, b))
+        }
+
+        // unit direction, a third lie in a coordinate plane and a sixth along an axis
+        fn dir(&mut self) -> Vec4
+        {
+            let mut d = self.vec(-1.0, 1.0);
+            let k = self.next();
+            if k < 0.5 { d.set(0.0, (self.next() * 3.0) as usize % 3); }
+            if k < 1.0 / 6.0 { d.set(0.0, (self.next() * 3.0) as usize % 3); }
+            if d.length3() < 1e-3 { Vec4::from3(0.0, 1.0, 0.0) } else { d.norm3() }
+        }
+    }
+
+    struct Expected
+    {
+        depth: f32,
+        point: Vec4,
+        normal: Vec4,
+        u: f32,
+        v: f32,
+        tangent: Vec4,
+        bitangent: Vec4,
+        margin: f32 // distance of the hit to the closest face edge
+    }
+
+    // closest crossing of the six face planes that lies within the face, eps widens the faces
+    fn reference(center: &Vec4, dims: &Vec4, axes: &[Vec4; 3], r: &Ray, min: f32, max: f32, eps: f32) -> Option<Expected>
+    {
+        let mut best: Option<Expected> = None;
+
+        for &(a, side, i, si, j, sj) in FACES.iter()
+        {
+            let n = axes[a] * side;
+            let denom = n.dot(&r.direction);
+            if denom == 0.0 { continue; }
+
+            let depth = (dims.get(a) - (r.origin - center).dot(&n)) / denom;
+            if !(depth > min && depth < max) || best.as_ref().map_or(false, |b| b.depth <= depth) { continue; }
+
+            let point = r.point_at(depth);
+            let (li, lj) = ((point - center).dot(&axes[i]), (point - center).dot(&axes[j]));
+            let margin = (dims.get(i) - li.abs()).min(dims.get(j) - lj.abs());
+            if margin < -eps { continue; }
+
+            let (du, dv) = (axes[i] * si, axes[j] * sj);
+            best = Some(Expected{
+                depth: depth,
+                point: point,
+                normal: n,
+                u: 0.5 + 0.5 * (point - center).dot(&du) / dims.get(i),
+                v: 0.5 + 0.5 * (point - center).dot(&dv) / dims.get(j),
+                tangent: du * (2.0 * dims.get(i)),
+                bitangent: dv * (2.0 * dims.get(j)),
+                margin: margin});
+        }
 
-        let winding = if (ray_origin.abs() * self.inv_dimensions).max_elem3() < 1.0 {-1.0} else {1.0};
+        best
+    }
 
-        let mut sign = -r.direction.sign();
+    fn near(a: &Vec4, b: &Vec4, tolerance: f32) -> bool
+    {
+        (a - b).length3() <= tolerance
+    }
 
-        let dist = (self.dimensions * sign * winding - ray_origin) * self.inv_dimensions;
+    // compares hit against the reference, rays touching edges or the clip range only need to agree on hitting
+    fn check<T: Hitable>(shape: &T, center: &Vec4, dims: &Vec4, axes: &[Vec4; 3], r: &Ray, min: f32, max: f32) -> Result<(), String>
+    {
+        let mut out = HitInfo::new();
+        let hit = shape.hit(r, &mut out, min, max);
 
-        // TODO: vectrize
-        let test = |u: usize, v: usize, w: usize| -> bool
+        let expected = match reference(center, dims, axes, r, min, max, 1e-4)
         {
-            let dU = r.direction.extract1(u);
-            let uT = dU < 0.0;
-            let vT = uT && (ray_origin.extract1(v) + r.direction.extract1(v) * dU).abs() < self.dimensions.extract1(v);
-            let wT = vT && (ray_origin.extract1(w) + r.direction.extract1(w) * dU).abs() < self.dimensions.extract1(w);
-            wT
+            Some(e) => e,
+            None =>
+            {
+                let slack = reference(center, dims, axes, r, min - 1e-3, max + 1e-3, 1e-3).is_some();
+                return if hit && !slack { Err(format!("unexpected hit at {}", out.depth)) } else { Ok(()) };
+            }
         };
 
-        let m  = m16x4::new(test(0, 1, 2), test(1, 2, 0), test(2, 0, 1), false);
-        sign.v = m.select(sign.v, f32x4::splat(0.0));
+        let scale = expected.depth.abs().max(1.0);
+        let ambiguous = expected.margin < 1e-3 * scale || (expected.depth - min).abs() < 1e-3 * scale || (max - expected.depth).abs() < 1e-3 * scale;
+
+        if !hit
+        {
+            return if ambiguous { Ok(()) } else { Err(format!("missed hit at {}", expected.depth)) };
+        }
+
+        if ambiguous
+        {
+            return Ok(());
+        }
+
+        if (out.depth - expected.depth).abs() > 1e-4 * scale { return Err(format!("depth {} expected {}", out.depth, expected.depth)); }
+        if !near(&out.point, &expected.point, 1e-4 * scale) { return Err("point".to_string()); }
+        if !near(&out.normal, &expected.normal, 1e-4) { return Err("normal".to_string()); }
+        if (out.u - expected.u).abs() > 1e-3 || (out.v - expected.v).abs() > 1e-3 { return Err(format!("uv {} {} expected {} {}", out.u, out.v, expected.u, expected.v)); }
+        if !near(&out.tangent, &expected.tangent, 1e-4 * scale) || !near(&out.bitangent, &expected.bitangent, 1e-4 * scale) { return Err("tangent frame".to_string()); }
+
+        Ok(())
+    }
+
+    fn unit_axes() -> [Vec4; 3]
+    {
+        [Vec4::from3(1.0, 0.0, 0.0), Vec4::from3(0.0, 1.0, 0.0), Vec4::from3(0.0, 0.0, 1.0)]
+    }
+
+    // random boxes and rays, many start inside, are axis parallel or get clipped by min and max
+    fn random_rays<F, T>(shape: F)
+    where F: Fn(&mut Lcg, Vec4, Vec4) -> (T, [Vec4; 3]), T: Hitable
+    {
+        let mut rng = Lcg(7);
+        let mut hits = 0;
+
+        for n in 0..20000
+        {
+            let center = rng.vec(-2.0, 2.0);
+            let dims = rng.vec(0.2, 1.5);
+            let (b, axes) = shape(&mut rng, center, dims);
+
+            // half of the rays aim at a point of the box
+            let origin = center + rng.vec(-2.5, 2.5);
+            let target = center + axes[0] * (dims.x() * rng.range(-1.0, 1.0)) + axes[1] * (dims.y() * rng.range(-1.0, 1.0)) + axes[2] * (dims.z() * rng.range(-1.0, 1.0));
+            let dir = if rng.next() < 0.5 && (target - origin).length3() > 1e-3 { (target - origin).norm3() } else { rng.dir() };
+            let r = Ray::new(origin, dir);
+            let min = if rng.next() < 0.25 { rng.range(0.0, 3.0) } else { 0.0 };
+            let max = if rng.next() < 0.25 { rng.range(min, 5.0) } else { std::f32::INFINITY };
+
+            if let Err(e) = check(&b, &center, &dims, &axes, &r, min, max)
+            {
+                panic!("ray {}: {}", n, e);
+            }
+
+            hits += reference(&center, &dims, &axes, &r, min, max, 0.0).is_some() as u32;
+        }
+
+        assert!(hits > 8000, "only {} hits", hits);
+    }
+
+    #[test]
+    fn bbox_matches_reference()
+    {
+        random_rays(|_, center, dims| (BBox::new_with_uv(center, dims), unit_axes()));
+    }
+
+    #[test]
+    fn bbox_from_inside()
+    {
+        let mut rng = Lcg(11);
+        let b = BBox::new(Vec4::from3(0.5, -1.0, 2.0), Vec4::from3(1.0, 0.5, 2.0));
+
+        for _ in 0..10000
+        {
+            let origin = b.center + rng.vec(-0.99, 0.99) * b.dimensions;
+            let r = Ray::new(origin, rng.dir());
+            let mut out = HitInfo::new();
+
+            assert!(b.hit(&r, &mut out, 0.0, std::f32::INFINITY));
+            assert!(out.normal.dot(&r.direction) > 0.0, "normal must point out of the box");
+
+            let local = (out.point - b.center) * b.inv_dimensions;
+            assert!((local.abs().max_elem3() - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn bbox_edges_and_corners()
+    {
+        let b = BBox::new(Vec4::from3(0.0, 0.0, 0.0), Vec4::from3(1.0, 2.0, 0.5));
+
+        // every edge midpoint and corner, approached from outside
+        for x in -1..2
+        {
+            for y in -1..2
+            {
+                for z in -1..2
+                {
+                    let signs = Vec4::from3(x as f32, y as f32, z as f32);
+                    if x * x + y * y + z * z < 2 { continue; }
+
+                    let target = signs * b.dimensions;
+                    let origin = target + (signs + Vec4::from3(0.3, -0.2, 0.1)) * 3.0;
+                    let dist = (target - origin).length3();
+                    let r = Ray::new(origin, (target - origin) / dist);
+
+                    let mut out = HitInfo::new();
+                    if b.hit(&r, &mut out, 0.0, std::f32::INFINITY)
+                    {
+                        assert!(out.depth <= dist * (1.0 + 1e-4), "grazing hit behind the edge");
+                    }
+                    else
+                    {
+                        // only rays leaving through the edge may miss, those touching it from the outside can't tell
+                        assert!(reference(&b.center, &b.dimensions, &unit_axes(), &r, 0.0, std::f32::INFINITY, 0.0).map_or(true, |e| (e.depth - dist).abs() < 1e-4 * dist));
+                    }
+                }
+            }
+        }
+
+        // axis parallel rays sliding along a face or an edge enter through the face ahead
+        for origin in [Vec4::from3(-3.0, 2.0, 0.0), Vec4::from3(-3.0, 2.0, 0.5), Vec4::from3(-3.0, -2.0, -0.5)].iter()
+        {
+            let mut out = HitInfo::new();
+            assert!(b.hit(&Ray::new(*origin, Vec4::from3(1.0, 0.0, 0.0)), &mut out, 0.0, std::f32::INFINITY));
+            assert!((out.depth - 2.0).abs() < 1e-5);
+            assert!(out.normal == Vec4::from3(-1.0, 0.0, 0.0));
+        }
+    }
 
-        let (x, y, z) = dist.extract_xyz();
+    #[test]
+    fn bbox_clipping()
+    {
+        let b = BBox::new(Vec4::zero(), Vec4::one());
+        let r = Ray::new(Vec4::from3(-3.0, 0.2, 0.1), Vec4::from3(1.0, 0.0, 0.0));
+        let mut out = HitInfo::new();
+
+        assert!(b.hit(&r, &mut out, 0.0, std::f32::INFINITY) && (out.depth - 2.0).abs() < 1e-5 && out.normal.x() == -1.0);
+        assert!(b.hit(&r, &mut out, 2.5, std::f32::INFINITY) && (out.depth - 4.0).abs() < 1e-5 && out.normal.x() == 1.0);
+        assert!(!b.hit(&r, &mut out, 4.5, std::f32::INFINITY));
+        assert!(!b.hit(&r, &mut out, 0.0, 1.5));
+        assert!(b.hit(&r, &mut out, 0.0, 3.0) && (out.depth - 2.0).abs() < 1e-5);
+        assert!(!b.hit(&Ray::new(Vec4::from3(-3.0, 0.2, 0.1), Vec4::from3(-1.0, 0.0, 0.0)), &mut out, 0.0, std::f32::INFINITY));
+
+        match b.clip(&r)
+        {
+            Some((near, far)) => assert!((near - 2.0).abs() < 1e-5 && (far - 4.0).abs() < 1e-5),
+            None => panic!("clip missed")
+        }
+    }
 
-        out.depth = if x != 0.0 {x} else { if y != 0.0 {y} else {z} };
-        out.normal = sign;
+    #[test]
+    fn oriented_box_matches_rotated_reference()
+    {
+        random_rays(|rng, center, dims| {
+            let rotation = Quat::from_axis_angle_deg(&rng.dir(), rng.range(0.0, 360.0));
+            let axes = [
+                rotation.rotate_unit(&Vec4::from3(1.0, 0.0, 0.0)),
+                rotation.rotate_unit(&Vec4::from3(0.0, 1.0, 0.0)),
+                rotation.rotate_unit(&Vec4::from3(0.0, 0.0, 1.0))];
 
-        out.depth > min && out.depth < max
+            (OrientedBox::new_with_uv(center, dims, rotation), axes)
+        });
     }
-}
\ No newline at end of file
+
+    // one sided planar shapes agree on the side they are hit from
+    #[test]
+    fn planar_sides()
+    {
+        let up = Vec4::from3(0.0, 1.0, 0.0);
+        let from_above = Ray::new(Vec4::from3(0.1, 1.0, 0.2), -up);
+        let from_below = Ray::new(Vec4::from3(0.1, -1.0, 0.2), up);
+        let mut out = HitInfo::new();
+
+        let plane = Plane::new(Vec4::zero(), up * 2.0);
+        let disk = Disk::new(Vec4::zero(), up, 1.0, false);
+        assert!(plane.hit(&from_above, &mut out, 0.0, std::f32::INFINITY) && out.normal == up && (out.depth - 1.0).abs() < 1e-6);
+        assert!(disk.hit(&from_above, &mut out, 0.0, std::f32::INFINITY));
+        assert!(!plane.hit(&from_below, &mut out, 0.0, std::f32::INFINITY));
+        assert!(!disk.hit(&from_below, &mut out, 0.0, std::f32::INFINITY));
+
+        let two_sided = Plane::new_two_sided(Vec4::zero(), up);
+        assert!(two_sided.hit(&from_below, &mut out, 0.0, std::f32::INFINITY) && out.normal == up);
+        assert!(!two_sided.hit(&Ray::new(Vec4::from3(0.0, 1.0, 0.0), Vec4::from3(1.0, 0.0, 0.0)), &mut out, 0.0, std::f32::INFINITY));
+    }
+
+    #[test]
+    fn oriented_box_bounds()
+    {
+        let mut rng = Lcg(3);
+
+        for _ in 0..1000
+        {
+            let dims = rng.vec(0.2, 1.5);
+            let o = OrientedBox::new(Vec4::zero(), dims, Quat::from_axis_angle_deg(&rng.dir(), rng.range(0.0, 360.0)));
+            let (lo, hi) = o.bounds().corners();
+
+            for k in 0..8
+            {
+                let corner = Vec4::from3(if k & 1 == 0 { -1.0 } else { 1.0 }, if k & 2 == 0 { -1.0 } else { 1.0 }, if k & 4 == 0 { -1.0 } else { 1.0 }) * dims;
+                let p = o.rotation.rotate_unit(&corner);
+                for i in 0..3
+                {
+                    assert!(p.get(i) >= lo.get(i) - 1e-4 && p.get(i) <= hi.get(i) + 1e-4);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,636 @@
+use super::hit::*;
+use super::vec::*;
+use super::texture::*;
+
+// textures evaluated from the hit instead of an image
+
+// where procedural textures read their coordinates from
+#[derive(Copy, Clone, PartialEq)]
+pub enum TextureCoord
+{
+    Uv, // (u, v, 0)
+    World
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum NoiseBasis
+{
+    Perlin,
+    Simplex
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum NoiseKind
+{
+    Plain, // single octave
+    Fbm, // signed octaves summed
+    Turbulence // absolute octaves summed
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum VoronoiOutput
+{
+    Distance, // to the nearest feature point
+    Cell // random grey per cell
+}
+
+// octave sums of gradient noise
+#[derive(Copy, Clone)]
+pub struct Noise
+{
+    pub basis: NoiseBasis,
+    pub kind: NoiseKind,
+    pub octaves: u32,
+    pub lacunarity: f32, // frequency multiplier per octave
+    pub gain: f32, // amplitude multiplier per octave
+    pub seed: u32
+}
+
+#[derive(Clone)]
+pub struct CheckerTexture
+{
+    pub even: Box<Texture>,
+    pub odd: Box<Texture>,
+    pub coord: TextureCoord,
+    pub scale: Vec4 // checks per unit
+}
+
+// grey noise remapped to [0, 1]
+#[derive(Copy, Clone)]
+pub struct NoiseTexture
+{
+    pub noise: Noise,
+    pub coord: TextureCoord,
+    pub scale: Vec4
+}
+
+// veins of sin(x + turbulence)
+#[derive(Clone)]
+pub struct MarbleTexture
+{
+    pub base: Vec4,
+    pub vein: Vec4,
+    pub noise: Noise,
+    pub coord: TextureCoord,
+    pub scale: Vec4,
+    pub distortion: f32
+}
+
+// rings around the y axis, perturbed by noise
+#[derive(Clone)]
+pub struct WoodTexture
+{
+    pub light: Vec4,
+    pub dark: Vec4,
+    pub noise: Noise,
+    pub coord: TextureCoord,
+    pub scale: Vec4,
+    pub rings: f32, // rings per unit distance from the axis
+    pub distortion: f32
+}
+
+// worley cellular noise
+#[derive(Copy, Clone)]
+pub struct VoronoiTexture
+{
+    pub output: VoronoiOutput,
+    pub coord: TextureCoord,
+    pub scale: Vec4,
+    pub jitter: f32, // 0 regular grid, 1 fully random feature points
+    pub seed: u32
+}
+
+// piecewise linear ramp along one coordinate axis, stops sorted by position
+#[derive(Clone)]
+pub struct GradientTexture
+{
+    pub stops: std::vec::Vec<(f32, Vec4)>,
+    pub coord: TextureCoord,
+    pub scale: Vec4,
+    pub axis: usize
+}
+
+#[derive(Clone)]
+pub struct ScaleTexture
+{
+    pub tex: Box<Texture>,
+    pub scale: Vec4
+}
+
+// a * (1 - factor) + b * factor
+#[derive(Clone)]
+pub struct MixTexture
+{
+    pub a: Box<Texture>,
+    pub b: Box<Texture>,
+    pub factor: Box<Texture>
+}
+
+#[derive(Clone)]
+pub struct MultiplyTexture
+{
+    pub a: Box<Texture>,
+    pub b: Box<Texture>
+}
+
+// samples tex at uv' = rotate(uv * scale) + offset, tiles with repeat wrapping
+#[derive(Clone)]
+pub struct UvTransformTexture
+{
+    pub tex: Box<Texture>,
+    pub matrix: [f32; 4], // row major 2x2
+    pub offset: (f32, f32)
+}
+
+// box projection along the world axes blended by the normal, for primitives without uvs
+// https://bgolus.medium.com/normal-mapping-for-a-triplanar-shader-10bf39dca05a
+#[derive(Clone)]
+pub struct TriplanarTexture
+{
+    pub tex: Box<Texture>,
+    pub scale: f32, // uv units per world unit
+    pub sharpness: f32 // exponent on the normal weights, higher gives narrower seams
+}
+
+fn coord(hit: &HitInfo, coord: TextureCoord, scale: &Vec4) -> Vec4
+{
+    let p = match coord
+    {
+        TextureCoord::Uv => {Vec4::from3(hit.u, hit.v, 0.0)},
+        TextureCoord::World => {hit.point}
+    };
+
+    p * scale
+}
+
+//######################################################################
+// noise
+//######################################################################
+
+// https://nullprogram.com/blog/2018/07/31/
+fn hash(x: i32, y: i32, z: i32, seed: u32) -> u32
+{
+    let mut h = seed ^ (x as u32).wrapping_mul(0x8da6b343) ^ (y as u32).wrapping_mul(0xd8163841) ^ (z as u32).wrapping_mul(0xcb1ab31f);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846ca68b);
+    h ^= h >> 16;
+    h
+}
+
+fn hash_f32(x: i32, y: i32, z: i32, seed: u32) -> f32
+{
+    (hash(x, y, z, seed) >> 8) as f32 / (1 << 24) as f32
+}
+
+// one of the 12 cube edge directions
+fn grad(h: u32, x: f32, y: f32, z: f32) -> f32
+{
+    let h = h % 12;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else { z };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+// https://mrl.cs.nyu.edu/~perlin/noise/
+pub fn perlin(p: &Vec4, seed: u32) -> f32
+{
+    let (px, py, pz) = p.extract_xyz();
+    let (fx, fy, fz) = (px.floor(), py.floor(), pz.floor());
+    let (x, y, z) = (px - fx, py - fy, pz - fz);
+    let (ix, iy, iz) = (fx as i32, fy as i32, fz as i32);
+
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+
+    let g = |dx: i32, dy: i32, dz: i32| grad(hash(ix + dx, iy + dy, iz + dz, seed), x - dx as f32, y - dy as f32, z - dz as f32);
+
+    lerp(
+        lerp(lerp(g(0, 0, 0), g(1, 0, 0), u), lerp(g(0, 1, 0), g(1, 1, 0), u), v),
+        lerp(lerp(g(0, 0, 1), g(1, 0, 1), u), lerp(g(0, 1, 1), g(1, 1, 1), u), v),
+        w)
+}
+
+// http://staffwww.itn.liu.se/~stegu/simplexnoise/simplexnoise.pdf
+pub fn simplex(p: &Vec4, seed: u32) -> f32
+{
+    const F3: f32 = 1.0 / 3.0;
+    const G3: f32 = 1.0 / 6.0;
+
+    let (px, py, pz) = p.extract_xyz();
+
+    // skew into the simplex grid
+    let s = (px + py + pz) * F3;
+    let (i, j, k) = ((px + s).floor() as i32, (py + s).floor() as i32, (pz + s).floor() as i32);
+    let t = (i + j + k) as f32 * G3;
+    let (x0, y0, z0) = (px - (i as f32 - t), py - (j as f32 - t), pz - (k as f32 - t));
+
+    // which of the six simplices of the cube
+    let (i1, j1, k1, i2, j2, k2) = if x0 >= y0
+    {
+        if y0 >= z0 { (1, 0, 0, 1, 1, 0) } else if x0 >= z0 { (1, 0, 0, 1, 0, 1) } else { (0, 0, 1, 1, 0, 1) }
+    }
+    else
+    {
+        if y0 < z0 { (0, 0, 1, 0, 1, 1) } else if x0 < z0 { (0, 1, 0, 0, 1, 1) } else { (0, 1, 0, 1, 1, 0) }
+    };
+
+    let corner = |di: i32, dj: i32, dk: i32, offset: f32| -> f32
+    {
+        let (x, y, z) = (x0 - di as f32 + offset, y0 - dj as f32 + offset, z0 - dk as f32 + offset);
+        let t = 0.6 - x * x - y * y - z * z;
+        if t < 0.0 { 0.0 } else { t * t * t * t * grad(hash(i + di, j + dj, k + dk, seed), x, y, z) }
+    };
+
+    32.0 * (corner(0, 0, 0, 0.0) + corner(i1, j1, k1, G3) + corner(i2, j2, k2, 2.0 * G3) + corner(1, 1, 1, 3.0 * G3))
+}
+
+impl Noise
+{
+    pub fn new(_basis: NoiseBasis, _kind: NoiseKind, _octaves: u32) -> Noise
+    {
+        Noise{basis: _basis, kind: _kind, octaves: _octaves.max(1), lacunarity: 2.0, gain: 0.5, seed: 0}
+    }
+
+    pub fn perlin() -> Noise
+    {
+        Noise::new(NoiseBasis::Perlin, NoiseKind::Plain, 1)
+    }
+
+    pub fn turbulence(_octaves: u32) -> Noise
+    {
+        Noise::new(NoiseBasis::Perlin, NoiseKind::Turbulence, _octaves)
+    }
+
+    fn basis(&self, p: &Vec4, seed: u32) -> f32
+    {
+        match self.basis
+        {
+            NoiseBasis::Perlin => {perlin(p, seed)},
+            NoiseBasis::Simplex => {simplex(p, seed)}
+        }
+    }
+
+    // roughly [-1, 1] for Plain and Fbm, [0, 1] for Turbulence
+    pub fn eval(&self, p: &Vec4) -> f32
+    {
+        let octaves = if self.kind == NoiseKind::Plain { 1 } else { self.octaves };
+
+        let mut sum = 0.0;
+        let mut norm = 0.0;
+        let mut freq = 1.0;
+        let mut amp = 1.0;
+        for i in 0..octaves
+        {
+            let n = self.basis(&(p * freq), self.seed.wrapping_add(i));
+            sum += amp * if self.kind == NoiseKind::Turbulence { n.abs() } else { n };
+            norm += amp;
+            freq *= self.lacunarity;
+            amp *= self.gain;
+        }
+
+        sum / norm
+    }
+}
+
+//######################################################################
+// CheckerTexture
+//######################################################################
+
+impl CheckerTexture
+{
+    pub fn new(_even: Texture, _odd: Texture, _coord: TextureCoord, _scale: Vec4) -> Texture
+    {
+        Texture::Checker{tex: CheckerTexture{even: Box::new(_even), odd: Box::new(_odd), coord: _coord, scale: _scale}}
+    }
+
+    pub fn from(_even: Vec4, _odd: Vec4, _coord: TextureCoord, _scale: f32) -> Texture
+    {
+        CheckerTexture::new(ConstantTexture::new(&_even).texture(), ConstantTexture::new(&_odd).texture(), _coord, Vec4::from(_scale))
+    }
+}
+
+impl Sample for CheckerTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let (x, y, z) = coord(hit, self.coord, &self.scale).extract_xyz();
+        let parity = (x.floor() as i64 + y.floor() as i64 + z.floor() as i64) & 1;
+
+        if parity == 0 { self.even.sample(hit) } else { self.odd.sample(hit) }
+    }
+}
+
+//######################################################################
+// NoiseTexture
+//######################################################################
+
+impl NoiseTexture
+{
+    pub fn new(_noise: Noise, _coord: TextureCoord, _scale: f32) -> Texture
+    {
+        Texture::Noise{tex: NoiseTexture{noise: _noise, coord: _coord, scale: Vec4::from(_scale)}}
+    }
+}
+
+impl Sample for NoiseTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let n = self.noise.eval(&coord(hit, self.coord, &self.scale));
+        let n = if self.noise.kind == NoiseKind::Turbulence { n } else { 0.5 + 0.5 * n };
+        Vec4::from(n.max(0.0).min(1.0))
+    }
+}
+
+//######################################################################
+// MarbleTexture
+//######################################################################
+
+impl MarbleTexture
+{
+    pub fn new(_base: Vec4, _vein: Vec4, _scale: f32, _distortion: f32) -> Texture
+    {
+        Texture::Marble{tex: MarbleTexture{base: _base, vein: _vein, noise: Noise::turbulence(6), coord: TextureCoord::World, scale: Vec4::from(_scale), distortion: _distortion}}
+    }
+}
+
+impl Sample for MarbleTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let p = coord(hit, self.coord, &self.scale);
+        let t = 0.5 + 0.5 * (p.x() + self.distortion * self.noise.eval(&p)).sin();
+        self.vein.lerp(&self.base, t.powf(0.5))
+    }
+}
+
+//######################################################################
+// WoodTexture
+//######################################################################
+
+impl WoodTexture
+{
+    pub fn new(_light: Vec4, _dark: Vec4, _scale: f32, _rings: f32, _distortion: f32) -> Texture
+    {
+        Texture::Wood{tex: WoodTexture{light: _light, dark: _dark, noise: Noise::new(NoiseBasis::Perlin, NoiseKind::Fbm, 3), coord: TextureCoord::World, scale: Vec4::from(_scale), rings: _rings, distortion: _distortion}}
+    }
+}
+
+impl Sample for WoodTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let p = coord(hit, self.coord, &self.scale);
+        let r = (p.x() * p.x() + p.z() * p.z()).sqrt() * self.rings + self.distortion * self.noise.eval(&p);
+
+        // sharp late wood at the end of each ring
+        let t = (r - r.floor()).powf(3.0);
+        self.light.lerp(&self.dark, t)
+    }
+}
+
+//######################################################################
+// VoronoiTexture
+//######################################################################
+
+impl VoronoiTexture
+{
+    pub fn new(_output: VoronoiOutput, _coord: TextureCoord, _scale: f32) -> Texture
+    {
+        Texture::Voronoi{tex: VoronoiTexture{output: _output, coord: _coord, scale: Vec4::from(_scale), jitter: 1.0, seed: 0}}
+    }
+
+    fn feature(&self, x: i32, y: i32, z: i32) -> Vec4
+    {
+        let j = |axis: u32| 0.5 + self.jitter * (hash_f32(x, y, z, self.seed.wrapping_add(axis)) - 0.5);
+        Vec4::from3(x as f32 + j(0), y as f32 + j(1), z as f32 + j(2))
+    }
+}
+
+impl Sample for VoronoiTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let p = coord(hit, self.coord, &self.scale);
+        let (cx, cy, cz) = (p.x().floor() as i32, p.y().floor() as i32, p.z().floor() as i32);
+
+        // feature points stay within their cell, the 27 neighbours contain the nearest one
+        let mut best = (std::f32::INFINITY, (cx, cy, cz));
+        for z in cz - 1..cz + 2
+        {
+            for y in cy - 1..cy + 2
+            {
+                for x in cx - 1..cx + 2
+                {
+                    let d = (self.feature(x, y, z) - p).square_length3();
+                    if d < best.0
+                    {
+                        best = (d, (x, y, z));
+                    }
+                }
+            }
+        }
+
+        match self.output
+        {
+            VoronoiOutput::Distance => {Vec4::from(best.0.sqrt().min(1.0))},
+            VoronoiOutput::Cell => {
+                let (x, y, z) = best.1;
+                Vec4::from3(hash_f32(x, y, z, self.seed ^ 0x1234), hash_f32(x, y, z, self.seed ^ 0x5678), hash_f32(x, y, z, self.seed ^ 0x9abc))
+            }
+        }
+    }
+}
+
+//######################################################################
+// GradientTexture
+//######################################################################
+
+impl GradientTexture
+{
+    pub fn new(_stops: std::vec::Vec<(f32, Vec4)>, _coord: TextureCoord, _axis: usize) -> Texture
+    {
+        let mut stops = _stops;
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        Texture::Gradient{tex: GradientTexture{stops: stops, coord: _coord, scale: Vec4::one(), axis: _axis.min(2)}}
+    }
+
+    pub fn eval(&self, t: f32) -> Vec4
+    {
+        eval_ramp(&self.stops, t)
+    }
+}
+
+// stops sorted by position, constant beyond the first and last one
+pub fn eval_ramp(stops: &[(f32, Vec4)], t: f32) -> Vec4
+{
+    let i = stops.iter().position(|s| s.0 > t).unwrap_or(stops.len());
+    if i == 0 { return stops.first().map(|s| s.1).unwrap_or(Vec4::zero()); }
+    if i == stops.len() { return stops[i - 1].1; }
+
+    let (a, b) = (&stops[i - 1], &stops[i]);
+    a.1.lerp(&b.1, (t - a.0) / (b.0 - a.0))
+}
+
+impl Sample for GradientTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        self.eval(coord(hit, self.coord, &self.scale).get(self.axis))
+    }
+}
+
+//######################################################################
+// nodes
+//######################################################################
+
+impl ScaleTexture
+{
+    pub fn new(_tex: Texture, _scale: Vec4) -> Texture
+    {
+        Texture::Scale{tex: ScaleTexture{tex: Box::new(_tex), scale: _scale}}
+    }
+}
+
+impl Sample for ScaleTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        self.tex.sample(hit) * self.scale
+    }
+}
+
+impl MixTexture
+{
+    // only the x channel of factor is used
+    pub fn new(_a: Texture, _b: Texture, _factor: Texture) -> Texture
+    {
+        Texture::Mix{tex: MixTexture{a: Box::new(_a), b: Box::new(_b), factor: Box::new(_factor)}}
+    }
+}
+
+impl Sample for MixTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let t = self.factor.sample(hit).x().max(0.0).min(1.0);
+        self.a.sample(hit).lerp(&self.b.sample(hit), t)
+    }
+}
+
+impl MultiplyTexture
+{
+    pub fn new(_a: Texture, _b: Texture) -> Texture
+    {
+        Texture::Multiply{tex: MultiplyTexture{a: Box::new(_a), b: Box::new(_b)}}
+    }
+}
+
+impl Sample for MultiplyTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        self.a.sample(hit) * self.b.sample(hit)
+    }
+}
+
+//######################################################################
+// projection
+//######################################################################
+
+impl UvTransformTexture
+{
+    // rotation in radians, applied after scaling
+    pub fn new(_tex: Texture, _scale: (f32, f32), _rotation: f32, _offset: (f32, f32)) -> Texture
+    {
+        let (sin, cos) = _rotation.sin_cos();
+        let matrix = [cos * _scale.0, -sin * _scale.1, sin * _scale.0, cos * _scale.1];
+        Texture::UvTransform{tex: UvTransformTexture{tex: Box::new(_tex), matrix: matrix, offset: _offset}}
+    }
+
+    pub fn tiled(_tex: Texture, _u: f32, _v: f32) -> Texture
+    {
+        UvTransformTexture::new(_tex, (_u, _v), 0.0, (0.0, 0.0))
+    }
+}
+
+impl Sample for UvTransformTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let m = &self.matrix;
+        let det = m[0] * m[3] - m[1] * m[2];
+        if det == 0.0
+        {
+            return self.tex.sample(hit);
+        }
+
+        let mut local = *hit;
+        local.u = m[0] * hit.u + m[1] * hit.v + self.offset.0;
+        local.v = m[2] * hit.u + m[3] * hit.v + self.offset.1;
+
+        // footprint grows with the area scale, dp/duv' = dp/duv * inverse(m) keeps normal maps aligned
+        local.footprint = hit.footprint * det.abs().sqrt();
+        local.tangent = (hit.tangent * m[3] - hit.bitangent * m[2]) / det;
+        local.bitangent = (hit.bitangent * m[0] - hit.tangent * m[1]) / det;
+
+        self.tex.sample(&local)
+    }
+}
+
+impl TriplanarTexture
+{
+    pub fn new(_tex: Texture, _scale: f32, _sharpness: f32) -> Texture
+    {
+        Texture::Triplanar{tex: TriplanarTexture{tex: Box::new(_tex), scale: _scale, sharpness: _sharpness.max(1.0)}}
+    }
+}
+
+impl Sample for TriplanarTexture
+{
+    fn sample(&self, hit: &HitInfo) -> Vec4
+    {
+        let n = hit.normal.abs();
+        let mut w = [n.x().powf(self.sharpness), n.y().powf(self.sharpness), n.z().powf(self.sharpness)];
+        let sum = w[0] + w[1] + w[2];
+        if sum <= 0.0
+        {
+            return self.tex.sample(hit);
+        }
+
+        for x in w.iter_mut() { *x /= sum; }
+
+        // projected ray cone width, primitives without uvs leave the footprint at zero
+        let cos = hit.view.dot(&hit.normal).abs().max(0.05);
+        let footprint = if hit.cone_width > 0.0 { hit.cone_width * self.scale / cos } else { hit.footprint };
+
+        let p = hit.point * self.scale;
+        let axes = [Vec4::new(1.0, 0.0, 0.0, 0.0), Vec4::new(0.0, 1.0, 0.0, 0.0), Vec4::new(0.0, 0.0, 1.0, 0.0)];
+
+        // plane perpendicular to axis i is spanned by the two other axes
+        let mut result = Vec4::zero();
+        for i in 0..3
+        {
+            if w[i] <= 0.0
+            {
+                continue;
+            }
+
+            let (a, b) = ((i + 1) % 3, (i + 2) % 3);
+            let mut local = *hit;
+            local.u = p.get(a);
+            local.v = p.get(b);
+            local.footprint = footprint;
+            local.tangent = axes[a] / self.scale;
+            local.bitangent = axes[b] / self.scale;
+
+            result += self.tex.sample(&local) * w[i];
+        }
+
+        result
+    }
+}
@@ -1,21 +1,64 @@
 use super::vec::*;
 use rand::prelude::*;
 
+#[cfg(not(test))]
+fn rng() -> ThreadRng
+{
+    rand::thread_rng()
+}
+
+// tests draw from a fixed seed, every test thread gets the same sequence on every run
+#[cfg(test)]
+thread_local!(static TEST_RNG: std::cell::RefCell<StdRng> = std::cell::RefCell::new(StdRng::seed_from_u64(0x5eed)));
+
+#[cfg(test)]
+struct TestRng;
+
+#[cfg(test)]
+impl RngCore for TestRng
+{
+    fn next_u32(&mut self) -> u32
+    {
+        TEST_RNG.with(|r| r.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64
+    {
+        TEST_RNG.with(|r| r.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8])
+    {
+        TEST_RNG.with(|r| r.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error>
+    {
+        TEST_RNG.with(|r| r.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+fn rng() -> TestRng
+{
+    TestRng
+}
+
 pub fn random_tuple(min: f32, max: f32) -> (f32, f32)
 {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng();
     (rng.gen_range(min, max), rng.gen_range(min, max))
 }
 
 pub fn random_in_unit_disk() -> Vec4
 {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng();
     Vec4::from3(rng.gen_range(0.0, 1.0), rng.gen_range(0.0, 1.0), 0.0).norm()
 }
 
 pub fn random_in_unit_disk_tuple() -> (f32, f32)
 {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng();
     let x = rng.gen_range(0.0, 1.0);
     let y = rng.gen_range(0.0, 1.0);
     let len = (x*x + y*y as f32).sqrt();    
@@ -26,7 +69,7 @@ pub fn random_in_unit_disk_tuple() -> (f32, f32)
 // http://mathworld.wolfram.com/DiskPointPicking.html
 pub fn random_in_unit_disk2() -> (f32, f32)
 {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng();
     let phi = rng.gen_range(0.0, 2.0 * std::f32::consts::PI) as f32;
     let r = (rng.gen_range(0.0, 1.0) as f32).sqrt();
     (r * phi.cos(), r * phi.sin())
@@ -34,7 +77,7 @@ pub fn random_in_unit_disk2() -> (f32, f32)
 
 pub fn random_in_unit_sphere() -> Vec4
 {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng();
 
     // method 2
     Vec4::from3(rng.gen_range(0.0, 1.0), rng.gen_range(0.0, 1.0), rng.gen_range(0.0, 1.0)).norm()
@@ -42,7 +85,7 @@ pub fn random_in_unit_sphere() -> Vec4
 
 pub fn random_in_unit_sphere2() -> Vec4
 {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng();
 
     //https://hbfs.wordpress.com/2010/10/12/random-points-on-a-sphere-generating-random-sequences-iii/
     //https://corysimon.github.io/articles/uniformdistn-on-sphere/
@@ -55,4 +98,35 @@ pub fn random_in_unit_sphere2() -> Vec4
     let z = cos_theta * y;
 
     Vec4::from3(x, y, z)
-}
\ No newline at end of file
+}
+
+pub fn random_f32() -> f32
+{
+    let mut rng = rng();
+    rng.gen_range(0.0, 1.0)
+}
+
+// cosine weighted direction around +z, pdf = cos(theta) / PI
+pub fn random_cosine_hemisphere() -> Vec4
+{
+    let (x, y) = random_in_unit_disk2();
+    Vec4::from3(x, y, (1.0 - x*x - y*y).max(0.0).sqrt())
+}
+
+// uniform direction on the unit sphere, pdf = 1 / (4 * PI)
+pub fn random_on_unit_sphere() -> Vec4
+{
+    let z = 1.0 - 2.0 * random_f32();
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * std::f32::consts::PI * random_f32();
+    Vec4::from3(r * phi.cos(), r * phi.sin(), z)
+}
+
+// uniform direction in the cone around +z, pdf = 1 / (2 * PI * (1 - cos_max))
+pub fn random_in_cone(cos_max: f32) -> Vec4
+{
+    let cos = 1.0 - random_f32() * (1.0 - cos_max);
+    let sin = (1.0 - cos * cos).max(0.0).sqrt();
+    let phi = 2.0 * std::f32::consts::PI * random_f32();
+    Vec4::from3(sin * phi.cos(), sin * phi.sin(), cos)
+}
@@ -4,21 +4,38 @@ use super::quaternion::*;
 #[derive(Copy, Clone)]
 pub struct Ray {
     pub origin: Vec4,
-    pub direction: Vec4
+    pub direction: Vec4,
+    // ray cone for texture lod: width at the origin and spread angle, both zero for point sampling
+    // http://www.realtimerendering.com/raytracinggems/unofficial_RayTracingGems_v1.5.pdf chapter 20
+    pub cone_width: f32,
+    pub cone_spread: f32
 }
 
 impl Ray {
     pub fn new(_origin: Vec4, _dir: Vec4) -> Ray {
         Ray {
             origin: _origin,
-            direction: _dir
+            direction: _dir,
+            cone_width: 0.0,
+            cone_spread: 0.0
+        }
+    }
+
+    pub fn with_cone(_origin: Vec4, _dir: Vec4, _width: f32, _spread: f32) -> Ray {
+        Ray {
+            origin: _origin,
+            direction: _dir,
+            cone_width: _width,
+            cone_spread: _spread
         }
     }
 
     pub fn invalid() -> Ray {
         Ray {
             origin: Vec4::zero(),
-            direction: Vec4::zero()
+            direction: Vec4::zero(),
+            cone_width: 0.0,
+            cone_spread: 0.0
         }
     }
 
@@ -31,7 +48,9 @@ impl Ray {
     {
         Ray {
             origin: rot.rotate_unit(&self.origin),
-            direction: rot.rotate_unit(&self.direction) // norm3 ?
+            direction: rot.rotate_unit(&self.direction), // norm3 ?
+            cone_width: self.cone_width,
+            cone_spread: self.cone_spread
         }
     }
 }
\ No newline at end of file
@@ -4,12 +4,36 @@ use super::hit::*;
 use super::ray::*;
 use super::vec::*;
 use super::texture::DynamicTextureType;
+use super::random::*;
+use super::medium::*;
+use super::light::*;
+use super::microfacet::Frame;
+use super::bsdf::Bsdf;
+use super::quaternion::Quat;
+use super::sky::SkyModel;
+
+use std::f32::consts::PI;
 //use std::vec::*;
 
+// any-hit retries per object before a ray is considered to pass through it
+const MAX_ALPHA_SKIPS: usize = 8;
+
+// member of the light set, emissive objects come before lights
+#[derive(Copy, Clone, PartialEq)]
+pub enum LightRef
+{
+    Object {obj: u32},
+    Light {light: u32}
+}
+
 pub struct Scene
 {
     objects: std::vec::Vec<Object>,
     materials: std::vec::Vec<Material>,
+    emissive: std::vec::Vec<u32>, // objects with emissive materials
+    lights: std::vec::Vec<Light>,
+    media: std::vec::Vec<Medium>,
+    fog: Option<u32>, // medium outside of all bounded volumes
     miss: u32
 }
 
@@ -18,6 +42,10 @@ impl Scene {
         let mut scn = Scene {
             objects: std::vec::Vec::new(),
             materials: std::vec::Vec::new(),
+            emissive: std::vec::Vec::new(),
+            lights: std::vec::Vec::new(),
+            media: std::vec::Vec::new(),
+            fog: None,
             miss: 0,
         };
 
@@ -37,8 +65,277 @@ impl Scene {
 
     pub fn add_prmitive(&mut self, obj: Object) -> u32
     {
+        let id = self.objects.len() as u32;
+
+        if self.is_light(&obj)
+        {
+            self.emissive.push(id);
+        }
+        else if self.get_mat(obj.mat).is_emissive()
+        {
+            eprintln!("emissive object {} has no finite area to sample, it only contributes when hit", id);
+        }
+
         self.objects.push(obj);
-        (self.objects.len() - 1) as u32
+        id
+    }
+
+    // emitters need a finite area to be sampled, the others only contribute when hit
+    fn is_light(&self, object: &Object) -> bool
+    {
+        let area = object.area();
+        self.get_mat(object.mat).is_emissive() && area > 0.0 && area.is_finite()
+    }
+
+    pub fn get_object(&self, obj: u32) -> &Object
+    {
+        &self.objects[(obj as usize)]
+    }
+
+    pub fn add_light(&mut self, light: Light) -> u32
+    {
+        self.lights.push(light);
+        (self.lights.len() - 1) as u32
+    }
+
+    pub fn get_light(&self, light: u32) -> &Light
+    {
+        &self.lights[light as usize]
+    }
+
+    // emissive objects and lights
+    pub fn light_count(&self) -> usize
+    {
+        self.emissive.len() + self.lights.len()
+    }
+
+    // uniform choice of an emissive object or light, each with probability light_select_pdf
+    pub fn pick_light(&self) -> Option<LightRef>
+    {
+        let count = self.light_count();
+        if count == 0
+        {
+            return None;
+        }
+
+        let idx = ((random_f32() * count as f32) as usize).min(count - 1);
+
+        if idx < self.emissive.len()
+        {
+            Some(LightRef::Object{obj: self.emissive[idx]})
+        }
+        else
+        {
+            Some(LightRef::Light{light: (idx - self.emissive.len()) as u32})
+        }
+    }
+
+    pub fn light_select_pdf(&self) -> f32
+    {
+        1.0 / self.light_count() as f32
+    }
+
+    // picks an emissive object or light uniformly and samples it from origin, pdf includes the selection probability
+    pub fn sample_light(&self, origin: &Vec4) -> Option<LightSample>
+    {
+        let mut sample = match self.pick_light()?
+        {
+            LightRef::Object{obj} =>
+            {
+                let mut s = self.get_object(obj).sample_surface(origin)?;
+                s.hit.object = obj;
+
+                let to_light = s.hit.point - origin;
+                let dist = to_light.length();
+                if dist <= 0.0 { return None; }
+
+                let wi = to_light / dist;
+                LightSample{point: s.hit.point, normal: s.hit.normal, wi: wi, dist: dist, le: self.get_mat(s.hit.material).emission(&-wi, &s.hit), pdf: s.pdf, delta: false}
+            },
+            LightRef::Light{light} => self.get_light(light).sample_li(origin)?
+        };
+
+        sample.pdf *= self.light_select_pdf();
+        Some(sample)
+    }
+
+    // pdf of sample_light producing the direction dir towards the hit object
+    pub fn light_pdf(&self, origin: &Vec4, dir: &Vec4, obj: u32) -> f32
+    {
+        let object = self.get_object(obj);
+
+        if !self.is_light(object)
+        {
+            return 0.0;
+        }
+
+        object.surface_pdf(origin, dir) / self.light_count() as f32
+    }
+
+    // radiance of the lights r hits before max, each weighted by weight(light, pdf of sample_light from origin)
+    pub fn light_emission<F>(&self, r: &Ray, max: f32, origin: &Vec4, camera_ray: bool, weight: F) -> Vec4
+    where F: Fn(&Light, f32) -> f32
+    {
+        let mut radiance = Vec4::zero();
+        let dir = r.direction.norm3();
+
+        for light in self.lights.iter()
+        {
+            if camera_ray && !light.camera_visible()
+            {
+                continue;
+            }
+
+            if let Some((_, le)) = light.le(r, max)
+            {
+                radiance += le * weight(light, light.pdf_li(origin, &dir) / self.light_count() as f32);
+            }
+        }
+
+        radiance
+    }
+
+    // ray leaving an emissive object or light, pdf_pos includes the selection probability
+    pub fn sample_emission(&self) -> Option<EmissionSample>
+    {
+        let mut sample = self.sample_emission_from(self.pick_light()?)?;
+        sample.pdf_pos *= self.light_select_pdf();
+        Some(sample)
+    }
+
+    // ray leaving the given emitter, pdf_pos excludes the selection probability
+    pub fn sample_emission_from(&self, light: LightRef) -> Option<EmissionSample>
+    {
+        match light
+        {
+            LightRef::Object{obj} =>
+            {
+                let object = self.get_object(obj);
+                let s = object.sample_area()?;
+
+                // cosine weighted, on a random side for two sided objects
+                let (n, side_pdf) = if !object.two_sided() { (s.hit.normal, 1.0) } else if random_f32() < 0.5 { (s.hit.normal, 0.5) } else { (-s.hit.normal, 0.5) };
+                let dir = Frame::new(&n).to_world(&random_cosine_hemisphere());
+                let cos = dir.dot(&n);
+                if cos <= 0.0 { return None; }
+
+                let le = self.get_mat(s.hit.material).emission(&dir, &s.hit);
+                Some(EmissionSample{point: s.hit.point, normal: s.hit.normal, dir: dir, le: le, pdf_pos: s.pdf, pdf_dir: side_pdf * cos / PI})
+            },
+            LightRef::Light{light} => self.get_light(light).sample_le()
+        }
+    }
+
+    // area pdf of sample_emission starting on obj, zero for emitters outside of the light set
+    pub fn light_area_pdf(&self, obj: u32) -> f32
+    {
+        let object = self.get_object(obj);
+
+        if !self.is_light(object)
+        {
+            return 0.0;
+        }
+
+        self.light_select_pdf() / object.area()
+    }
+
+    // any hit test for shadow rays, medium boundaries don't block
+    pub fn occluded(&self, r: &Ray, max: f32) -> bool
+    {
+        let mut info = HitInfo::new();
+        self.objects.iter().any(|obj| !self.get_mat(obj.mat).is_interface() && self.hit_object(obj, r, &mut info, 0.0, max))
+    }
+
+    // closest hit of obj that survives the alpha test of its material
+    fn hit_object(&self, obj: &Object, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let mut t_min = min;
+
+        // primitives only have a couple of intersections along a ray
+        for _ in 0..MAX_ALPHA_SKIPS
+        {
+            if !obj.hit(r, out, t_min, max)
+            {
+                return false;
+            }
+
+            if self.get_mat(out.material).alpha_test(out)
+            {
+                return true;
+            }
+
+            t_min = out.depth;
+        }
+
+        false
+    }
+
+    // transmittance of a shadow ray starting in medium, zero if blocked by a surface
+    pub fn transmittance(&self, r: &Ray, max: f32, medium: Option<u32>) -> Vec4
+    {
+        let mut ray = *r;
+        let mut remaining = max;
+        let mut medium = medium;
+        let mut tr = Vec4::one();
+
+        loop
+        {
+            let mut hit = HitInfo::new();
+            let found = self.hit(&ray, &mut hit, 0.0, remaining);
+
+            if found && !self.get_mat(hit.material).is_interface()
+            {
+                return Vec4::zero();
+            }
+
+            if let Some(m) = self.get_medium(medium)
+            {
+                tr *= m.transmittance(&ray, if found { hit.depth } else { remaining });
+            }
+
+            if !found || tr == Vec4::zero()
+            {
+                return tr;
+            }
+
+            medium = self.next_medium(&hit, &ray.direction, medium);
+            remaining -= hit.depth;
+            ray = hit.spawn_ray(&ray.direction);
+        }
+    }
+
+    pub fn add_medium(&mut self, medium: Medium) -> u32
+    {
+        self.media.push(medium);
+        (self.media.len() - 1) as u32
+    }
+
+    pub fn set_fog(&mut self, medium: Option<u32>)
+    {
+        self.fog = medium;
+    }
+
+    // medium of a ray inside the bounded volume inside, or outside of all volumes if None
+    pub fn get_medium(&self, inside: Option<u32>) -> Option<&Medium>
+    {
+        inside.or(self.fog).map(|m| &self.media[m as usize])
+    }
+
+    // bounded volume a ray leaving hit along dir travels in
+    pub fn next_medium(&self, hit: &HitInfo, dir: &Vec4, current: Option<u32>) -> Option<u32>
+    {
+        match self.get_object(hit.object).medium
+        {
+            Some(m) => if dir.dot(&hit.normal) < 0.0 { Some(m) } else { None },
+            None => current
+        }
+    }
+
+    // number of primitives pierced by the ray
+    pub fn hit_count(&self, r: &Ray, min: f32, max: f32) -> u32
+    {
+        let mut info = HitInfo::new();
+        self.objects.iter().filter(|obj| obj.hit(r, &mut info, min, max)).count() as u32
     }
 
     pub fn add_mat(&mut self, mat: Material) -> u32
@@ -57,12 +354,20 @@ impl Scene {
         &mut self.materials[(mat as usize)]
     }
 
-    // returns prmitive & material id
-    pub fn set_envmap<P>(&mut self, path: P, _strength: Vec4, _type: DynamicTextureType) -> (u32, u32)
+    // adds an environment light and hides the miss material behind it, returns the light id
+    pub fn set_envmap<P>(&mut self, path: P, _strength: f32, _rotation: Quat, _type: DynamicTextureType) -> u32
     where P: AsRef<std::path::Path>,
     {
-        let mat = self.add_mat(Emissive::from_path(path, _strength, _type));
-        (self.add_prmitive(Sphere::new_with_uv(Vec4::zero(), 100.0).object(mat)), mat)
+        self.miss = self.add_mat(Background::new(Vec4::zero(), 0.0));
+        self.add_light(EnvironmentLight::from_path(path, _strength, _rotation, _type))
+    }
+
+    // sky as miss material plus a sun disc light of the same model, returns the sun light id
+    pub fn set_sky(&mut self, _model: SkyModel, _strength: f32) -> u32
+    {
+        let sun = DirectionalLight::new(_model.sun(), _model.sun_irradiance() * _strength, 0.27);
+        self.miss = self.add_mat(Sky::new(_model, _strength));
+        self.add_light(sun)
     }
 }
 
@@ -72,15 +377,26 @@ impl Hitable for Scene {
         best_info.depth = max;
 
         let mut info = HitInfo::new();
-        for obj in self.objects.iter() {
-            if obj.hit(r, &mut info, min, best_info.depth)
+        for (i, obj) in self.objects.iter().enumerate() {
+            if self.hit_object(obj, r, &mut info, min, best_info.depth)
             {
                 best_info = info;
+                best_info.object = i as u32;
             }
         }
 
         if best_info.depth > min && best_info.depth < max
         {
+            // grow the ray cone to the hit and project it onto the surface
+            best_info.cone_width = r.cone_width + r.cone_spread * best_info.depth * r.direction.length3();
+            best_info.cone_spread = r.cone_spread;
+            best_info.view = -r.direction.norm3();
+            if best_info.uv_scale > 0.0
+            {
+                let cos = r.direction.norm3().dot(&best_info.normal).abs().max(0.05);
+                best_info.footprint = best_info.cone_width / (cos * best_info.uv_scale);
+            }
+
             *out = best_info;
             return true;
         }
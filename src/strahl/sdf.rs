@@ -0,0 +1,355 @@
+use super::hit::*;
+use super::vec::*;
+use super::ray::*;
+use super::quaternion::*;
+use super::primitives::*;
+
+use std::sync::Arc;
+
+// shapes given by a signed distance expression, intersected by sphere tracing
+// https://iquilezles.org/articles/distfunctions/
+
+const SDF_MAX_STEPS: u32 = 512;
+const SDF_EPSILON: f32 = 1e-5; // below RAY_EPSILON so spawned rays start clear of the surface
+const SDF_NORMAL_DELTA: f32 = 1e-4;
+
+#[derive(Clone)]
+pub enum SdfNode
+{
+    Sphere {radius: f32},
+    Box {half: Vec4, rounding: f32}, // half extents, rounding is added on top
+    Torus {major_radius: f32, minor_radius: f32}, // in the xz plane
+    Translate {node: Box<SdfNode>, offset: Vec4},
+    Rotate {node: Box<SdfNode>, rotation: Quat},
+    Scale {node: Box<SdfNode>, factor: f32},
+    Union {a: Box<SdfNode>, b: Box<SdfNode>, smooth: f32}, // smooth is the blend radius, 0 for a sharp edge
+    Subtract {a: Box<SdfNode>, b: Box<SdfNode>, smooth: f32}, // a minus b
+    Intersect {a: Box<SdfNode>, b: Box<SdfNode>, smooth: f32},
+    Repeat {node: Box<SdfNode>, period: Vec4, count: Vec4}, // 2 count + 1 copies per axis, zero period leaves the axis alone
+    Displace {node: Box<SdfNode>, amplitude: f32, frequency: f32} // sin(fx) sin(fy) sin(fz) ripples
+}
+
+#[derive(Clone)]
+pub struct Sdf
+{
+    root: Arc<SdfNode>,
+    bounds: BBox,
+    lipschitz: f32 // steps are divided by it, expressions that stretch space would overshoot otherwise
+}
+
+//######################################################################
+// SdfNode
+//######################################################################
+
+// polynomial smooth minimum
+fn smooth_min(a: f32, b: f32, k: f32) -> f32
+{
+    if k <= 0.0 { return a.min(b); }
+    let h = (0.5 + 0.5 * (b - a) / k).max(0.0).min(1.0);
+    b + (a - b) * h - k * h * (1.0 - h)
+}
+
+fn corners_bounds(lo: &Vec4, hi: &Vec4, f: &dyn Fn(Vec4) -> Vec4) -> (Vec4, Vec4)
+{
+    let mut min = Vec4::from(std::f32::MAX);
+    let mut max = Vec4::from(-std::f32::MAX);
+
+    for i in 0..8
+    {
+        let corner = Vec4::from3(
+            if i & 1 == 0 { lo.x() } else { hi.x() },
+            if i & 2 == 0 { lo.y() } else { hi.y() },
+            if i & 4 == 0 { lo.z() } else { hi.z() });
+        let p = f(corner);
+        min = min.min(&p);
+        max = max.max(&p);
+    }
+
+    (min, max)
+}
+
+impl SdfNode
+{
+    pub fn sphere(_radius: f32) -> SdfNode
+    {
+        SdfNode::Sphere{radius: _radius}
+    }
+
+    pub fn cube(_half: Vec4, _rounding: f32) -> SdfNode
+    {
+        SdfNode::Box{half: _half, rounding: _rounding}
+    }
+
+    pub fn torus(_major_radius: f32, _minor_radius: f32) -> SdfNode
+    {
+        SdfNode::Torus{major_radius: _major_radius, minor_radius: _minor_radius}
+    }
+
+    pub fn translate(_node: SdfNode, _offset: Vec4) -> SdfNode
+    {
+        SdfNode::Translate{node: Box::new(_node), offset: _offset}
+    }
+
+    pub fn rotate(_node: SdfNode, _rotation: Quat) -> SdfNode
+    {
+        SdfNode::Rotate{node: Box::new(_node), rotation: _rotation}
+    }
+
+    pub fn scale(_node: SdfNode, _factor: f32) -> SdfNode
+    {
+        SdfNode::Scale{node: Box::new(_node), factor: _factor}
+    }
+
+    pub fn union(_a: SdfNode, _b: SdfNode, _smooth: f32) -> SdfNode
+    {
+        SdfNode::Union{a: Box::new(_a), b: Box::new(_b), smooth: _smooth}
+    }
+
+    pub fn subtract(_a: SdfNode, _b: SdfNode, _smooth: f32) -> SdfNode
+    {
+        SdfNode::Subtract{a: Box::new(_a), b: Box::new(_b), smooth: _smooth}
+    }
+
+    pub fn intersect(_a: SdfNode, _b: SdfNode, _smooth: f32) -> SdfNode
+    {
+        SdfNode::Intersect{a: Box::new(_a), b: Box::new(_b), smooth: _smooth}
+    }
+
+    pub fn repeat(_node: SdfNode, _period: Vec4, _count: Vec4) -> SdfNode
+    {
+        SdfNode::Repeat{node: Box::new(_node), period: _period, count: _count}
+    }
+
+    pub fn displace(_node: SdfNode, _amplitude: f32, _frequency: f32) -> SdfNode
+    {
+        SdfNode::Displace{node: Box::new(_node), amplitude: _amplitude, frequency: _frequency}
+    }
+
+    pub fn distance(&self, p: &Vec4) -> f32
+    {
+        match self
+        {
+            SdfNode::Sphere{radius} => p.length3() - radius,
+            SdfNode::Box{half, rounding} =>
+            {
+                let q = p.abs() - half;
+                Vec4::from3(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0)).length3() + q.max_elem3().min(0.0) - rounding
+            },
+            SdfNode::Torus{major_radius, minor_radius} =>
+            {
+                let x = (p.x() * p.x() + p.z() * p.z()).sqrt() - major_radius;
+                (x * x + p.y() * p.y()).sqrt() - minor_radius
+            },
+            SdfNode::Translate{node, offset} => node.distance(&(p - offset)),
+            SdfNode::Rotate{node, rotation} => node.distance(&rotation.inverse_rotate_unit(p)),
+            // a negative factor mirrors through the origin and keeps the inside in
+            SdfNode::Scale{node, factor} => node.distance(&(p / *factor)) * factor.abs(),
+            SdfNode::Union{a, b, smooth} => smooth_min(a.distance(p), b.distance(p), *smooth),
+            SdfNode::Subtract{a, b, smooth} => -smooth_min(-a.distance(p), b.distance(p), *smooth),
+            SdfNode::Intersect{a, b, smooth} => -smooth_min(-a.distance(p), -b.distance(p), *smooth),
+            SdfNode::Repeat{node, period, count} =>
+            {
+                // nearest copy, clamped so the repetition stays finite
+                let mut q = *p;
+                for i in 0..3
+                {
+                    let c = period.get(i);
+                    if c > 0.0
+                    {
+                        let n = count.get(i);
+                        q.set(p.get(i) - c * (p.get(i) / c).round().max(-n).min(n), i);
+                    }
+                }
+                node.distance(&q)
+            },
+            SdfNode::Displace{node, amplitude, frequency} =>
+            {
+                let f = *frequency;
+                node.distance(p) + amplitude * (f * p.x()).sin() * (f * p.y()).sin() * (f * p.z()).sin()
+            }
+        }
+    }
+
+    // local box containing the surface
+    pub fn bounds(&self) -> (Vec4, Vec4)
+    {
+        match self
+        {
+            SdfNode::Sphere{radius} => (Vec4::from3(-radius, -radius, -radius), Vec4::from3(*radius, *radius, *radius)),
+            SdfNode::Box{half, rounding} =>
+            {
+                let e = half.abs() + Vec4::from3(*rounding, *rounding, *rounding);
+                (-e, e)
+            },
+            SdfNode::Torus{major_radius, minor_radius} =>
+            {
+                let e = major_radius + minor_radius;
+                (Vec4::from3(-e, -minor_radius, -e), Vec4::from3(e, *minor_radius, e))
+            },
+            SdfNode::Translate{node, offset} =>
+            {
+                let (lo, hi) = node.bounds();
+                (lo + offset, hi + offset)
+            },
+            SdfNode::Rotate{node, rotation} =>
+            {
+                let (lo, hi) = node.bounds();
+                corners_bounds(&lo, &hi, &|c| rotation.rotate_unit(&c))
+            },
+            SdfNode::Scale{node, factor} =>
+            {
+                let (lo, hi) = node.bounds();
+                let (a, b) = (lo * *factor, hi * *factor);
+                (a.min(&b), a.max(&b))
+            },
+            // smooth blends bulge by at most a quarter of the radius
+            SdfNode::Union{a, b, smooth} =>
+            {
+                let ((alo, ahi), (blo, bhi)) = (a.bounds(), b.bounds());
+                let k = Vec4::from(0.25 * smooth);
+                (alo.min(&blo) - k, ahi.max(&bhi) + k)
+            },
+            SdfNode::Subtract{a, smooth, ..} =>
+            {
+                let (lo, hi) = a.bounds();
+                let k = Vec4::from(0.25 * smooth);
+                (lo - k, hi + k)
+            },
+            SdfNode::Intersect{a, b, smooth} =>
+            {
+                let ((alo, ahi), (blo, bhi)) = (a.bounds(), b.bounds());
+                let k = Vec4::from(0.25 * smooth);
+                (alo.max(&blo) - k, ahi.min(&bhi) + k)
+            },
+            SdfNode::Repeat{node, period, count} =>
+            {
+                let (lo, hi) = node.bounds();
+                let e = Vec4::from3(period.x() * count.x(), period.y() * count.y(), period.z() * count.z()).abs();
+                (lo - e, hi + e)
+            },
+            SdfNode::Displace{node, amplitude, ..} =>
+            {
+                let (lo, hi) = node.bounds();
+                let a = Vec4::from(amplitude.abs());
+                (lo - a, hi + a)
+            }
+        }
+    }
+
+    // bound on |gradient|, 1 for exact distances
+    pub fn lipschitz(&self) -> f32
+    {
+        match self
+        {
+            SdfNode::Sphere{..} | SdfNode::Box{..} | SdfNode::Torus{..} => 1.0,
+            SdfNode::Translate{node, ..} | SdfNode::Rotate{node, ..} | SdfNode::Scale{node, ..} | SdfNode::Repeat{node, ..} => node.lipschitz(),
+            SdfNode::Union{a, b, ..} | SdfNode::Subtract{a, b, ..} | SdfNode::Intersect{a, b, ..} => a.lipschitz().max(b.lipschitz()),
+            SdfNode::Displace{node, amplitude, frequency} => node.lipschitz() + (amplitude * frequency).abs() * 3f32.sqrt()
+        }
+    }
+}
+
+//######################################################################
+// Sdf
+//######################################################################
+
+impl Sdf
+{
+    pub fn new(_root: SdfNode) -> Sdf
+    {
+        let (lo, hi) = _root.bounds();
+        let lipschitz = _root.lipschitz().max(1.0);
+        Sdf{root: Arc::new(_root), bounds: BBox::from_corners(lo, hi), lipschitz: lipschitz}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Sdf{obj: self.clone()}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        self.bounds
+    }
+
+    pub fn distance(&self, p: &Vec4) -> f32
+    {
+        self.root.distance(p)
+    }
+
+    // tetrahedron of central differences
+    fn normal(&self, p: &Vec4) -> Vec4
+    {
+        let h = SDF_NORMAL_DELTA;
+        let k = [Vec4::from3(1.0, -1.0, -1.0), Vec4::from3(-1.0, -1.0, 1.0), Vec4::from3(-1.0, 1.0, -1.0), Vec4::from3(1.0, 1.0, 1.0)];
+
+        let mut n = Vec4::zero();
+        for d in k.iter()
+        {
+            n += d * self.distance(&(p + d * h));
+        }
+
+        if n.length3() > 0.0 { n.norm3() } else { Vec4::from3(0.0, 1.0, 0.0) }
+    }
+}
+
+impl Hitable for Sdf
+{
+    // marches from the entry of the bounds, rays starting inside the shape trace -distance to find the exit
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let (near, far) = match self.bounds.clip(r)
+        {
+            Some(span) => span,
+            None => return false
+        };
+
+        let scale = r.direction.length3();
+        if scale == 0.0 { return false; }
+
+        let mut t = near.max(min);
+        let end = far.min(max);
+        if t >= end { return false; }
+
+        let sign = if self.distance(&r.point_at(t)) < 0.0 { -1.0 } else { 1.0 };
+
+        for _ in 0..SDF_MAX_STEPS
+        {
+            let d = sign * self.distance(&r.point_at(t));
+
+            if d < SDF_EPSILON
+            {
+                if t <= min { return false; }
+
+                out.depth = t;
+                out.point = r.point_at(t);
+                out.normal = self.normal(&out.point);
+                return true;
+            }
+
+            t += d / (self.lipschitz * scale);
+            if t >= end { return false; }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn negative_scale_mirrors()
+    {
+        let offset = SdfNode::translate(SdfNode::sphere(1.0), Vec4::from3(2.0, 0.0, 0.0));
+        let node = SdfNode::scale(offset, -0.5);
+
+        assert!((node.distance(&Vec4::from3(-1.0, 0.0, 0.0)) + 0.5).abs() < 1e-5, "inside must stay negative");
+        assert!((node.distance(&Vec4::from3(1.0, 0.0, 0.0)) - 1.5).abs() < 1e-5);
+
+        let (lo, hi) = node.bounds();
+        assert!(lo.x() <= -1.5 && hi.x() >= -0.5 && hi.x() < 0.0);
+    }
+}
@@ -0,0 +1,129 @@
+#[derive(PartialEq, Copy, Clone)]
+pub enum DebugMode
+{
+    Normal,
+    Uv,
+    Depth,
+    Cost
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum IntegratorType
+{
+    Path,
+    NeePath, // next event estimation + multiple importance sampling
+    AmbientOcclusion,
+    DirectLighting,
+    Bidirectional,
+    PhotonMap, // path tracing with a caustic photon map
+    VolumePath, // next event estimation through participating media
+    Debug {mode: DebugMode}
+}
+
+impl IntegratorType
+{
+    pub fn from_name(name: &str) -> Option<IntegratorType>
+    {
+        match name
+        {
+            "path" => Some(IntegratorType::Path),
+            "nee" => Some(IntegratorType::NeePath),
+            "ao" => Some(IntegratorType::AmbientOcclusion),
+            "direct" => Some(IntegratorType::DirectLighting),
+            "bdpt" => Some(IntegratorType::Bidirectional),
+            "photon" => Some(IntegratorType::PhotonMap),
+            "volpath" => Some(IntegratorType::VolumePath),
+            "normal" => Some(IntegratorType::Debug{mode: DebugMode::Normal}),
+            "uv" => Some(IntegratorType::Debug{mode: DebugMode::Uv}),
+            "depth" => Some(IntegratorType::Debug{mode: DebugMode::Depth}),
+            "cost" => Some(IntegratorType::Debug{mode: DebugMode::Cost}),
+            _ => None
+        }
+    }
+}
+
+// lower survival caps would cut off all light past rr_depth
+pub const MIN_RR_SURVIVAL: f32 = 0.05;
+
+#[derive(Copy, Clone)]
+pub struct TraceSettings
+{
+    pub integrator: IntegratorType,
+    pub max_depth: u32, // 0 = unbounded, terminated by russian roulette only
+    pub max_diffuse_depth: u32,
+    pub max_specular_depth: u32, // glossy and mirror reflections
+    pub max_transmission_depth: u32,
+    pub max_volume_depth: u32, // scattering events inside media
+    pub rr_depth: u32, // bounces before russian roulette starts
+    pub rr_max_survival: f32, // clamped to [MIN_RR_SURVIVAL, 1]
+    pub ao_samples: u32,
+    pub ao_distance: f32,
+    pub bdpt_max_depth: u32, // all subpath vertices get connected, keep this small
+    pub photon_count: u32, // emitted, only photons reaching a surface via specular bounces are stored
+    pub photon_gather: u32, // nearest photons used for density estimation
+    pub photon_radius: f32 // max gather radius
+}
+
+impl TraceSettings
+{
+    pub fn new() -> TraceSettings
+    {
+        TraceSettings
+        {
+            integrator: IntegratorType::Path,
+            max_depth: 64,
+            max_diffuse_depth: 8,
+            max_specular_depth: 16,
+            max_transmission_depth: 32,
+            max_volume_depth: 64,
+            rr_depth: 3,
+            rr_max_survival: 0.95,
+            ao_samples: 16,
+            ao_distance: 1.0,
+            bdpt_max_depth: 8,
+            photon_count: 1000000,
+            photon_gather: 64,
+            photon_radius: 0.05
+        }
+    }
+
+    // parses --max-depth=N style overrides, unknown arguments are reported and ignored
+    pub fn from_args<I>(args: I) -> TraceSettings
+    where I: Iterator<Item = String>
+    {
+        let mut settings = TraceSettings::new();
+
+        for arg in args
+        {
+            let mut split = arg.splitn(2, '=');
+            let key = split.next().unwrap_or("");
+            let value = split.next().unwrap_or("");
+
+            let parsed = match key
+            {
+                "--integrator" => IntegratorType::from_name(value).map(|v| settings.integrator = v).is_some(),
+                "--max-depth" => value.parse().map(|v| settings.max_depth = v).is_ok(),
+                "--max-diffuse-depth" => value.parse().map(|v| settings.max_diffuse_depth = v).is_ok(),
+                "--max-specular-depth" => value.parse().map(|v| settings.max_specular_depth = v).is_ok(),
+                "--max-transmission-depth" => value.parse().map(|v| settings.max_transmission_depth = v).is_ok(),
+                "--max-volume-depth" => value.parse().map(|v| settings.max_volume_depth = v).is_ok(),
+                "--rr-depth" => value.parse().map(|v| settings.rr_depth = v).is_ok(),
+                "--rr-max-survival" => value.parse().map(|v: f32| settings.rr_max_survival = v.max(MIN_RR_SURVIVAL).min(1.0)).is_ok(),
+                "--ao-samples" => value.parse().map(|v: u32| settings.ao_samples = v.max(1)).is_ok(),
+                "--ao-distance" => value.parse().map(|v| settings.ao_distance = v).is_ok(),
+                "--bdpt-max-depth" => value.parse().map(|v| settings.bdpt_max_depth = v).is_ok(),
+                "--photon-count" => value.parse().map(|v| settings.photon_count = v).is_ok(),
+                "--photon-gather" => value.parse().map(|v: u32| settings.photon_gather = v.max(1)).is_ok(),
+                "--photon-radius" => value.parse().map(|v| settings.photon_radius = v).is_ok(),
+                _ => false
+            };
+
+            if !parsed
+            {
+                eprintln!("ignoring argument {}", arg);
+            }
+        }
+
+        settings
+    }
+}
@@ -0,0 +1,942 @@
+use super::hit::*;
+use super::vec::*;
+use super::ray::*;
+use super::quaternion::*;
+use super::microfacet::Frame;
+use super::primitives::*;
+use super::random::*;
+
+use std::f32::consts::PI;
+
+// analytic shapes built around the z axis of a local frame, rays are moved into the frame and hits back out
+// roots of the implicit surfaces are filtered to the part belonging to the shape
+
+#[derive(Copy, Clone)]
+pub struct Cylinder
+{
+    base: Vec4,
+    frame: Frame,
+    radius: f32,
+    height: f32,
+    capped: bool
+}
+
+// apex at base + axis * height
+#[derive(Copy, Clone)]
+pub struct Cone
+{
+    base: Vec4,
+    frame: Frame,
+    radius: f32,
+    height: f32,
+    capped: bool
+}
+
+// ring around the axis, tube of minor_radius at major_radius from the center
+#[derive(Copy, Clone)]
+pub struct Torus
+{
+    center: Vec4,
+    frame: Frame,
+    major_radius: f32,
+    minor_radius: f32
+}
+
+// points within radius of the segment from base to base + axis * height
+#[derive(Copy, Clone)]
+pub struct Capsule
+{
+    base: Vec4,
+    frame: Frame,
+    radius: f32,
+    height: f32
+}
+
+// z = height * (x^2 + y^2) / radius^2, open at the top
+#[derive(Copy, Clone)]
+pub struct Paraboloid
+{
+    base: Vec4,
+    frame: Frame,
+    radius: f32,
+    height: f32
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Part
+{
+    Side,
+    Bottom,
+    Top
+}
+
+// surface at a local hit point, derivatives w.r.t. u and v
+struct Local
+{
+    normal: Vec4,
+    u: f32,
+    v: f32,
+    dpdu: Vec4,
+    dpdv: Vec4
+}
+
+//######################################################################
+// shared
+//######################################################################
+
+fn local_ray(base: &Vec4, frame: &Frame, r: &Ray) -> (Vec4, Vec4)
+{
+    (frame.to_local(&(r.origin - base)), frame.to_local(&r.direction))
+}
+
+// real roots of a t^2 + 2 b t + c in ascending order, avoids cancellation
+fn quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)>
+{
+    if a == 0.0
+    {
+        if b == 0.0 { return None; }
+        let t = -c / (2.0 * b);
+        return Some((t, t));
+    }
+
+    let disc = b * b - a * c;
+    if disc < 0.0 { return None; }
+
+    let q = -(b + b.signum() * disc.sqrt());
+    if q == 0.0 { return Some((0.0, 0.0)); }
+
+    let (t0, t1) = (q / a, c / q);
+    Some(if t0 < t1 { (t0, t1) } else { (t1, t0) })
+}
+
+// keeps t if it lies in (min, max) and is closer than the current best
+fn closer(best: &mut Option<(f32, Part)>, t: f32, part: Part, min: f32, max: f32)
+{
+    if t > min && t < max && best.map_or(true, |(b, _)| t < b)
+    {
+        *best = Some((t, part));
+    }
+}
+
+// disk of radius in the plane at height z
+fn cap(best: &mut Option<(f32, Part)>, o: &Vec4, d: &Vec4, z: f32, radius: f32, part: Part, min: f32, max: f32)
+{
+    if d.z() == 0.0 { return; }
+
+    let t = (z - o.z()) / d.z();
+    let p = o + d * t;
+    if p.x() * p.x() + p.y() * p.y() <= radius * radius
+    {
+        closer(best, t, part, min, max);
+    }
+}
+
+fn cap_surface(p: &Vec4, radius: f32, side: f32) -> Local
+{
+    Local{
+        normal: Vec4::from3(0.0, 0.0, side),
+        u: 0.5 + 0.5 * side * p.x() / radius,
+        v: 0.5 + 0.5 * p.y() / radius,
+        dpdu: Vec4::from3(2.0 * radius * side, 0.0, 0.0),
+        dpdv: Vec4::from3(0.0, 2.0 * radius, 0.0)}
+}
+
+// angle around the axis in [0, 1] and dp/du of it
+fn azimuth(p: &Vec4) -> (f32, Vec4)
+{
+    let phi = p.y().atan2(p.x());
+    ((phi + PI) / (2.0 * PI), Vec4::from3(-p.y(), p.x(), 0.0) * (2.0 * PI))
+}
+
+// unit direction away from the axis, x if on it
+fn radial(p: &Vec4) -> Vec4
+{
+    let rho = (p.x() * p.x() + p.y() * p.y()).sqrt();
+    if rho > 0.0 { Vec4::from3(p.x() / rho, p.y() / rho, 0.0) } else { Vec4::from3(1.0, 0.0, 0.0) }
+}
+
+fn finish(out: &mut HitInfo, r: &Ray, frame: &Frame, t: f32, s: Local) -> bool
+{
+    out.depth = t;
+    out.point = r.point_at(t);
+    fill(out, frame, s);
+    true
+}
+
+// surface at the local point p for area sampling, pdf w.r.t. area
+fn area_point(base: &Vec4, frame: &Frame, p: &Vec4, s: Local, area: f32) -> Option<SurfaceSample>
+{
+    let mut hit = HitInfo::new();
+    hit.point = base + frame.to_world(p);
+    fill(&mut hit, frame, s);
+    Some(SurfaceSample{hit: hit, pdf: 1.0 / area})
+}
+
+fn fill(out: &mut HitInfo, frame: &Frame, s: Local)
+{
+    out.normal = frame.to_world(&s.normal).norm3();
+    out.u = s.u;
+    out.v = s.v;
+    out.tangent = frame.to_world(&s.dpdu);
+    out.bitangent = frame.to_world(&s.dpdv);
+    out.uv_scale = (s.dpdu.length3() * s.dpdv.length3()).sqrt();
+}
+
+// point at distance rho from the axis at height z
+fn around(rho: f32, z: f32) -> Vec4
+{
+    let phi = 2.0 * PI * random_f32();
+    Vec4::from3(rho * phi.cos(), rho * phi.sin(), z)
+}
+
+// world bounds of the local box [lo, hi] placed at base in frame
+fn frame_bounds(base: &Vec4, frame: &Frame, lo: Vec4, hi: Vec4) -> BBox
+{
+    let mut min = Vec4::from(std::f32::MAX);
+    let mut max = Vec4::from(-std::f32::MAX);
+
+    for i in 0..8
+    {
+        let corner = Vec4::from3(
+            if i & 1 == 0 { lo.x() } else { hi.x() },
+            if i & 2 == 0 { lo.y() } else { hi.y() },
+            if i & 4 == 0 { lo.z() } else { hi.z() });
+        let p = base + frame.to_world(&corner);
+        min = min.min(&p);
+        max = max.max(&p);
+    }
+
+    BBox::from_corners(min, max)
+}
+
+//######################################################################
+// quartic
+//######################################################################
+
+const EQN_EPS: f64 = 1e-9;
+
+// roots of c[0] + c[1] x + c[2] x^2 (+ c[3] x^3 ...), unordered
+// https://github.com/erich666/GraphicsGems/blob/master/gems/Roots3And4.c
+fn solve_quadric(c: [f64; 3], s: &mut [f64]) -> usize
+{
+    let p = c[1] / (2.0 * c[2]);
+    let q = c[0] / c[2];
+    let d = p * p - q;
+
+    if d.abs() < EQN_EPS
+    {
+        s[0] = -p;
+        1
+    }
+    else if d < 0.0
+    {
+        0
+    }
+    else
+    {
+        let sqrt_d = d.sqrt();
+        s[0] = sqrt_d - p;
+        s[1] = -sqrt_d - p;
+        2
+    }
+}
+
+fn solve_cubic(c: [f64; 4], s: &mut [f64]) -> usize
+{
+    let a = c[2] / c[3];
+    let b = c[1] / c[3];
+    let c0 = c[0] / c[3];
+
+    // x = y - a / 3 removes the quadratic term
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c0) / 2.0;
+
+    let cb_p = p * p * p;
+    let d = q * q + cb_p;
+
+    let num = if d.abs() < EQN_EPS
+    {
+        if q.abs() < EQN_EPS
+        {
+            s[0] = 0.0;
+            1
+        }
+        else
+        {
+            let u = (-q).cbrt();
+            s[0] = 2.0 * u;
+            s[1] = -u;
+            2
+        }
+    }
+    else if d < 0.0
+    {
+        let phi = (-q / (-cb_p).sqrt()).max(-1.0).min(1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        s[0] = t * phi.cos();
+        s[1] = -t * (phi + std::f64::consts::PI / 3.0).cos();
+        s[2] = -t * (phi - std::f64::consts::PI / 3.0).cos();
+        3
+    }
+    else
+    {
+        let sqrt_d = d.sqrt();
+        s[0] = (sqrt_d - q).cbrt() - (sqrt_d + q).cbrt();
+        1
+    };
+
+    for x in s[..num].iter_mut() { *x -= a / 3.0; }
+    num
+}
+
+fn solve_quartic(c: [f64; 5], s: &mut [f64; 4]) -> usize
+{
+    let a = c[3] / c[4];
+    let b = c[2] / c[4];
+    let c1 = c[1] / c[4];
+    let d = c[0] / c[4];
+
+    // x = y - a / 4 removes the cubic term
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c1;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c1 / 4.0 + d;
+
+    let num = if r.abs() < EQN_EPS
+    {
+        let n = solve_cubic([q, p, 0.0, 1.0], &mut s[..]);
+        s[n] = 0.0;
+        n + 1
+    }
+    else
+    {
+        // a root of the resolvent cubic splits the quartic into two quadrics, the largest keeps both factors real
+        let mut z = [0.0; 3];
+        let n = solve_cubic([r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0], &mut z);
+        let z = z[..n].iter().cloned().fold(std::f64::MIN, f64::max);
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+
+        let u = if u.abs() < EQN_EPS { 0.0 } else if u > 0.0 { u.sqrt() } else { return 0; };
+        let v = if v.abs() < EQN_EPS { 0.0 } else if v > 0.0 { v.sqrt() } else { return 0; };
+
+        let n = solve_quadric([z - u, if q < 0.0 { -v } else { v }, 1.0], &mut s[..]);
+        n + solve_quadric([z + u, if q < 0.0 { v } else { -v }, 1.0], &mut s[n..])
+    };
+
+    for x in s[..num].iter_mut()
+    {
+        *x -= a / 4.0;
+
+        // closed form loses digits, polish on the original polynomial
+        for _ in 0..2
+        {
+            let f = (((c[4] * *x + c[3]) * *x + c[2]) * *x + c[1]) * *x + c[0];
+            let df = ((4.0 * c[4] * *x + 3.0 * c[3]) * *x + 2.0 * c[2]) * *x + c[1];
+            if df != 0.0 { *x -= f / df; }
+        }
+    }
+
+    num
+}
+
+//######################################################################
+// Cylinder
+//######################################################################
+
+impl Cylinder
+{
+    // from _base along _axis, caps close both ends
+    pub fn new(_base: Vec4, _axis: Vec4, _radius: f32, _height: f32, _capped: bool) -> Cylinder
+    {
+        Cylinder{base: _base, frame: Frame::new(&_axis.norm3()), radius: _radius, height: _height, capped: _capped}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Cylinder{obj: *self}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        frame_bounds(&self.base, &self.frame, Vec4::from3(-self.radius, -self.radius, 0.0), Vec4::from3(self.radius, self.radius, self.height))
+    }
+
+    fn surface(&self, p: &Vec4, part: Part) -> Local
+    {
+        match part
+        {
+            Part::Side =>
+            {
+                let (u, dpdu) = azimuth(p);
+                Local{normal: radial(p), u: u, v: p.z() / self.height, dpdu: dpdu, dpdv: Vec4::from3(0.0, 0.0, self.height)}
+            },
+            Part::Bottom => cap_surface(p, self.radius, -1.0),
+            Part::Top => cap_surface(p, self.radius, 1.0)
+        }
+    }
+}
+
+impl Hitable for Cylinder
+{
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let (o, d) = local_ray(&self.base, &self.frame, r);
+        let mut best = None;
+
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = o.x() * d.x() + o.y() * d.y();
+        let c = o.x() * o.x() + o.y() * o.y() - self.radius * self.radius;
+
+        if let Some((t0, t1)) = quadratic(a, b, c)
+        {
+            for &t in [t0, t1].iter()
+            {
+                let z = o.z() + d.z() * t;
+                if z >= 0.0 && z <= self.height { closer(&mut best, t, Part::Side, min, max); }
+            }
+        }
+
+        if self.capped
+        {
+            cap(&mut best, &o, &d, 0.0, self.radius, Part::Bottom, min, max);
+            cap(&mut best, &o, &d, self.height, self.radius, Part::Top, min, max);
+        }
+
+        let (t, part) = match best
+        {
+            Some(b) => b,
+            None => return false
+        };
+
+        finish(out, r, &self.frame, t, self.surface(&(o + d * t), part))
+    }
+}
+
+impl SampleSurface for Cylinder
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        area_sample(self.sample_area()?.hit, self.area(), origin, true)
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        area_pdf(self, self.area(), origin, dir)
+    }
+
+    // side or cap in proportion to their area
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let side = 2.0 * PI * self.radius * self.height;
+        let pick = random_f32() * self.area();
+
+        let (p, part) = if pick < side
+        {
+            (around(self.radius, random_f32() * self.height), Part::Side)
+        }
+        else
+        {
+            let bottom = pick < side + PI * self.radius * self.radius;
+            (around(self.radius * random_f32().sqrt(), if bottom { 0.0 } else { self.height }), if bottom { Part::Bottom } else { Part::Top })
+        };
+
+        area_point(&self.base, &self.frame, &p, self.surface(&p, part), self.area())
+    }
+
+    fn area(&self) -> f32
+    {
+        let caps = if self.capped { 2.0 * PI * self.radius * self.radius } else { 0.0 };
+        2.0 * PI * self.radius * self.height + caps
+    }
+}
+
+//######################################################################
+// Cone
+//######################################################################
+
+impl Cone
+{
+    // _radius at _base, the cap closes the base
+    pub fn new(_base: Vec4, _axis: Vec4, _radius: f32, _height: f32, _capped: bool) -> Cone
+    {
+        Cone{base: _base, frame: Frame::new(&_axis.norm3()), radius: _radius, height: _height, capped: _capped}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Cone{obj: *self}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        frame_bounds(&self.base, &self.frame, Vec4::from3(-self.radius, -self.radius, 0.0), Vec4::from3(self.radius, self.radius, self.height))
+    }
+
+    fn surface(&self, p: &Vec4, part: Part) -> Local
+    {
+        match part
+        {
+            Part::Side =>
+            {
+                let k2 = (self.radius / self.height) * (self.radius / self.height);
+                let (u, dpdu) = azimuth(p);
+                let n = Vec4::from3(p.x(), p.y(), k2 * (self.height - p.z()));
+                let n = if n.length3() > 0.0 { n.norm3() } else { Vec4::from3(0.0, 0.0, 1.0) };
+                Local{normal: n, u: u, v: p.z() / self.height, dpdu: dpdu, dpdv: Vec4::from3(0.0, 0.0, self.height) - radial(p) * self.radius}
+            },
+            _ => cap_surface(p, self.radius, -1.0)
+        }
+    }
+}
+
+impl Hitable for Cone
+{
+    // x^2 + y^2 = k^2 (h - z)^2 with k = radius / height
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let (o, d) = local_ray(&self.base, &self.frame, r);
+        let mut best = None;
+
+        let k2 = (self.radius / self.height) * (self.radius / self.height);
+        let w = self.height - o.z();
+
+        let a = d.x() * d.x() + d.y() * d.y() - k2 * d.z() * d.z();
+        let b = o.x() * d.x() + o.y() * d.y() + k2 * w * d.z();
+        let c = o.x() * o.x() + o.y() * o.y() - k2 * w * w;
+
+        // the mirrored nappe above the apex is cut away by the height test
+        if let Some((t0, t1)) = quadratic(a, b, c)
+        {
+            for &t in [t0, t1].iter()
+            {
+                let z = o.z() + d.z() * t;
+                if z >= 0.0 && z <= self.height { closer(&mut best, t, Part::Side, min, max); }
+            }
+        }
+
+        if self.capped
+        {
+            cap(&mut best, &o, &d, 0.0, self.radius, Part::Bottom, min, max);
+        }
+
+        let (t, part) = match best
+        {
+            Some(b) => b,
+            None => return false
+        };
+
+        finish(out, r, &self.frame, t, self.surface(&(o + d * t), part))
+    }
+}
+
+impl SampleSurface for Cone
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        area_sample(self.sample_area()?.hit, self.area(), origin, true)
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        area_pdf(self, self.area(), origin, dir)
+    }
+
+    // the side grows linearly with the distance s from the apex, so s = sqrt(xi)
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let side = PI * self.radius * (self.radius * self.radius + self.height * self.height).sqrt();
+
+        let (p, part) = if random_f32() * self.area() < side
+        {
+            let s = random_f32().sqrt();
+            (around(self.radius * s, self.height * (1.0 - s)), Part::Side)
+        }
+        else
+        {
+            (around(self.radius * random_f32().sqrt(), 0.0), Part::Bottom)
+        };
+
+        area_point(&self.base, &self.frame, &p, self.surface(&p, part), self.area())
+    }
+
+    fn area(&self) -> f32
+    {
+        let cap = if self.capped { PI * self.radius * self.radius } else { 0.0 };
+        PI * self.radius * (self.radius * self.radius + self.height * self.height).sqrt() + cap
+    }
+}
+
+//######################################################################
+// Torus
+//######################################################################
+
+impl Torus
+{
+    pub fn new(_center: Vec4, _axis: Vec4, _major_radius: f32, _minor_radius: f32) -> Torus
+    {
+        Torus{center: _center, frame: Frame::new(&_axis.norm3()), major_radius: _major_radius, minor_radius: _minor_radius}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Torus{obj: *self}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        let e = self.major_radius + self.minor_radius;
+        frame_bounds(&self.center, &self.frame, Vec4::from3(-e, -e, -self.minor_radius), Vec4::from3(e, e, self.minor_radius))
+    }
+
+    // normal points from the core circle to p
+    fn surface(&self, p: &Vec4) -> Local
+    {
+        let dir = radial(p);
+        let core = dir * self.major_radius;
+        let tube = p - core;
+
+        let (u, dpdu) = azimuth(p);
+        let psi = tube.z().atan2(tube.dot3(&dir));
+        let normal = if tube.length3() > 0.0 { tube.norm3() } else { dir };
+        let dpdv = (Vec4::from3(0.0, 0.0, 1.0) * tube.dot3(&dir) - dir * tube.z()) * (2.0 * PI);
+
+        Local{normal: normal, u: u, v: (psi + PI) / (2.0 * PI), dpdu: dpdu, dpdv: dpdv}
+    }
+}
+
+impl Hitable for Torus
+{
+    // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2), solved in f64 along the normalized direction
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let (o, d) = local_ray(&self.center, &self.frame, r);
+        let len = d.length3();
+        if len == 0.0 { return false; }
+        let dn = d / len;
+
+        // bounding sphere, also moves the origin next to the torus so the coefficients stay small
+        let outer = self.major_radius + self.minor_radius;
+        let closest = -o.dot3(&dn);
+        if o.square_length3() - closest * closest > outer * outer { return false; }
+        let shift = (closest - outer).max(0.0);
+        let os = o + dn * shift;
+
+        // scaled to a unit major radius, the solver's tolerances are absolute
+        let scale = 1.0 / self.major_radius as f64;
+        let (ox, oy, oz) = (os.x() as f64 * scale, os.y() as f64 * scale, os.z() as f64 * scale);
+        let (dx, dy, dz) = (dn.x() as f64, dn.y() as f64, dn.z() as f64);
+        let big = 1.0;
+        let small = (self.minor_radius as f64 * scale) * (self.minor_radius as f64 * scale);
+
+        let n = ox * dx + oy * dy + oz * dz;
+        let q = ox * ox + oy * oy + oz * oz + big - small;
+
+        let coeffs = [
+            q * q - 4.0 * big * (ox * ox + oy * oy),
+            4.0 * n * q - 8.0 * big * (ox * dx + oy * dy),
+            4.0 * n * n + 2.0 * q - 4.0 * big * (dx * dx + dy * dy),
+            4.0 * n,
+            1.0];
+
+        let mut roots = [0.0; 4];
+        let count = solve_quartic(coeffs, &mut roots);
+
+        let mut best = None;
+        for &root in roots[..count].iter()
+        {
+            closer(&mut best, (shift + (root / scale) as f32) / len, Part::Side, min, max);
+        }
+
+        let t = match best
+        {
+            Some((t, _)) => t,
+            None => return false
+        };
+
+        finish(out, r, &self.frame, t, self.surface(&(o + d * t)))
+    }
+}
+
+impl SampleSurface for Torus
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        area_sample(self.sample_area()?.hit, self.area(), origin, true)
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        area_pdf(self, self.area(), origin, dir)
+    }
+
+    // rings around the axis get longer away from it, so the tube angle is kept with probability (R + r cos psi) / (R + r)
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let (big, small) = (self.major_radius, self.minor_radius);
+        let psi = loop
+        {
+            let psi = 2.0 * PI * random_f32();
+            if random_f32() * (big + small) <= big + small * psi.cos() { break psi; }
+        };
+
+        let p = around(big + small * psi.cos(), small * psi.sin());
+        area_point(&self.center, &self.frame, &p, self.surface(&p), self.area())
+    }
+
+    fn area(&self) -> f32
+    {
+        4.0 * PI * PI * self.major_radius * self.minor_radius
+    }
+}
+
+//######################################################################
+// Capsule
+//######################################################################
+
+impl Capsule
+{
+    // segment from _a to _b swept by a sphere of _radius
+    pub fn new(_a: Vec4, _b: Vec4, _radius: f32) -> Capsule
+    {
+        let axis = _b - _a;
+        let height = axis.length3();
+        let frame = if height > 0.0 { Frame::new(&(axis / height)) } else { Frame::new(&Vec4::from3(0.0, 1.0, 0.0)) };
+        Capsule{base: _a, frame: frame, radius: _radius, height: height}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Capsule{obj: *self}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        frame_bounds(&self.base, &self.frame, Vec4::from3(-self.radius, -self.radius, -self.radius), Vec4::from3(self.radius, self.radius, self.height + self.radius))
+    }
+
+    fn surface(&self, p: &Vec4, part: Part) -> Local
+    {
+        let n = match part
+        {
+            Part::Side => radial(p),
+            Part::Bottom => p / self.radius,
+            Part::Top => (p - Vec4::from3(0.0, 0.0, self.height)) / self.radius
+        };
+
+        // v is the arc length along a meridian from pole to pole
+        let length = self.height + PI * self.radius;
+        let arc = match part
+        {
+            Part::Bottom => self.radius * (-n.z()).max(-1.0).min(1.0).acos(),
+            Part::Side => 0.5 * PI * self.radius + p.z(),
+            Part::Top => 0.5 * PI * self.radius + self.height + self.radius * n.z().max(-1.0).min(1.0).asin()
+        };
+
+        let out_dir = radial(p);
+        let rho = (n.x() * n.x() + n.y() * n.y()).sqrt();
+        let meridian = Vec4::from3(0.0, 0.0, rho) - out_dir * n.z();
+        let (u, dpdu) = azimuth(p);
+
+        Local{normal: n, u: u, v: arc / length, dpdu: dpdu, dpdv: meridian * length}
+    }
+
+    fn end(&self, best: &mut Option<(f32, Part)>, o: &Vec4, d: &Vec4, z: f32, part: Part, min: f32, max: f32)
+    {
+        let oc = o - Vec4::from3(0.0, 0.0, z);
+        if let Some((t0, t1)) = quadratic(d.square_length3(), oc.dot3(d), oc.square_length3() - self.radius * self.radius)
+        {
+            for &t in [t0, t1].iter()
+            {
+                let pz = o.z() + d.z() * t;
+                if (part == Part::Bottom && pz <= 0.0) || (part == Part::Top && pz >= self.height) { closer(best, t, part, min, max); }
+            }
+        }
+    }
+}
+
+impl Hitable for Capsule
+{
+    // cylinder side plus the outer halves of the end spheres
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let (o, d) = local_ray(&self.base, &self.frame, r);
+        let mut best = None;
+
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = o.x() * d.x() + o.y() * d.y();
+        let c = o.x() * o.x() + o.y() * o.y() - self.radius * self.radius;
+
+        if let Some((t0, t1)) = quadratic(a, b, c)
+        {
+            for &t in [t0, t1].iter()
+            {
+                let z = o.z() + d.z() * t;
+                if z >= 0.0 && z <= self.height { closer(&mut best, t, Part::Side, min, max); }
+            }
+        }
+
+        self.end(&mut best, &o, &d, 0.0, Part::Bottom, min, max);
+        self.end(&mut best, &o, &d, self.height, Part::Top, min, max);
+
+        let (t, part) = match best
+        {
+            Some(b) => b,
+            None => return false
+        };
+
+        finish(out, r, &self.frame, t, self.surface(&(o + d * t), part))
+    }
+}
+
+impl SampleSurface for Capsule
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        area_sample(self.sample_area()?.hit, self.area(), origin, true)
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        area_pdf(self, self.area(), origin, dir)
+    }
+
+    // the end caps together form a full sphere
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let side = 2.0 * PI * self.radius * self.height;
+
+        let (p, part) = if random_f32() * self.area() < side
+        {
+            (around(self.radius, random_f32() * self.height), Part::Side)
+        }
+        else
+        {
+            let p = random_on_unit_sphere() * self.radius;
+            if p.z() <= 0.0 { (p, Part::Bottom) } else { (p + Vec4::from3(0.0, 0.0, self.height), Part::Top) }
+        };
+
+        area_point(&self.base, &self.frame, &p, self.surface(&p, part), self.area())
+    }
+
+    fn area(&self) -> f32
+    {
+        2.0 * PI * self.radius * self.height + 4.0 * PI * self.radius * self.radius
+    }
+}
+
+//######################################################################
+// Paraboloid
+//######################################################################
+
+impl Paraboloid
+{
+    // bowl with its vertex at _base, opening along _axis to _radius at _height
+    pub fn new(_base: Vec4, _axis: Vec4, _radius: f32, _height: f32) -> Paraboloid
+    {
+        Paraboloid{base: _base, frame: Frame::new(&_axis.norm3()), radius: _radius, height: _height}
+    }
+
+    pub fn object(&self, _mat: u32) -> Object
+    {
+        Object::new(&Primitive::Paraboloid{obj: *self}, _mat, Quat::new())
+    }
+
+    pub fn bounds(&self) -> BBox
+    {
+        frame_bounds(&self.base, &self.frame, Vec4::from3(-self.radius, -self.radius, 0.0), Vec4::from3(self.radius, self.radius, self.height))
+    }
+
+    fn surface(&self, p: &Vec4) -> Local
+    {
+        let (h, r2) = (self.height, self.radius * self.radius);
+        let v = p.z() / h;
+        let (u, dpdu) = azimuth(p);
+        let normal = Vec4::from3(2.0 * h * p.x(), 2.0 * h * p.y(), -r2).norm3();
+        let dpdv = Vec4::from3(p.x(), p.y(), 0.0) / (2.0 * v.max(1e-4)) + Vec4::from3(0.0, 0.0, h);
+
+        Local{normal: normal, u: u, v: v, dpdu: dpdu, dpdv: dpdv}
+    }
+
+    // squared slope of the bowl at unit distance from the axis, nearly flat bowls are treated as disks
+    fn slope2(&self) -> Option<f32>
+    {
+        let k = 2.0 * self.height / (self.radius * self.radius);
+        if k * k * self.radius * self.radius > 1e-3 { Some(k * k) } else { None }
+    }
+}
+
+impl Hitable for Paraboloid
+{
+    // h (x^2 + y^2) - r^2 z = 0
+    fn hit(&self, r: &Ray, out: &mut HitInfo, min: f32, max: f32) -> bool
+    {
+        let (o, d) = local_ray(&self.base, &self.frame, r);
+        let (h, r2) = (self.height, self.radius * self.radius);
+        let mut best = None;
+
+        let a = h * (d.x() * d.x() + d.y() * d.y());
+        let b = h * (o.x() * d.x() + o.y() * d.y()) - 0.5 * r2 * d.z();
+        let c = h * (o.x() * o.x() + o.y() * o.y()) - r2 * o.z();
+
+        if let Some((t0, t1)) = quadratic(a, b, c)
+        {
+            for &t in [t0, t1].iter()
+            {
+                let z = o.z() + d.z() * t;
+                if z >= 0.0 && z <= h { closer(&mut best, t, Part::Side, min, max); }
+            }
+        }
+
+        let t = match best
+        {
+            Some((t, _)) => t,
+            None => return false
+        };
+
+        finish(out, r, &self.frame, t, self.surface(&(o + d * t)))
+    }
+}
+
+impl SampleSurface for Paraboloid
+{
+    fn sample_surface(&self, origin: &Vec4) -> Option<SurfaceSample>
+    {
+        area_sample(self.sample_area()?.hit, self.area(), origin, true)
+    }
+
+    fn surface_pdf(&self, origin: &Vec4, dir: &Vec4) -> f32
+    {
+        area_pdf(self, self.area(), origin, dir)
+    }
+
+    // the area within rho of the axis grows like (1 + k^2 rho^2)^(3/2) - 1, inverted for rho
+    fn sample_area(&self) -> Option<SurfaceSample>
+    {
+        let r = self.radius;
+        let rho = match self.slope2()
+        {
+            Some(k2) =>
+            {
+                let s = 1.0 + random_f32() * ((1.0 + k2 * r * r).powf(1.5) - 1.0);
+                ((s.powf(2.0 / 3.0) - 1.0).max(0.0) / k2).sqrt().min(r)
+            },
+            None => r * random_f32().sqrt()
+        };
+
+        let p = around(rho, self.height * rho * rho / (r * r));
+        area_point(&self.base, &self.frame, &p, self.surface(&p), self.area())
+    }
+
+    fn area(&self) -> f32
+    {
+        let r = self.radius;
+        match self.slope2()
+        {
+            Some(k2) => 2.0 * PI / (3.0 * k2) * ((1.0 + k2 * r * r).powf(1.5) - 1.0),
+            None => PI * r * r
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use super::vec::*;
+use super::sky_data::*;
+
+use std::f32::consts::PI;
+
+// analytic daylight skies, y is up and sun points towards the sun
+
+// http://www.cs.utah.edu/~shirley/papers/sunsky/sunsky.pdf
+#[derive(Copy, Clone)]
+pub struct PreethamSky
+{
+    pub sun: Vec4,
+    pub turbidity: f32,
+    pub ground_albedo: Vec4,
+    perez: [[f32; 5]; 3], // Y, x, y
+    zenith: [f32; 3] // Y in kcd/m^2, x, y
+}
+
+// https://cgg.mff.cuni.cz/projects/SkylightModelling/
+#[derive(Copy, Clone)]
+pub struct HosekSky
+{
+    pub sun: Vec4,
+    pub turbidity: f32,
+    pub ground_albedo: Vec4,
+    config: [[f32; 9]; 3],
+    radiance: [f32; 3]
+}
+
+#[derive(Clone)]
+pub enum SkyModel
+{
+    Preetham {sky: PreethamSky},
+    Hosek {sky: HosekSky}
+}
+
+impl SkyModel
+{
+    pub fn sun(&self) -> Vec4
+    {
+        match self
+        {
+            SkyModel::Preetham {sky} => {sky.sun},
+            SkyModel::Hosek {sky} => {sky.sun}
+        }
+    }
+
+    // linear srgb radiance seen along dir, below the horizon a diffuse ground lit by the horizon
+    pub fn radiance(&self, dir: &Vec4) -> Vec4
+    {
+        let dir = dir.norm3();
+        let (ground, albedo) = (dir.y() < 0.0, self.ground_albedo());
+        let dir = if ground { Vec4::from3(dir.x(), 0.0, dir.z()).norm3() } else { dir };
+
+        let l = match self
+        {
+            SkyModel::Preetham {sky} => {sky.radiance(&dir)},
+            SkyModel::Hosek {sky} => {sky.radiance(&dir)}
+        };
+
+        if ground { l * albedo } else { l }
+    }
+
+    fn ground_albedo(&self) -> Vec4
+    {
+        match self
+        {
+            SkyModel::Preetham {sky} => {sky.ground_albedo},
+            SkyModel::Hosek {sky} => {sky.ground_albedo}
+        }
+    }
+
+    // irradiance of the sun disc in the units of the sky radiance
+    pub fn sun_irradiance(&self) -> Vec4
+    {
+        match self
+        {
+            SkyModel::Preetham {sky} => {sun_irradiance(&sky.sun, sky.turbidity)},
+            SkyModel::Hosek {sky} =>
+            {
+                // the rgb dataset has no sun, scale the Preetham one by the ratio of the zenith luminances
+                let preetham = PreethamSky::new(sky.sun, sky.turbidity, sky.ground_albedo);
+                let zenith = Vec4::from3(0.0, 1.0, 0.0);
+                let ratio = sky.radiance(&zenith).luminance() / preetham.radiance(&zenith).luminance().max(1e-6);
+                sun_irradiance(&sky.sun, sky.turbidity) * ratio
+            }
+        }
+    }
+}
+
+fn xyz_to_rgb(x: f32, y: f32, z: f32) -> Vec4
+{
+    Vec4::from3(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z).max(&Vec4::zero())
+}
+
+// angle between the zenith and dir, angle between the sun and dir
+fn sky_angles(sun: &Vec4, dir: &Vec4) -> (f32, f32)
+{
+    (dir.y().max(0.0).min(1.0).acos(), dir.dot(sun).max(-1.0).min(1.0).acos())
+}
+
+// extraterrestrial sun illuminance of 128 klux, attenuated by rayleigh and aerosol scattering at 680, 550 and 440nm
+// http://www.cs.utah.edu/~shirley/papers/sunsky/sunsky.pdf appendix A.2
+fn sun_irradiance(sun: &Vec4, turbidity: f32) -> Vec4
+{
+    let theta = sun.y().max(0.0).min(1.0).acos();
+    if theta >= PI * 0.5
+    {
+        return Vec4::zero();
+    }
+
+    // relative optical mass
+    let m = 1.0 / (theta.cos() + 0.15 * (93.885 - theta.to_degrees()).powf(-1.253));
+    let beta = 0.04608 * turbidity - 0.04586;
+
+    let tau = |lambda: f32| (-0.008735 * lambda.powf(-4.08) * m).exp() * (-beta * lambda.powf(-1.3) * m).exp();
+    Vec4::from3(tau(0.68), tau(0.55), tau(0.44)) * 128.0
+}
+
+//######################################################################
+// PreethamSky
+//######################################################################
+
+impl PreethamSky
+{
+    pub fn new(_sun: Vec4, _turbidity: f32, _ground_albedo: Vec4) -> PreethamSky
+    {
+        let sun = _sun.norm3();
+        let t = _turbidity.max(1.0);
+        let theta = sun.y().max(0.0).min(1.0).acos();
+
+        let perez = [
+            [0.1787 * t - 1.4630, -0.3554 * t + 0.4275, -0.0227 * t + 5.3251, 0.1206 * t - 2.5771, -0.0670 * t + 0.3703],
+            [-0.0193 * t - 0.2592, -0.0665 * t + 0.0008, -0.0004 * t + 0.2125, -0.0641 * t - 0.8989, -0.0033 * t + 0.0452],
+            [-0.0167 * t - 0.2608, -0.0950 * t + 0.0092, -0.0079 * t + 0.2102, -0.0441 * t - 1.6537, -0.0109 * t + 0.0529]];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta);
+        let (t2, th, th2, th3) = (t * t, theta, theta * theta, theta * theta * theta);
+
+        let zenith_y = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let zenith_x = t2 * (0.00166 * th3 - 0.00375 * th2 + 0.00209 * th)
+            + t * (-0.02903 * th3 + 0.06377 * th2 - 0.03202 * th + 0.00394)
+            + (0.11693 * th3 - 0.21196 * th2 + 0.06052 * th + 0.25886);
+        let zenith_yc = t2 * (0.00275 * th3 - 0.00610 * th2 + 0.00317 * th)
+            + t * (-0.04214 * th3 + 0.08970 * th2 - 0.04153 * th + 0.00516)
+            + (0.15346 * th3 - 0.26756 * th2 + 0.06670 * th + 0.26688);
+
+        PreethamSky{sun: sun, turbidity: t, ground_albedo: _ground_albedo, perez: perez, zenith: [zenith_y, zenith_x, zenith_yc]}
+    }
+
+    pub fn model(self) -> SkyModel
+    {
+        SkyModel::Preetham{sky: self}
+    }
+
+    fn perez(c: &[f32; 5], theta: f32, gamma: f32) -> f32
+    {
+        (1.0 + c[0] * (c[1] / theta.cos().max(0.01)).exp()) * (1.0 + c[2] * (c[3] * gamma).exp() + c[4] * gamma.cos() * gamma.cos())
+    }
+
+    pub fn radiance(&self, dir: &Vec4) -> Vec4
+    {
+        let (theta, gamma) = sky_angles(&self.sun, dir);
+        let theta_sun = self.sun.y().max(0.0).min(1.0).acos();
+
+        let mut values = [0.0; 3];
+        for i in 0..3
+        {
+            values[i] = self.zenith[i] * PreethamSky::perez(&self.perez[i], theta, gamma) / PreethamSky::perez(&self.perez[i], 0.0, theta_sun);
+        }
+
+        // xyY to XYZ
+        let (lum, x, y) = (values[0], values[1], values[2]);
+        if y <= 0.0
+        {
+            return Vec4::zero();
+        }
+        xyz_to_rgb(x / y * lum, lum, (1.0 - x - y) / y * lum)
+    }
+}
+
+//######################################################################
+// HosekSky
+//######################################################################
+
+// quintic bezier through the 6 elevation control points, stride apart
+fn bezier(data: &[f32], stride: usize, x: f32) -> f32
+{
+    let ix = 1.0 - x;
+    ix.powi(5) * data[0]
+        + 5.0 * ix.powi(4) * x * data[stride]
+        + 10.0 * ix.powi(3) * x.powi(2) * data[2 * stride]
+        + 10.0 * ix.powi(2) * x.powi(3) * data[3 * stride]
+        + 5.0 * ix * x.powi(4) * data[4 * stride]
+        + x.powi(5) * data[5 * stride]
+}
+
+// blends the four albedo / turbidity tables around the parameters, count values per control point
+fn hosek_cook<F>(data: &[f32], count: usize, turbidity: f32, albedo: f32, elevation: f32, mut out: F)
+where F: FnMut(usize, f32)
+{
+    let x = (elevation / (PI * 0.5)).max(0.0).min(1.0).powf(1.0 / 3.0);
+    let low = (turbidity as usize).max(1).min(10);
+    let rem = turbidity - low as f32;
+
+    let table = count * 6;
+    let mut blend = |turb: usize, alb: usize, weight: f32|
+    {
+        let base = alb * table * 10 + table * (turb - 1);
+        for i in 0..count
+        {
+            out(i, weight * bezier(&data[base + i..], count, x));
+        }
+    };
+
+    blend(low, 0, (1.0 - albedo) * (1.0 - rem));
+    blend(low, 1, albedo * (1.0 - rem));
+    if low < 10
+    {
+        blend(low + 1, 0, (1.0 - albedo) * rem);
+        blend(low + 1, 1, albedo * rem);
+    }
+}
+
+impl HosekSky
+{
+    pub fn new(_sun: Vec4, _turbidity: f32, _ground_albedo: Vec4) -> HosekSky
+    {
+        let sun = _sun.norm3();
+        let turbidity = _turbidity.max(1.0).min(10.0);
+        let elevation = sun.y().max(0.0).min(1.0).asin();
+
+        let mut config = [[0.0; 9]; 3];
+        let mut radiance = [0.0; 3];
+        for c in 0..3
+        {
+            let albedo = _ground_albedo.get(c).max(0.0).min(1.0);
+            hosek_cook(&HOSEK_RGB_COEFFICIENTS[c], 9, turbidity, albedo, elevation, |i, v| config[c][i] += v);
+            hosek_cook(&HOSEK_RGB_RADIANCES[c], 1, turbidity, albedo, elevation, |_, v| radiance[c] += v);
+        }
+
+        HosekSky{sun: sun, turbidity: turbidity, ground_albedo: _ground_albedo, config: config, radiance: radiance}
+    }
+
+    pub fn model(self) -> SkyModel
+    {
+        SkyModel::Hosek{sky: self}
+    }
+
+    pub fn radiance(&self, dir: &Vec4) -> Vec4
+    {
+        let (theta, gamma) = sky_angles(&self.sun, dir);
+        let (cos_theta, cos_gamma) = (theta.cos(), gamma.cos());
+
+        let mut rgb = [0.0; 3];
+        for c in 0..3
+        {
+            let k = &self.config[c];
+            let exp_m = (k[4] * gamma).exp();
+            let ray_m = cos_gamma * cos_gamma;
+            let mie_m = (1.0 + ray_m) / (1.0 + k[8] * k[8] - 2.0 * k[8] * cos_gamma).max(1e-6).powf(1.5);
+            let zenith = cos_theta.max(0.0).sqrt();
+
+            rgb[c] = (1.0 + k[0] * (k[1] / (cos_theta + 0.01)).exp())
+                * (k[2] + k[3] * exp_m + k[5] * ray_m + k[6] * mie_m + k[7] * zenith) * self.radiance[c];
+        }
+
+        Vec4::from3(rgb[0], rgb[1], rgb[2]).max(&Vec4::zero())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // values of the reference implementation for a sun 30 degrees up, turbidity 3 and albedo 0.3
+    #[test]
+    fn hosek_matches_reference()
+    {
+        let sun = Vec4::from3(30.0f32.to_radians().cos(), 0.5, 0.0);
+        let sky = HosekSky::new(sun, 3.0, Vec4::from(0.3));
+
+        let expected = [
+            (Vec4::from3(0.0, 1.0, 0.0), Vec4::from3(1.7577, 3.3454, 6.6762)),
+            (Vec4::from3(0.0, 1.0, 1.0), Vec4::from3(2.6885, 4.9390, 9.0618)),
+            (Vec4::from3(35.0f32.to_radians().cos(), 35.0f32.to_radians().sin(), 0.0), Vec4::from3(20.0703, 22.1903, 23.3703))];
+
+        for (dir, rgb) in expected.iter()
+        {
+            let l = sky.radiance(&dir.norm3());
+            for c in 0..3
+            {
+                assert!((l.get(c) - rgb.get(c)).abs() < 1e-3 * rgb.get(c), "channel {} is {} expected {}", c, l.get(c), rgb.get(c));
+            }
+        }
+    }
+}
@@ -0,0 +1,536 @@
+// rgb dataset of the Hosek-Wilkie sky model, ArHosekSkyModelData_RGB.h of the reference implementation
+// https://cgg.mff.cuni.cz/projects/SkylightModelling/
+//
+// This source is published under the following 3-clause BSD license.
+//
+// Copyright (c) 2012 - 2013, Lukas Hosek and Alexander Wilkie
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright
+//       notice, this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//     * None of the names of the contributors may be used to endorse or promote
+//       products derived from this software without specific prior written
+//       permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+// ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+// WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+// (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+// LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+// ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+// (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// per channel: 2 albedos x 10 turbidities x 6 elevation control points x 9 coefficients
+pub const HOSEK_RGB_COEFFICIENTS: [[f32; 1080]; 3] = [
+    [
+        // r, albedo 0, turbidity 1
+        -1.099459, -0.1335146, -4.083223, 5.919603, -0.1104166, 1.600158, -1.326538e-6, 4.917807, 0.5127716,
+        -1.169858, -0.1832793, 0.9694744, 0.09495762, -0.04738918, 0.2194171, 0.1095749, 3.603604, 0.3815119,
+        -0.9665225, -0.1403888, 5.194457, -1.107607, -0.8135181, 4.969661, -0.2300508, -2.48935, 1.279158,
+        -1.292508, -0.1299552, -2.071404, -0.04752482, 1.215598, -1.904179, 0.3027985, 8.707768, 0.06332446,
+        -0.9264666, -0.169678, 4.57407, -0.4232936, -7.575833, 5.079755, -0.2576343, -4.506805, 0.6908129,
+        -1.139072, -0.1796056, 1.923311, 6.788529, -2.364389, -1.064041, 0.171701, 1.534681, 0.501581,
+        // r, albedo 0, turbidity 2
+        -1.107257, -0.1384411, -4.285744, 5.713157, -0.1015992, 1.372638, 0.06555893, 5.127514, 0.6550471,
+        -1.187337, -0.1969013, 0.8551048, 0.05289708, -0.07626406, 0.01733153, 0.1779454, 3.801038, 0.4742709,
+        -0.9685321, -0.1553308, 4.732492, -1.178935, -0.7852791, 4.604492, -0.2666518, -2.367663, 1.177527,
+        -1.252817, -0.05129949, -2.800433, -0.01295992, 1.308964, -2.204331, 0.7276011, 8.699265, 0.1188388,
+        -0.9459509, -0.2322133, 4.375041, -0.1712018, -7.451681, 5.078019, -0.4223538, -4.595561, 1.074719,
+        -1.125092, -0.179675, 1.626399, 6.989743, -2.406382, -0.9060383, 0.2961611, 1.337715, 0.543814,
+        // r, albedo 0, turbidity 3
+        -1.135338, -0.171616, -1.499253, 2.373491, -0.1654023, 0.9566404, 0.1113453, 4.528473, 0.6579439,
+        -1.13278, -0.1456214, -1.736672, 1.756589, -0.1087003, 0.3757927, 0.252507, 7.178513, 0.5003814,
+        -1.167176, -0.2927225, 5.727667, -3.139244, -0.6425204, 2.822634, -0.1457812, -6.78708, 1.017072,
+        -1.042529, 0.04110823, -4.000629, 4.362364, 1.09054, -1.338674, 0.8246964, 10.95249, 0.2912211,
+        -1.061598, -0.2096143, 3.803155, -7.977069, -3.63788, 3.707671, -0.1903128, -3.397953, 0.99715,
+        -1.07356, -0.2077964, 1.492052, 16.26322, -5.015304, -0.4059889, 0.2659782, 0.639538, 0.5634436,
+        // r, albedo 0, turbidity 4
+        -1.172794, -0.2111186, -1.360013, 1.60408, -0.08473723, 0.7217312, 0.154803, 4.25701, 0.6328974,
+        -1.238374, -0.2670827, 0.3247678, 0.5466311, -0.7425952, 0.527644, 0.02678026, 5.484169, 0.6814734,
+        -1.176923, -0.2574586, 2.304045, -2.797678, 1.464405, 1.998552, 0.2550559, -4.199772, 0.7544892,
+        -1.003284, 0.01943984, -2.145066, 10.30924, -15.25413, -2.02301, 0.5448699, 8.159497, 0.5539148,
+        -1.060017, -0.2037206, 2.483018, -4.595459, 6.526991, 4.031804, 0.1206513, -2.586527, 0.7875752,
+        -1.081141, -0.2123302, 1.092275, 2.683841, -4.166938, -1.396582, 0.4371205, 1.030233, 0.6664862,
+        // r, albedo 0, turbidity 5
+        -1.222392, -0.2651924, -0.4625037, 0.3521964, 0.02148855, 0.5078494, 0.179159, 3.852516, 0.5998216,
+        -1.42461, -0.4710155, -0.1826815, 1.786277, -1.952442, 0.5277612, -0.01773629, 2.415874, 0.6701272,
+        -1.130655, -0.1358609, 0.9171203, -4.660394, 6.251162, 1.904529, 0.2639668, 1.85613, 0.822844,
+        -0.9739015, -0.06674749, -0.4768897, 12.48589, -19.94688, -2.353043, 0.5885575, 1.287251, 0.4830135,
+        -1.082178, -0.1974495, 1.050245, -4.792855, 8.663406, 3.246969, 0.1556731, 0.8117442, 0.8050376,
+        -1.063354, -0.1727108, 0.9681592, 2.736077, -4.969269, -0.836057, 0.5994612, 1.024039, 0.6786935,
+        // r, albedo 0, turbidity 6
+        -1.261936, -0.3053676, -0.4262222, 0.4000196, -0.02059388, 0.4721802, 0.1480028, 3.505343, 0.6121337,
+        -1.681088, -0.6971919, -0.1105652, 0.7437426, -0.6594399, 0.2254221, 0.08710195, 1.263913, 0.5681865,
+        -0.9453001, 0.03460388, 0.6067038, -1.985128, 3.457236, 2.655483, -0.01162354, 3.304716, 1.00195,
+        -1.086609, -0.2029011, -0.639917, 6.926885, -15.12189, -3.793051, 0.945612, 0.2222222, 0.2893725,
+        -1.041259, -0.138879, 1.147331, 6.282086, 3.679836, 4.398314, -0.1355232, 1.031134, 0.9273509,
+        -1.063473, -0.1916051, 0.6556979, -0.003371891, -3.699664, -1.926783, 0.7371154, 1.179975, 0.6367068,
+        // r, albedo 0, turbidity 7
+        -1.33639, -0.3778927, -0.7259477, 0.2270247, 0.4627513, 0.1366459, 0.2637347, 3.292059, 0.4998211,
+        -2.119878, -1.055472, 0.5422052, 0.7826648, -1.286065, 0.9517905, -0.1432358, -0.2379816, 0.5910513,
+        -0.7761432, 0.2124336, -0.6845184, -0.9812342, 4.347257, 0.967198, 0.377315, 5.789529, 0.9646598,
+        -1.118734, -0.3513815, 0.5500918, 0.9449627, -12.6207, -1.82528, 0.473126, -3.326892, 0.3568768,
+        -1.026437, -0.08257946, 0.3221701, 11.98372, 1.55513, 2.560304, 0.1406465, 2.912858, 0.8643181,
+        -1.069949, -0.2029607, 0.5825042, -0.002398595, -3.278335, -1.349882, 0.7208433, 0.8505164, 0.6625391,
+        // r, albedo 0, turbidity 8
+        -1.392309, -0.4454945, -0.5664, 0.6283393, -0.3761727, 0.6949802, 0.07748178, 3.192797, 0.5968661,
+        -2.713405, -1.395112, 0.202923, 0.1877272, -0.3715859, -0.1652929, 0.2385861, -0.4150768, 0.1375467,
+        -0.9588644, 0.024339, -1.527493, -0.9632874, 5.496269, 1.094931, 0.2004044, 6.084554, 1.369604,
+        -0.8028546, -0.2473563, 1.617898, 2.073591, -11.49446, -0.8394131, 0.2726847, -4.634538, 0.1367293,
+        -1.198326, -0.1804865, -0.3565414, 4.0732, 1.662086, 1.23977, 0.3367978, 2.997402, 0.9360383,
+        -1.013531, -0.185906, 0.5799857, 13.31883, -4.346873, -1.11382, 0.5275714, 0.8045177, 0.6496373,
+        // r, albedo 0, turbidity 9
+        -1.530103, -0.6107468, -0.3841771, 1.881508, -1.464807, 0.665469, -5.950797e-6, 2.738912, 0.8101012,
+        -2.415469, -1.057499, -0.4161968, -2.357548, 0.6300296, 0.6224915, 0.01545048, 2.038561, -0.1339415,
+        -3.096796, -1.465688, -1.199232, 4.567061, 3.26098, -0.9794907, 0.8950491, 2.049235, 1.331015,
+        0.2713904, 0.2852852, 1.20209, -8.206784, -5.805762, 1.804431, -0.6090648, -1.990902, 0.3288858,
+        -1.45658, -0.345596, -0.06409257, 16.67697, -2.311094, -0.9771104, 0.6759863, 1.245136, 0.7911932,
+        -0.9860389, -0.2099564, 0.294665, -0.0035478, -2.268313, -0.06205647, 0.4705185, 0.8657995, 0.6856284,
+        // r, albedo 0, turbidity 10
+        -1.971736, -0.9414047, -0.3400557, 1.468763, -1.474284, 0.5501062, -1.10975e-5, 2.35637, 0.9001702,
+        -1.589845, -0.7797079, -0.558224, -0.8137376, 0.5846617, 0.1129459, -0.02658005, 2.707248, -0.2112486,
+        -6.940173, -2.823963, -1.620848, 1.090696, 2.39173, 1.370047, 0.5890462, 1.7284, 1.331253,
+        1.293144, -0.001919778, 1.644206, -0.8666967, -7.161953, -1.385018, -0.1505374, -1.388643, 0.2530122,
+        -1.48888, -0.2495496, -0.2377137, 11.67714, -0.8617124, 1.053828, 0.1992744, 0.3633564, 0.8553304,
+        -1.060891, -0.4035829, 0.2823207, -0.002369798, -1.876577, -0.5950265, 0.4241017, 0.3140802, 0.6631669,
+        // r, albedo 1, turbidity 1
+        -1.101204, -0.1351353, -4.030882, 6.096353, -0.1148599, 1.606507, -1.555474e-6, 4.436084, 0.5973715,
+        -1.154597, -0.1923378, 0.8512132, 0.2934895, -0.06522777, 0.1389077, 0.09091469, 3.133307, 0.2108541,
+        -1.031588, -0.1546804, 5.266214, -0.949139, -0.7184867, 4.875626, -0.1911907, -2.865642, 1.087895,
+        -1.159454, -0.09546699, -1.508146, -0.02031411, 1.040653, -2.333508, 0.2540592, 8.594981, 0.0931677,
+        -1.03594, -0.2021151, 4.719343, -0.9019318, -7.858046, 3.901234, -0.2233137, -4.344739, 0.6550733,
+        -1.096669, -0.1558196, 2.057553, 6.274495, -2.678352, -1.814927, 0.1550676, 1.903276, 0.4998989,
+        // r, albedo 1, turbidity 2
+        -1.114209, -0.1473531, -7.602914, 8.973685, -0.04980074, 1.289198, 0.08366906, 4.557987, 0.6118757,
+        -1.149397, -0.1981628, 4.914096, -3.498986, -0.0625709, 0.1667401, 0.104898, 2.284689, 0.5935965,
+        -1.056121, -0.1456172, 0.4272656, 2.912649, -0.5501745, 4.406542, -0.138768, 1.245555, 0.9733011,
+        -1.125047, -0.04003662, 1.058457, -3.462236, 0.4395278, -2.395805, 0.5177589, 4.866247, 0.4253189,
+        -1.051444, -0.2804541, 3.364668, 3.293787, -10.15741, 3.807407, -0.3592377, -3.367415, 0.7900825,
+        -1.093847, -0.1436965, 2.38478, 5.78707, -2.445987, -1.311171, 0.2326563, 1.158439, 0.5555416,
+        // r, albedo 1, turbidity 3
+        -1.134824, -0.1680468, -3.32562, 4.458596, -0.1135063, 1.1045, 0.07794544, 4.609952, 0.6854854,
+        -1.143017, -0.1565926, 0.3014687, -0.1763027, -0.03557925, -0.2342406, 0.2528705, 5.884085, 0.4750602,
+        -1.136801, -0.2907502, 3.682423, -0.4061202, -0.8728159, 4.00151, -0.1522202, -5.528713, 1.044847,
+        -1.063652, 0.07808107, -1.983678, 0.3648078, 2.102276, -3.06505, 0.8431951, 10.3883, 0.2662834,
+        -1.061015, -0.2859814, 4.223615, -2.290138, -8.31401, 4.405718, -0.4613627, -4.50291, 1.008383,
+        -1.106302, -0.1697123, 2.087196, 8.238929, -2.992416, -1.821776, 0.3434859, 0.7755179, 0.534119,
+        // r, albedo 1, turbidity 4
+        -1.17111, -0.2106304, -1.614361, 2.378103, -0.1625969, 0.8504483, 0.1059312, 4.046256, 0.6618227,
+        -1.20048, -0.2235733, 1.01439, -1.174074, -0.444018, 0.2262406, 0.1665868, 5.461829, 0.567631,
+        -1.223587, -0.3502622, 1.699106, 0.6724266, 1.268567, 2.135102, 0.0008039374, -5.221111, 0.944569,
+        -0.9452673, 0.1468459, -1.335034, 4.346628, -12.85652, -1.807046, 0.8175243, 9.301065, 0.3656798,
+        -1.134681, -0.3310951, 3.571244, -2.208948, 6.04158, 3.107577, -0.3112127, -4.186351, 0.9188333,
+        -1.083237, -0.1831394, 2.062654, 1.385424, -5.00495, -1.332669, 0.3627352, 0.332315, 0.6191181,
+        // r, albedo 1, turbidity 5
+        -1.211527, -0.2590617, -0.1660874, 0.3627905, -0.1039258, 0.4697924, 0.1671653, 3.507497, 0.6022506,
+        -1.433017, -0.4733592, 0.1724445, 0.9953236, -1.874457, 0.4432099, 0.0171581, 2.339272, 0.644147,
+        -1.08492, -0.1587903, 0.8999585, -2.537516, 5.877859, 2.014554, 0.09689141, 0.3177242, 0.9030399,
+        -1.008242, 0.00279303, -0.3507469, 10.283, -20.80454, -2.781026, 0.899509, 3.366951, 0.3473867,
+        -1.103151, -0.2799598, 2.525791, -4.255704, 9.903388, 3.722668, -0.3603941, -1.303292, 0.9369454,
+        -1.102235, -0.2025061, 2.08566, 1.686787, -5.010957, -1.656458, 0.4584029, -0.2751759, 0.6184162,
+        // r, albedo 1, turbidity 6
+        -1.25613, -0.3104904, 0.163935, 0.1315502, -0.7297583, 0.477848, 0.1259265, 3.012108, 0.6202728,
+        -1.620114, -0.655267, -0.2877157, 1.094371, 0.2818914, 0.369683, 0.09428521, 1.450951, 0.5681308,
+        -0.9686204, -0.03755647, 1.46998, -3.103414, 2.856583, 1.883209, -0.05746099, 1.286383, 1.001751,
+        -1.089377, -0.1023062, -1.498891, 10.66455, -17.20184, -2.759314, 1.061258, 2.910211, 0.2624701,
+        -1.044681, -0.2156857, 3.230136, -0.5863862, 6.09664, 3.550019, -0.4255773, -1.500033, 0.9687696,
+        -1.133658, -0.2505101, 1.71784, 0.008480428, -5.011789, -1.740989, 0.498343, -0.2081829, 0.6088641,
+        // r, albedo 1, turbidity 7
+        -1.335366, -0.3863319, -0.5279971, 0.3638324, 0.3230699, 0.08339707, 0.2483293, 2.678646, 0.4998346,
+        -2.004511, -0.9957121, 1.250807, 0.01625025, -0.3410754, 0.7858244, -0.09506757, 0.02651876, 0.5788643,
+        -0.8714157, 0.1192051, -0.8486879, -0.3702497, 1.818277, 1.103427, 0.2454866, 3.841575, 0.984735,
+        -1.042618, -0.2285793, 0.3620175, 2.983368, -9.776844, -1.971587, 0.6691674, -0.7901947, 0.32132,
+        -1.099112, -0.1869868, 2.044065, 2.062964, 1.265668, 2.71013, -0.1099443, 0.2179353, 0.9024108,
+        -1.106985, -0.2396881, 1.809807, 8.523319, -5.011788, -1.590086, 0.3248449, -0.1003187, 0.6550606,
+        // r, albedo 1, turbidity 8
+        -1.421285, -0.4767024, -0.3885004, 0.827459, -0.3644229, 0.6999513, 0.0519671, 2.578431, 0.624631,
+        -2.611217, -1.398846, 0.4527425, -0.5932142, 0.2224617, -0.5593581, 0.3389633, -0.7767112, 0.06536004,
+        -0.9881543, 0.04684782, -0.8616613, 0.8799807, 4.00313, 1.739543, -0.08098378, 5.524802, 1.499673,
+        -0.7544759, -0.2314808, 0.812577, -0.7724135, -9.577645, -1.629433, 0.6790832, -4.193895, -0.02526624,
+        -1.273719, -0.218703, 1.401798, 5.231832, 0.7405093, 1.775166, -0.07269476, 1.996087, 1.05745,
+        -1.046864, -0.2247559, 1.679449, 11.40057, -4.948829, -1.182664, 0.3241038, -0.2470012, 0.61159,
+        // r, albedo 1, turbidity 9
+        -1.514607, -0.598543, -0.187761, 1.75693, -1.314206, 0.611581, -5.97046e-6, 2.412975, 0.8124304,
+        -2.308414, -1.083797, -0.1179959, -1.728246, 0.7784742, 0.5494505, 0.006203168, 0.9326251, -0.1419518,
+        -3.230837, -1.43867, -0.9868286, 2.974393, 1.949339, -0.6337857, 0.8160271, 3.278606, 1.354373,
+        0.5149378, 0.2754789, 1.040965, -4.501186, -3.399057, 0.9661861, -0.4736173, -4.037574, 0.2794847,
+        -1.62187, -0.3192763, 0.8786242, 9.785565, -2.727652, 0.01903691, 0.5521261, 2.138764, 0.8419871,
+        -0.9951701, -0.2550607, 1.498952, -0.002737197, -3.101832, -0.5921329, 0.2864422, -0.4405218, 0.663141,
+        // r, albedo 1, turbidity 10
+        -1.902954, -0.9056918, -0.206957, 1.191499, -1.092577, 0.5849556, -9.649602e-6, 2.048407, 0.9001527,
+        -1.271627, -0.7193923, -0.01136606, -0.1167951, 0.003286175, -0.05262827, -0.02473874, 1.716125, -0.2187133,
+        -7.647175, -3.114129, -1.490128, -0.5266488, 3.06309, 1.474262, 0.5481458, 2.052174, 1.353089,
+        2.191403, 0.342112, 1.44651, 2.170943, -7.768187, -1.471207, -0.1456708, -1.753574, 0.2310576,
+        -1.932296, -0.3814739, 0.6245422, 6.748294, -0.3060171, 1.067747, 0.2500671, -0.1252596, 0.8614611,
+        -0.9471101, -0.405264, 1.300174, -0.003951536, -1.908284, -0.5385721, 0.2133578, -0.6250292, 0.6658012
+    ],
+    [
+        // g, albedo 0, turbidity 1
+        -1.14053, -0.1982747, -7.51273, 8.403899, -0.05699038, 0.9015907, 0.03392161, 4.772522, 0.5111184,
+        -1.165117, -0.1852955, 2.963684, -2.262274, -0.1571683, 0.6339974, 0.04977879, 7.243307, 0.4220053,
+        -1.169936, -0.3357429, 1.911291, -0.2391074, -0.4791643, 1.446113, -0.09178108, -4.700239, 0.8096219,
+        -1.060246, -0.1051633, 0.5013829, 2.832309, -0.3707855, 1.523131, 0.09163749, 5.604183, 0.7208566,
+        -1.089753, -0.2382167, 2.360312, -5.902562, -8.799894, 1.377692, -0.06131633, -1.415472, 0.6124057,
+        -1.075481, -0.1242391, 1.425781, 8.810319, -2.922646, 1.48652, 0.0327058, 3.889783, 0.4999482,
+        // g, albedo 0, turbidity 2
+        -1.149342, -0.2076337, -7.446587, 8.014559, -0.04866227, 0.8203043, 0.06386483, 4.894198, 0.5452051,
+        -1.120531, -0.1513311, 2.735504, -2.417591, -0.1361114, 0.4296342, 0.09427488, 8.171403, 0.4102448,
+        -1.226964, -0.3516378, 1.308298, -0.05097487, -0.4846783, 1.654619, -0.113494, -3.347854, 1.131147,
+        -0.9664377, 0.02767589, 0.1658235, 2.407439, -0.1300304, 0.9170958, 0.2742895, 6.642633, 0.2550064,
+        -1.153358, -0.3126223, 2.078934, -5.857733, -8.659848, 1.758505, -0.09616094, -1.230863, 0.9663832,
+        -1.05385, -0.1330743, 1.481738, 10.49485, -3.528854, 0.9142363, 0.124488, 2.644615, 0.5001048,
+        // g, albedo 0, turbidity 3
+        -1.173687, -0.2360362, -3.741454, 4.088507, -0.07528205, 0.6645237, 0.07718265, 4.65122, 0.5586318,
+        -1.213757, -0.2589561, 0.7132551, -0.4259327, -0.1980821, 0.3627815, 0.0466656, 5.807984, 0.5847377,
+        -1.108794, -0.225987, 1.574179, -0.3753731, -0.5984743, 1.659414, -0.01681021, 0.6785219, 0.8647325,
+        -1.060896, -0.0134669, -0.7529656, 1.711319, -0.9792435, 0.2022433, 0.3826487, 5.725157, 0.5290714,
+        -1.085145, -0.2840715, 2.088029, -4.935097, -9.056542, 1.976149, -0.03912485, -0.8636064, 0.7452125,
+        -1.077983, -0.1416633, 1.100848, 10.15875, -2.943712, 0.5255135, 0.2164224, 2.941143, 0.6699937,
+        // g, albedo 0, turbidity 4
+        -1.223293, -0.2867444, -1.624136, 1.668299, -0.09537589, 0.5015947, 0.1130741, 4.244812, 0.5082152,
+        -1.325342, -0.4280991, 0.470549, 0.06926592, -0.4572587, 0.5344144, -0.02554192, 3.093939, 0.6639401,
+        -1.113581, -0.1192133, 0.4011536, 0.7011889, 0.2052842, 0.9880724, 0.01807533, 4.69016, 0.857624,
+        -1.016063, -0.1038138, -0.2280391, 0.7898918, -11.27333, 0.2074545, 0.5388182, 1.364263, 0.4660455,
+        -1.099582, -0.2228607, 1.332648, 5.135188, 1.653152, 1.41702, -0.1087532, 1.809275, 0.8080874,
+        -1.064357, -0.1520775, 0.8207368, -0.001323565, -5.009523, 0.3946298, 0.4337902, 2.593198, 0.6719172,
+        // g, albedo 0, turbidity 5
+        -1.278702, -0.3512866, -0.4511055, 0.389576, -0.2429672, 0.4270577, 0.1135348, 3.71913, 0.4998867,
+        -1.580069, -0.7095475, -0.3198904, 1.715748, -1.185915, 0.4523161, -0.01026159, 0.7927188, 0.553835,
+        -0.9474023, 0.1173703, 0.4881381, -2.618684, 3.251661, 1.213931, -0.01736274, 8.000768, 1.025998,
+        -1.129091, -0.3287694, -0.3524077, 3.352892, -14.16073, -0.8485617, 0.6560766, -2.820937, 0.3111303,
+        -1.030884, -0.1137581, 1.109855, 8.082276, 1.519214, 2.112433, -0.1592299, 3.675905, 0.8703367,
+        -1.075192, -0.1627166, 0.351491, 1.168164, -4.255822, -0.6015348, 0.6265776, 2.884818, 0.6548384,
+        // g, albedo 0, turbidity 6
+        -1.316017, -0.3889652, -0.5030854, 0.4488704, -0.31868, 0.4570763, 0.08909201, 3.659274, 0.5011746,
+        -1.731876, -0.8493806, 0.1194871, 2.002781, -2.006547, 0.4872233, -0.02854606, 0.2662137, 0.4611629,
+        -0.927368, 0.1380954, -0.3302179, -3.553265, 4.633345, 0.9696729, 0.08799775, 8.291129, 1.094451,
+        -1.099377, -0.3325392, 0.2501063, 2.613712, -13.28142, -0.5579527, 0.4992081, -3.504402, 0.3022924,
+        -1.04842, -0.1227773, 0.5845373, 11.05869, 0.03813151, 1.330409, 0.01978131, 3.95943, 0.8396439,
+        -1.063233, -0.1560639, 0.2840033, 0.8751565, -3.41182, -0.1436564, 0.584658, 2.899292, 0.6799095,
+        // g, albedo 0, turbidity 7
+        -1.376715, -0.4541567, -1.445491, 1.569898, -0.1390627, 0.555827, 0.04109877, 3.349451, 0.5516123,
+        -1.953391, -1.035869, 1.690563, -0.196469, -0.7787096, 0.5799605, 0.02945626, 0.04217906, 0.2451373,
+        -1.012422, 0.07136451, -1.862534, -0.7228653, 0.1947997, 0.2091805, 0.06399233, 7.928994, 1.290733,
+        -0.9706708, -0.288095, 1.107797, -2.731734, -8.445995, 0.4296774, 0.5117648, -3.824277, 0.1761207,
+        -1.110611, -0.1789409, 0.2108488, 20.7143, -1.763174, 0.09554695, -0.02943103, 3.422079, 0.8815496,
+        -1.048334, -0.1614087, 0.2475184, 0.02146938, -2.983901, 0.2538224, 0.560137, 2.461925, 0.6777394,
+        // g, albedo 0, turbidity 8
+        -1.393719, -0.5002724, -2.40894, 2.680983, -0.1362825, 0.7395067, -3.300343e-6, 3.260889, 0.8132057,
+        -2.128663, -1.151182, 2.923026, -1.931838, -0.442617, 0.2309983, -0.00548589, 0.3279529, -0.2229467,
+        -1.618022, -0.376649, -3.163544, 1.611608, -0.3967476, 0.393368, 0.3006742, 6.835177, 1.613765,
+        -0.5669064, -0.1481749, 2.071817, -8.157422, -5.988088, 0.2387202, 0.1447191, -4.296385, 0.05011258,
+        -1.241724, -0.2519348, -0.1908609, 29.52235, -3.33366, -0.01837651, 0.1022249, 2.92932, 0.8867262,
+        -1.02167, -0.1667327, 0.1789771, -0.002178108, -2.641572, -0.05641484, 0.5303758, 2.138196, 0.678035,
+        // g, albedo 0, turbidity 9
+        -1.669332, -0.7588708, -2.993557, 3.17876, -0.08066442, 0.6544672, -8.08988e-6, 2.628924, 0.9001272,
+        -1.755806, -0.8735348, 3.258881, -2.504785, -0.3300791, 0.1180565, -0.009315982, 1.785154, -0.3205824,
+        -3.720277, -1.73335, -3.332272, 1.515869, 0.1734218, 0.8011956, 0.199544, 3.817666, 1.638502,
+        0.4724641, 0.3209828, 2.051443, -5.105574, -6.509139, -0.4232041, 0.2598931, -2.151756, -0.00349391,
+        -1.5256, -0.4897606, -0.09891121, 23.46818, -2.278152, 0.1681219, -0.04469389, 1.051, 0.9294666,
+        -0.9908649, -0.2008182, 0.1605143, -0.002463113, -2.477349, -0.1218647, 0.4750121, 1.460813, 0.6661364,
+        // g, albedo 0, turbidity 10
+        -2.122119, -1.125475, -3.066599, 3.145078, -0.05411593, 0.5133628, -7.823408e-6, 2.268448, 0.9001416,
+        -1.528158, -0.9370249, 2.567559, -1.591439, -0.363446, 0.1763256, 0.001119624, 1.811848, -0.2637929,
+        -6.524387, -2.673507, -2.940472, -0.6025609, 0.7852067, 1.073499, -0.03540435, 3.517416, 1.490466,
+        0.8886026, -0.09681828, 1.430554, 4.993717, -6.071355, -0.6053986, 0.5092997, -1.27301, 0.07491329,
+        -1.481997, -0.5897282, 0.2659264, 1.267239, -0.5741291, 0.05983011, -0.2217312, -0.3016452, 0.926083,
+        -1.010943, -0.2075134, 0.05066749, 14.70708, -3.780501, 0.07253223, 0.4045458, 1.320164, 0.6559925,
+        // g, albedo 1, turbidity 1
+        -1.129907, -0.1884011, -8.04767, 9.035776, -0.05539419, 0.8823349, 0.03197135, 4.839388, 0.5042822,
+        -1.133821, -0.1510781, 3.362822, -2.453381, -0.1463925, 0.4728708, 0.0595814, 7.6363, 0.4805162,
+        -1.176518, -0.3549902, 1.729044, -0.2160966, -0.5075865, 1.675584, -0.08906902, -5.386842, 0.5452218,
+        -1.043563, -0.07520975, 0.8750644, 2.510518, 0.007584882, 0.936125, 0.07889083, 6.066644, 0.5813108,
+        -1.081304, -0.2222253, 2.517638, -4.45382, -8.663691, 0.8662558, -0.04802657, -0.8965449, 0.4886656,
+        -1.083774, -0.1375469, 1.685818, 5.63112, -3.100752, 0.4045941, 0.02346895, 3.390321, 0.5008309,
+        // g, albedo 1, turbidity 2
+        -1.143158, -0.2058334, -9.660198, 10.62394, -0.04434119, 0.8607615, 0.03177325, 4.416481, 0.5918162,
+        -1.146773, -0.1727385, 4.626048, -4.684602, -0.08307137, 0.1619616, 0.1484866, 7.572868, 0.2681126,
+        -1.151324, -0.3099303, 0.4125596, 2.340752, -0.4214444, 1.987375, -0.191341, -3.845978, 1.337311,
+        -1.034258, -0.007778759, 0.7050094, -0.8036369, 0.313857, 0.2469452, 0.355997, 7.485917, 0.04790329,
+        -1.096568, -0.2673169, 2.575654, -0.8057121, -8.884928, 1.41617, -0.2091315, -1.543494, 1.065445,
+        -1.083304, -0.1528265, 1.697727, 2.503702, -2.885296, -0.12985, 0.154887, 2.479652, 0.5066496,
+        // g, albedo 1, turbidity 3
+        -1.165736, -0.2329945, -5.967964, 6.705959, -0.05931355, 0.7485638, 0.03913878, 4.221591, 0.6183926,
+        -1.212422, -0.254591, 2.418626, -2.266104, -0.1102014, 0.01363887, 0.1055411, 5.648062, 0.4557412,
+        -1.070436, -0.2163341, 0.7098718, 0.7843075, -0.432393, 2.109823, -0.095897, -0.1985193, 1.060428,
+        -1.104879, -0.03013622, 0.02976276, 1.069707, 0.141, -0.488002, 0.4452288, 6.41859, 0.3195986,
+        -1.048969, -0.2655317, 2.689426, -3.941038, -9.506461, 1.837119, -0.1892124, -1.562146, 0.9043414,
+        -1.106145, -0.1601642, 1.544544, 7.388492, -2.9246, -0.4328453, 0.1763161, 2.523111, 0.5851902,
+        // g, albedo 1, turbidity 4
+        -1.203666, -0.2776587, -2.084286, 2.45084, -0.08746613, 0.5258507, 0.07983316, 3.860055, 0.5486167,
+        -1.340448, -0.423059, 0.3462849, 0.4707607, -0.2512626, 0.1530746, 0.02724218, 3.035216, 0.5876133,
+        -1.014554, -0.116879, 0.9477794, -1.061218, -0.419673, 2.058832, -0.05989624, 3.058168, 0.9763861,
+        -1.137388, -0.0985403, -0.2984893, 3.64782, -0.6585571, -1.47918, 0.6102932, 3.265914, 0.3480333,
+        -1.021816, -0.2344957, 2.463671, -7.240685, -8.862697, 2.514058, -0.2122768, -0.03313968, 0.9028136,
+        -1.126581, -0.1874347, 1.454154, 10.34398, -3.237393, -0.8654927, 0.2457248, 1.845769, 0.6002482,
+        // g, albedo 1, turbidity 5
+        -1.263727, -0.3439354, -0.1786388, 0.3980166, -0.3349517, 0.3825166, 0.1029225, 3.331096, 0.4998955,
+        -1.53001, -0.6879698, 0.2380415, 1.608216, -1.682679, 0.354636, -0.00391522, 0.4517655, 0.5128605,
+        -0.9685659, 0.09480403, 0.06076844, -3.217561, 4.568074, 1.069299, 0.02083638, 7.301088, 1.072165,
+        -1.113925, -0.3112382, 0.3954133, 5.105907, -14.56866, -0.4917378, 0.5289909, -2.678374, 0.3014709,
+        -1.046864, -0.1215754, 1.778308, 4.661489, 0.2565583, 1.35368, -0.1175767, 3.415972, 0.8457746,
+        -1.10448, -0.1940913, 1.343668, -0.001759206, -5.009204, -0.4186951, 0.312571, 1.628183, 0.6720408,
+        // g, albedo 1, turbidity 6
+        -1.286902, -0.3781238, -0.08977253, 0.3545393, -0.4866515, 0.3843664, 0.08281675, 3.122231, 0.5046991,
+        -1.712597, -0.8549112, 0.4809286, 1.515398, -2.212211, 0.2539029, 0.02335997, -0.06089466, 0.4268444,
+        -0.8807283, 0.1646097, -0.4437898, -3.188247, 5.984417, 1.334779, -0.04026975, 7.546431, 1.175751,
+        -1.147253, -0.3538199, 0.6101836, 4.43778, -15.59813, -1.103222, 0.6242039, -3.091472, 0.217429,
+        -1.03823, -0.1213475, 1.547505, 5.893176, 1.368738, 1.663127, -0.137713, 3.185279, 0.8736453,
+        -1.101026, -0.1874907, 1.272667, 3.596524, -5.007243, -0.6352483, 0.3048985, 1.931613, 0.6788844,
+        // g, albedo 1, turbidity 7
+        -1.342753, -0.4384971, -1.213491, 1.621399, -0.1551441, 0.5614218, 0.02591739, 2.958967, 0.5782132,
+        -1.937684, -1.066019, 1.913336, -0.7347719, -0.5916167, 0.158759, 0.1092568, -0.6275002, 0.1599071,
+        -0.9302391, 0.1486187, -1.603835, 0.1783713, 1.100461, 1.174181, -0.1602361, 7.868331, 1.468971,
+        -1.053631, -0.372705, 1.114117, -0.9603286, -10.62469, -1.16214, 0.7952797, -4.478765, -0.04440862,
+        -1.083629, -0.1261405, 1.229344, 11.27825, 0.131901, 1.624729, -0.2825898, 3.661082, 1.036911,
+        -1.09395, -0.2067455, 1.258035, 7.548645, -4.598387, -0.8944932, 0.3292634, 1.311304, 0.6291871,
+        // g, albedo 1, turbidity 8
+        -1.385867, -0.5068139, -1.48649, 1.969049, -0.1698025, 0.6629167, -5.289365e-6, 2.760315, 0.8644368,
+        -2.107367, -1.175639, 2.313241, -1.001653, -0.4843139, 0.1124485, 3.901494e-5, -0.3502469, -0.320478,
+        -1.475244, -0.2833055, -2.085824, 1.192563, -0.76452, 0.8380081, 0.220358, 7.157885, 1.753702,
+        -0.6644372, -0.2549735, 1.600273, -8.589034, -6.144718, -0.7599731, 0.289837, -5.770923, -0.09656242,
+        -1.211687, -0.1653494, 0.83934, 27.92988, -3.395461, 0.9933752, -0.03976877, 3.776659, 0.9546526,
+        -1.063757, -0.2037563, 1.117207, -0.001252806, -3.33233, -0.6971409, 0.3388719, 1.311398, 0.6635171,
+        // g, albedo 1, turbidity 9
+        -1.678889, -0.7992295, -2.421687, 2.871029, -0.07662842, 0.6046208, -7.598099e-6, 2.002314, 0.9001307,
+        -1.692144, -0.880425, 3.060895, -2.000009, -0.3183563, 0.08385862, -0.006326713, 1.206639, -0.3369967,
+        -3.676795, -1.719207, -2.534697, 1.005285, 0.1550407, 1.07291, 0.1318094, 3.717018, 1.689191,
+        0.5424542, 0.3263528, 1.551055, -3.841058, -6.598996, -1.201779, 0.3530669, -2.542945, -0.06482523,
+        -1.553849, -0.457686, 0.9324676, 19.50982, -2.344516, 1.12102, -0.1221537, 0.7285496, 0.9582816,
+        -1.02065, -0.2215797, 1.009774, -0.002056855, -2.740338, -0.8122355, 0.3328967, 0.8982766, 0.6594676,
+        // g, albedo 1, turbidity 10
+        -2.24736, -1.221267, -3.072346, 3.385139, -0.04387559, 0.5084887, -7.418833e-6, 1.750107, 0.9001401,
+        -1.248499, -0.8442718, 3.062611, -2.020314, -0.2815341, 0.05254745, 0.003345008, 1.433225, -0.2835911,
+        -7.004119, -2.927978, -2.649852, 0.7971894, 0.5466893, 1.442667, -0.06063912, 2.806194, 1.547429,
+        1.434882, 0.09114639, 1.170089, 0.03512808, -5.861915, -1.411843, 0.5400486, -0.7746522, 0.02386984,
+        -1.559053, -0.5502302, 1.200396, 13.47741, -2.344397, 0.8868907, -0.3292661, -1.362105, 0.9217826,
+        -1.044436, -0.2360719, 0.7054471, -0.002904518, -2.092829, -0.5119668, 0.4174861, 0.9687435, 0.6588427
+    ],
+    [
+        // b, albedo 0, turbidity 1
+        -1.372629, -0.4905585, -41.00789, 41.22169, -0.00738936, 0.4839359, 0.006474757, 3.471755, 0.5092936,
+        -1.523025, -0.6497084, 6.249857, -5.662543, -0.01908402, 0.551281, -2.181049e-5, 2.507663, 0.4339598,
+        -1.035567, -0.0747874, 0.922103, -2.140047, -0.02374146, 0.3795517, -0.01769134, 7.479831, 0.7729303,
+        -1.271086, -0.558819, 0.6908023, 2.096832, -0.2453967, 1.410648, 0.04475036, -4.719115, 0.5741186,
+        -0.9712598, -0.07033926, 0.9167274, -0.9502097, 0.3004684, 0.4547054, -0.05929017, 5.266196, 0.7204135,
+        -1.087457, -0.1888896, 0.8156686, 0.3101712, -2.155419, 1.422205, 0.09692261, 3.122404, 0.499943,
+        // b, albedo 0, turbidity 2
+        -1.42528, -0.5413508, -34.54883, 34.81142, -0.008686975, 0.4914268, -2.479243e-6, 3.239879, 0.6094201,
+        -1.688557, -0.8070865, 7.018459, -6.244574, -0.02149341, 0.3993971, 0.01252502, 1.630662, 0.109786,
+        -0.8664152, 0.07869125, -0.5236535, -1.21896, -0.02059093, 0.6684898, -0.05584112, 8.602299, 1.410496,
+        -1.319763, -0.5985323, 1.253918, 1.914706, -0.3216739, 0.9011213, 0.1324845, -5.252749, 0.06231252,
+        -0.9706008, -0.05914059, 0.569315, -1.175362, 0.5221644, 0.7518213, -0.08247655, 5.875635, 0.9850863,
+        -1.08533, -0.1956105, 0.8019605, 0.5338101, -3.423464, 1.110444, 0.1507923, 2.864942, 0.4999481,
+        // b, albedo 0, turbidity 3
+        -1.431967, -0.5478935, -32.86288, 33.05288, -0.008380797, 0.477205, -3.044274e-6, 3.289973, 0.5976303,
+        -1.801361, -0.9315889, 5.391756, -4.588592, -0.02040076, 0.4144684, 0.01814534, 1.051795, 0.1145651,
+        -0.7905357, 0.1451332, -0.1605661, -1.592174, 0.0004561348, 0.3380323, -0.07770275, 8.775384, 1.489512,
+        -1.308575, -0.5539232, 0.9184133, 2.011479, -0.3842472, 1.432274, 0.1637153, -4.408856, 0.05272957,
+        -0.9829872, -0.08183048, 0.4464556, -1.442716, 1.029641, -0.06991617, 0.008702356, 5.706417, 0.9116452,
+        -1.08713, -0.2038013, 0.7260801, 0.9164376, -5.006183, 1.511271, 0.1257134, 2.715439, 0.6201652,
+        // b, albedo 0, turbidity 4
+        -1.448662, -0.5799075, -28.33268, 28.58023, -0.009134061, 0.4404783, -2.709026e-6, 3.029357, 0.5540071,
+        -2.061772, -1.14519, 7.918478, -7.212525, -0.0202076, 0.2962715, 0.0468967, 0.8517209, 0.2334587,
+        -0.6413755, 0.1780425, -2.412919, 1.064484, -0.01949986, 0.6769741, -0.175276, 7.262714, 1.325869,
+        -1.304871, -0.3975581, 1.219002, 0.7285178, -0.2710105, 0.7779727, 0.3247139, -0.8818168, 0.1839517,
+        -1.001104, -0.1994801, 0.3676742, -1.409737, 0.2901555, 0.250694, 0.002468899, 3.398923, 0.8584645,
+        -1.111552, -0.2487204, 0.7410842, 1.703749, -5.007855, 1.057763, 0.1354511, 2.088715, 0.6600013,
+        // b, albedo 0, turbidity 5
+        -1.547227, -0.6679466, -18.61465, 18.84045, -0.0124221, 0.4157339, -2.432805e-6, 2.812423, 0.5446957,
+        -2.04389, -1.149081, 2.304118, -1.715757, -0.02433628, 0.2816836, 0.07185458, 1.06486, 0.2706789,
+        -0.904072, -0.08274472, -0.2555676, -0.6326215, -0.0277088, 0.6676024, -0.2513532, 5.903839, 1.241452,
+        -1.000013, -0.1010774, 0.3699166, 0.8774526, -0.3042007, 0.6951053, 0.4361813, 0.6793421, 0.2573892,
+        -1.171332, -0.3768188, 0.3701377, -1.470757, 0.5525942, 0.02991456, 0.01581823, 2.365233, 0.8214514,
+        -1.068667, -0.232633, 0.6725059, 2.243733, -4.61437, 1.033677, 0.1376291, 2.013334, 0.6865304,
+        // b, albedo 0, turbidity 6
+        -1.592991, -0.7246948, -25.98204, 26.2196, -0.008365176, 0.4207571, -2.742772e-6, 2.623735, 0.587319,
+        -2.271349, -1.280884, 6.308739, -5.75835, -0.01977049, 0.3671835, 0.06698038, 1.150597, 0.1759218,
+        -0.636862, -0.007436052, -2.230026, 1.640997, -0.01548497, 0.3145331, -0.2492644, 5.083843, 1.260215,
+        -1.177925, -0.09628114, 0.3051152, -0.03749544, -0.2713209, 1.164226, 0.4559969, 2.175429, 0.2874284,
+        -1.0785, -0.3801779, 0.4788906, -0.4795969, 0.5977621, -0.4488535, 0.03386874, 1.538143, 0.8062054,
+        -1.108028, -0.2596892, 0.5162202, 1.557081, -4.265039, 1.182535, 0.1563762, 2.095084, 0.6883383,
+        // b, albedo 0, turbidity 7
+        -1.668427, -0.7908511, -27.7969, 27.99746, -0.007186935, 0.3757766, -3.326858e-6, 2.563421, 0.5439687,
+        -2.156175, -1.220004, 3.585732, -3.235988, -0.01086239, 0.1846143, 0.1046017, 1.234427, 0.2842191,
+        -1.117051, -0.4101627, -0.846373, 0.7671472, -0.02226609, 0.8574943, -0.3434124, 4.475715, 1.154824,
+        -0.744484, 0.2312078, -0.5393724, 0.1574213, -0.1763914, 0.2751692, 0.55642, 2.217672, 0.3483932,
+        -1.273036, -0.5275562, 0.4902512, -0.04498436, 0.4339366, 0.2386682, 0.02380879, 1.413444, 0.7855923,
+        -1.084192, -0.2936753, 0.4719432, 1.384436, -3.257789, 0.6119543, 0.1681884, 1.650441, 0.6936631,
+        // b, albedo 0, turbidity 8
+        -1.84849, -0.951267, -30.05251, 30.24315, -0.005635304, 0.344778, -2.782999e-6, 2.309422, 0.5643559,
+        -2.300008, -1.252335, -1.218876, 1.49373, -0.0061071, 0.0797486, 0.1023449, 1.505934, 0.2360948,
+        -1.483705, -0.8547575, -0.7797146, 0.6447971, -0.02678052, 1.091263, -0.3344889, 3.830416, 1.189425,
+        -0.5348005, 0.3982733, -0.4071573, 0.3265569, -0.08658789, -0.2370892, 0.5369097, 1.478279, 0.3143303,
+        -1.320401, -0.6043247, 0.3019196, -0.07732911, 0.4768381, 0.6745764, 0.03694098, 1.158234, 0.8169056,
+        -1.10104, -0.3420019, 0.3775661, 1.769338, -2.990515, 0.1649529, 0.1970125, 1.453355, 0.6759757,
+        // b, albedo 0, turbidity 9
+        -2.251946, -1.229349, -32.71808, 32.83114, -0.004252027, 0.3372289, -3.001937e-6, 2.154046, 0.5842674,
+        -1.867834, -0.9531252, -12.29365, 12.69149, -0.006844772, 0.1185107, 0.07539587, 1.846381, 0.1899412,
+        -3.398629, -2.180862, 2.335213, -3.382823, -0.008613985, 0.8431602, -0.2393567, 3.11246, 1.218556,
+        0.5708381, 0.940603, -0.6890113, 2.746233, -0.05772068, 0.1096005, 0.3491978, 0.7281453, 0.3212049,
+        -1.705909, -0.8517224, 0.113116, -2.141434, 0.4274043, 0.33976, 0.178649, 0.9026101, 0.78828,
+        -1.012865, -0.3495551, 0.3369038, 3.724205, -3.089586, 0.1266964, 0.146179, 1.170199, 0.6931052,
+        // b, albedo 0, turbidity 10
+        -2.890318, -1.665573, -34.93756, 35.00369, -0.002984251, 0.2622419, -4.25936e-6, 1.947681, 0.6905752,
+        -1.956022, -1.0629, -19.19714, 19.75164, -0.008865396, 0.216554, 0.05475637, 1.761134, 0.003164249,
+        -5.612198, -3.101371, 4.098034, -6.144001, 0.009944958, 0.2905472, -0.170711, 3.199107, 1.33766,
+        0.8353756, 0.4855943, -1.243589, 5.147385, -0.07013963, 0.938041, 0.2335714, 0.1727744, 0.2802696,
+        -1.524329, -0.7388547, 0.3259025, -4.050634, 0.4058549, -0.2591384, 0.1898299, 0.3556071, 0.7884126,
+        -1.070371, -0.4207858, 0.1739862, 5.29341, -3.136757, 0.2323856, 0.1673706, 1.007227, 0.6844287,
+        // b, albedo 1, turbidity 1
+        -1.34172, -0.4834889, -46.33447, 46.82148, -0.006137296, 0.4599216, 0.007047323, 2.895798, 0.4999398,
+        -1.529104, -0.6498631, 15.34103, -14.50675, -0.01531439, 0.3280082, 0.01682926, 1.901587, 0.5013227,
+        -1.014776, -0.1454495, -4.071085, 2.954982, -0.02630348, 0.5681531, -0.03016505, 6.773854, 0.5003504,
+        -1.172413, -0.402632, 2.960428, 0.202071, -0.2004947, 0.9375572, 0.05998168, -4.945934, 0.4502898,
+        -0.9898161, -0.05772814, 0.4470024, -0.5786656, 0.1158168, 0.346804, -0.0504336, 6.867947, 0.8012363,
+        -1.085111, -0.1882675, 1.223748, 0.3565495, -3.688357, 0.5653723, 0.06727646, 2.69013, 0.49994,
+        // b, albedo 1, turbidity 2
+        -1.389119, -0.529025, -40.55774, 41.05972, -0.007062577, 0.456006, -1.736334e-6, 2.775512, 0.6671455,
+        -1.584641, -0.7200619, 12.48067, -11.56028, -0.01659568, 0.3050029, 0.01099895, 1.438927, -0.02138015,
+        -0.9826068, -0.08887254, -2.960031, 1.808816, -0.02478159, 0.6035733, -0.04868441, 7.347705, 1.584739,
+        -1.150423, -0.4073793, 2.412991, 0.487084, -0.2337902, 0.8295114, 0.1129914, -5.150045, -0.09016643,
+        -1.016933, -0.06311501, 0.5218937, -0.571643, 0.1250993, 0.3601524, -0.05497586, 7.060139, 1.018333,
+        -1.073151, -0.1845444, 1.155394, 0.3004486, -3.431711, 0.4657031, 0.09401223, 2.68862, 0.4999544,
+        // b, albedo 1, turbidity 3
+        -1.391257, -0.5365815, -42.55881, 42.99132, -0.005838466, 0.4229134, -2.760038e-6, 2.775531, 0.6234597,
+        -1.780062, -0.922888, 13.76172, -12.60946, -0.01507526, 0.3117435, 0.02205045, 0.6093731, 0.03463446,
+        -0.7388169, 0.127567, -3.999528, 2.223993, -0.01856853, 0.543931, -0.08834054, 8.037139, 1.645951,
+        -1.322387, -0.5320143, 2.659359, 1.086712, -0.2129712, 0.8704649, 0.1800315, -4.967241, -0.138372,
+        -0.9378288, -0.01599895, 0.3607555, -1.980561, 0.3791456, 0.1212268, -0.02845992, 6.825542, 1.059139,
+        -1.100832, -0.2172313, 1.211561, 2.002721, -5.010011, 0.5717583, 0.06777702, 2.160006, 0.5676392,
+        // b, albedo 1, turbidity 4
+        -1.409373, -0.5708751, -30.34974, 30.79809, -0.007280715, 0.3723304, -2.436279e-6, 2.577348, 0.5913377,
+        -1.954312, -1.11651, 5.399148, -4.299553, -0.01724739, 0.3742824, 0.04187077, 0.1044883, 0.1232727,
+        -0.6772215, 0.2001396, -0.3670523, -1.014628, -0.003497152, 0.4099858, -0.1584633, 7.7504, 1.514559,
+        -1.2916, -0.4977437, 0.9641914, 1.56242, -0.3227782, 0.9055427, 0.3046444, -3.385619, 0.009546291,
+        -0.9750857, -0.0877056, 0.9054256, -1.429236, 0.8974777, -0.1217961, -0.05194608, 4.909409, 0.9589153,
+        -1.088007, -0.1959301, 0.9745799, 1.260761, -5.008864, 0.7271248, 0.1096661, 2.717295, 0.6340731,
+        // b, albedo 1, turbidity 5
+        -1.45605, -0.6223072, -22.28088, 22.69604, -0.009340812, 0.4118308, -2.418083e-6, 2.442117, 0.5589638,
+        -2.176449, -1.302416, 2.222836, -1.22273, -0.01728051, 0.1323513, 0.07027731, 0.04835745, 0.2093351,
+        -0.5789641, 0.2215407, 0.2142291, -1.201725, -0.01185728, 0.8122982, -0.238042, 6.706841, 1.404146,
+        -1.307463, -0.4515174, 0.6447827, 1.223841, -0.2902391, 0.4986588, 0.4073652, -1.706696, 0.1060885,
+        -0.9698678, -0.1307094, 0.9389347, -1.522852, 0.7768797, -0.1368595, -0.03857426, 3.676935, 0.8980966,
+        -1.104349, -0.2380323, 1.047043, 1.865421, -5.011664, 0.7014954, 0.09622701, 1.89136, 0.6687354,
+        // b, albedo 1, turbidity 6
+        -1.502249, -0.6724523, -28.88092, 29.3036, -0.006685766, 0.3685464, -2.469442e-6, 2.310797, 0.5566754,
+        -2.217125, -1.364924, 4.048243, -3.111333, -0.01317747, 0.1921948, 0.08627702, 0.001981769, 0.2213689,
+        -0.6215757, 0.1687995, -0.5949131, -0.1551293, 0.0003356129, 0.6897657, -0.2855053, 6.271042, 1.363084,
+        -1.216317, -0.3489429, 0.7566226, 0.5409809, -0.2830843, 0.6191825, 0.4755163, -0.9131387, 0.1383909,
+        -1.030437, -0.2034064, 0.8335995, -1.050947, 0.8689093, -0.367231, -0.04056183, 3.111269, 0.8856842,
+        -1.078984, -0.2070549, 0.9683145, 1.497022, -5.007653, 0.7702541, 0.1285822, 2.225188, 0.6587911,
+        // b, albedo 1, turbidity 7
+        -1.559291, -0.7374039, -35.96311, 36.3447, -0.004667132, 0.3277964, -2.487945e-6, 2.215652, 0.5764681,
+        -2.356929, -1.444755, 6.244526, -5.540162, -0.00879451, 0.17921, 0.09578517, 0.3737676, 0.1922194,
+        -0.6589752, -0.0292691, -1.831779, 1.869962, -0.002030095, 0.7552089, -0.3168157, 4.632196, 1.294054,
+        -1.161046, -0.1472506, 0.6494138, -0.8327174, -0.2320724, 0.3391212, 0.5269637, 0.9376341, 0.2458573,
+        -1.034427, -0.3062504, 0.8975634, 0.3203531, 0.8565142, -0.1250162, -0.04094017, 1.861304, 0.8223468,
+        -1.109954, -0.2740277, 1.063811, 0.7077398, -4.695734, 0.5621696, 0.1248956, 1.297723, 0.678972,
+        // b, albedo 1, turbidity 8
+        -1.788293, -0.9368751, -43.8298, 44.24963, -0.00365253, 0.3094331, -2.810503e-6, 1.904402, 0.5861599,
+        -2.268206, -1.312676, 2.863082, -2.373727, -0.00514498, 0.1711072, 0.09316041, 0.9309598, 0.1791683,
+        -1.376966, -0.7418582, -1.349589, 1.563419, -0.003124219, 0.6967139, -0.3061887, 3.602731, 1.255669,
+        -0.601754, 0.2815928, 0.5424052, -0.688545, -0.1620001, 0.2980046, 0.4995571, 0.7371203, 0.2812466,
+        -1.278853, -0.5245326, 0.787052, 0.3125067, 0.7748105, -0.07788581, 0.003490956, 1.283748, 0.813019,
+        -1.05093, -0.2786331, 1.056344, 1.053002, -4.047789, 0.4432174, 0.1169077, 0.9532621, 0.6806764,
+        // b, albedo 1, turbidity 9
+        -2.084927, -1.203954, -48.81638, 49.2016, -0.002896045, 0.2882977, -3.073517e-6, 1.702211, 0.637418,
+        -2.328567, -1.238023, -1.891019, 2.45152, -0.005847581, 0.2084702, 0.0784813, 1.211048, 0.08095008,
+        -2.634632, -1.78946, -0.1370558, -0.3326435, 0.002783737, 0.5239451, -0.2548881, 2.896327, 1.324116,
+        0.06882616, 0.5997821, 0.1535398, 1.375209, -0.1267285, 0.4239743, 0.4013122, 0.1794675, 0.2395382,
+        -1.430918, -0.6439041, 0.832598, -1.705612, 0.7236426, -0.05567593, 0.06408718, 0.6836524, 0.8388887,
+        -1.037956, -0.3215402, 0.9457349, 3.178114, -4.152156, 0.2230992, 0.1156198, 0.7606223, 0.6656923,
+        // b, albedo 1, turbidity 10
+        -2.967314, -1.728778, -37.30988, 37.55578, -0.002588835, 0.2927966, -3.935038e-6, 1.592161, 0.6868694,
+        -2.123311, -1.175148, -13.14988, 13.86882, -0.007828537, 0.1852026, 0.05481038, 1.294309, 0.02428177,
+        -5.443597, -3.156344, 2.110838, -3.421556, 0.0118189, 0.1196951, -0.1742902, 2.404353, 1.272805,
+        1.029898, 0.5912521, -0.3983531, 3.286069, -0.09252065, 1.331381, 0.2560642, 0.8001754, 0.3624178,
+        -1.547574, -0.7881604, 1.020902, -2.897069, 0.521347, -0.9242315, 0.1185594, -1.150721, 0.7317211,
+        -0.9621043, -0.1991406, 0.6531287, 3.925839, -3.596904, 0.6317332, 0.1531334, 1.457846, 0.6966285
+    ]
+];
+
+// per channel: 2 albedos x 10 turbidities x 6 elevation control points
+pub const HOSEK_RGB_RADIANCES: [[f32; 120]; 3] = [
+    [
+        // r, albedo 0
+        1.962684, 1.159831, 4.450588, 5.079633, 4.437388, 4.324573,
+        1.946487, 1.287515, 3.703696, 8.782833, 3.440437, 5.160333,
+        1.88217, 1.335878, 2.648641, 13.58368, 3.105473, 5.907387,
+        1.738159, 1.624289, -0.008786695, 21.18253, 2.770255, 7.055672,
+        1.571896, 2.301786, -4.028545, 29.66806, 1.630876, 8.711031,
+        1.475048, 2.679086, -6.311315, 33.77896, 2.140975, 9.385283,
+        1.326174, 3.378759, -9.831444, 39.42061, 2.852702, 10.82542,
+        1.153344, 3.967771, -12.65181, 41.95016, 7.468239, 12.2135,
+        0.9746081, 4.051626, -12.98454, 37.54964, 17.49232, 14.20619,
+        0.8448016, 3.181809, -8.757338, 21.97962, 35.24033, 16.39549,
+        // r, albedo 1
+        2.029623, 1.364434, 4.201529, 5.415099, 9.825839, 10.63328,
+        2.023126, 1.494728, 3.420413, 9.072178, 9.205157, 11.86639,
+        1.956307, 1.648665, 2.039712, 14.30239, 9.039526, 13.30453,
+        1.825053, 1.985022, -0.8036307, 22.02493, 9.415361, 15.17659,
+        1.650367, 2.593201, -4.469328, 29.69817, 9.410977, 17.4485,
+        1.555202, 2.962925, -6.60817, 33.29887, 10.64559, 18.50816,
+        1.412478, 3.439403, -9.196616, 36.85077, 13.45341, 20.03128,
+        1.25299, 3.820805, -11.15338, 37.21593, 20.14916, 21.8232,
+        1.091952, 3.663027, -10.3133, 29.78985, 32.96835, 23.7545,
+        0.9501691, 2.664579, -5.545167, 12.81159, 51.54768, 25.74284
+    ],
+    [
+        // g, albedo 0
+        1.59033, 1.355401, 1.151412, 13.59116, 5.857714, 8.090833,
+        1.55254, 1.51004, 0.1276413, 16.04643, 5.912162, 8.350009,
+        1.470871, 1.880464, -1.865398, 20.30808, 5.471461, 9.109834,
+        1.356563, 2.373866, -4.653245, 25.70922, 5.686009, 10.0948,
+        1.244232, 2.851519, -7.130942, 29.93449, 6.38212, 11.14578,
+        1.173693, 3.120604, -8.491886, 31.87393, 7.290615, 11.80066,
+        1.091845, 3.368888, -9.722083, 32.68508, 10.32424, 12.36508,
+        0.9858985, 3.500541, -10.26328, 30.92956, 16.10881, 13.31222,
+        0.8864993, 3.172888, -8.68755, 23.62161, 26.21851, 14.74967,
+        0.7946973, 2.189355, -4.207953, 9.399091, 40.62849, 16.81753,
+        // g, albedo 1
+        1.711696, 1.657311, 0.9328021, 13.1788, 15.06751, 18.63556,
+        1.666968, 1.849993, -0.2088601, 15.86653, 14.8688, 19.40719,
+        1.584846, 2.170022, -2.019597, 19.70826, 14.90684, 20.45055,
+        1.469412, 2.524017, -4.197267, 23.65249, 16.64588, 21.34477,
+        1.369714, 2.843548, -6.059031, 26.34993, 18.81361, 22.32186,
+        1.310477, 2.984444, -6.831686, 26.8234, 21.23267, 22.59755,
+        1.222552, 3.176523, -7.731496, 26.7176, 24.84358, 23.36863,
+        1.115781, 3.130635, -7.581744, 23.36531, 31.71048, 24.13859,
+        1.013181, 2.699342, -5.602709, 15.00158, 42.17613, 25.15957,
+        0.8976323, 1.726948, -1.29612, 1.183675, 55.03215, 26.43066
+    ],
+    [
+        // b, albedo 0
+        0.9926518, 1.999494, -4.136109, 18.5627, 13.51028, 13.90238,
+        0.9634366, 2.119694, -4.614523, 19.19701, 13.76644, 14.18731,
+        0.9446537, 2.17161, -4.915556, 19.1824, 15.37135, 14.0053,
+        0.9073074, 2.330536, -5.577596, 19.61615, 16.88365, 14.46955,
+        0.8739124, 2.388682, -5.842995, 19.23265, 18.87735, 14.85698,
+        0.8563688, 2.391534, -5.769133, 18.28709, 20.97209, 14.69587,
+        0.8270533, 2.34279, -5.558071, 16.84993, 23.56498, 15.05975,
+        0.7908339, 2.190341, -4.852571, 13.74862, 28.06846, 15.48444,
+        0.7403619, 1.783998, -2.983854, 7.622563, 35.0761, 16.15805,
+        0.6840111, 1.154457, -0.239383, -0.7896893, 42.82765, 17.79469,
+        // b, albedo 1
+        1.1683, 1.860993, -2.129074, 12.51952, 30.32499, 29.38716,
+        1.150338, 1.918813, -2.413527, 12.74862, 30.87134, 29.51432,
+        1.114719, 1.964689, -2.625423, 12.47837, 32.37949, 29.43596,
+        1.077948, 2.006292, -2.846934, 11.90195, 34.59293, 29.37492,
+        1.035143, 1.986681, -2.752584, 10.60972, 37.22185, 29.18594,
+        1.015992, 1.992054, -2.812626, 10.01416, 38.473, 29.24624,
+        0.9756887, 1.939897, -2.533281, 8.319176, 40.83907, 29.25586,
+        0.9264164, 1.716454, -1.597044, 4.739725, 45.07683, 28.78915,
+        0.8595191, 1.346034, -0.02801895, -0.6582906, 50.17523, 28.52953,
+        0.7754116, 0.7709245, 2.200201, -7.487661, 54.36622, 28.93432
+    ]
+];
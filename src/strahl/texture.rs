@@ -1,8 +1,10 @@
 use super::hit::*;
 use super::vec::*;
+use super::procedural::*;
+use super::graph::ShaderGraph;
 
 extern crate image;
-use image::{GenericImageView, Pixel};
+use image::GenericImageView;
 use std::path::Path;
 
 pub trait Sample
@@ -21,14 +23,47 @@ pub enum DynamicTextureType
 {
     Linear,
     sRGB, // TODO: add gamma?
-    RGBe
+    RGBe // rgbe bytes stored in an 8 bit rgba image, .hdr files are decoded by extension
 }
 
+// how lookups outside [0, 1) are folded back
+#[derive(Copy, Clone, PartialEq)]
+pub enum WrapMode
+{
+    Repeat,
+    Clamp,
+    Mirror,
+    LatLong // repeat along u, clamp along v, for equirectangular maps
+}
+
+// filter within a mip level, levels are blended linearly when the hit has a footprint
+#[derive(Copy, Clone, PartialEq)]
+pub enum FilterMode
+{
+    Nearest,
+    Bilinear,
+    Bicubic
+}
+
+// linear texels in square tiles, neighbouring lookups stay within a few cache lines
+#[derive(Clone)]
+pub struct TiledImage
+{
+    width: usize,
+    height: usize,
+    tiles_x: usize,
+    texels: std::vec::Vec<Vec4>
+}
+
+// decoded to linear at load, lookups don't touch the source image anymore
 #[derive(Clone)]
 pub struct DynamicTexture
 {
-    pub img: image::DynamicImage,
-    pub load_type: DynamicTextureType
+    pub load_type: DynamicTextureType,
+    pub filter: FilterMode,
+    pub wrap: WrapMode,
+    pub mipmap: bool,
+    levels: std::vec::Vec<TiledImage> // finest first
 }
 
 #[derive(Clone)]
@@ -36,6 +71,18 @@ pub enum Texture
 {
     ConstantTexture {tex: ConstantTexture},
     DynamicTexture {tex: DynamicTexture},
+    Checker {tex: CheckerTexture},
+    Noise {tex: NoiseTexture},
+    Marble {tex: MarbleTexture},
+    Wood {tex: WoodTexture},
+    Voronoi {tex: VoronoiTexture},
+    Gradient {tex: GradientTexture},
+    Scale {tex: ScaleTexture},
+    Mix {tex: MixTexture},
+    Multiply {tex: MultiplyTexture},
+    UvTransform {tex: UvTransformTexture},
+    Triplanar {tex: TriplanarTexture},
+    Graph {tex: ShaderGraph}
 }
 
 impl Sample for Texture
@@ -45,7 +92,19 @@ impl Sample for Texture
         let pixel = match self
         {
             Texture::ConstantTexture {tex} => {tex.sample(&hit)},
-            Texture::DynamicTexture {tex} => {tex.sample(&hit)}
+            Texture::DynamicTexture {tex} => {tex.sample(&hit)},
+            Texture::Checker {tex} => {tex.sample(&hit)},
+            Texture::Noise {tex} => {tex.sample(&hit)},
+            Texture::Marble {tex} => {tex.sample(&hit)},
+            Texture::Wood {tex} => {tex.sample(&hit)},
+            Texture::Voronoi {tex} => {tex.sample(&hit)},
+            Texture::Gradient {tex} => {tex.sample(&hit)},
+            Texture::Scale {tex} => {tex.sample(&hit)},
+            Texture::Mix {tex} => {tex.sample(&hit)},
+            Texture::Multiply {tex} => {tex.sample(&hit)},
+            Texture::UvTransform {tex} => {tex.sample(&hit)},
+            Texture::Triplanar {tex} => {tex.sample(&hit)},
+            Texture::Graph {tex} => {tex.sample(&hit)}
         };
 
         pixel
@@ -68,6 +127,11 @@ impl ConstantTexture
         ConstantTexture{color: Vec4::from3(r,g,b)}
     }
 
+    pub fn scalar(x: f32) -> ConstantTexture
+    {
+        ConstantTexture{color: Vec4::from(x)}
+    }
+
     pub fn texture(&self) -> Texture
     {
         Texture::ConstantTexture{tex: *self}
@@ -91,54 +155,446 @@ impl DynamicTexture
     pub fn new<P>(path: P, _type: DynamicTextureType) -> DynamicTexture
     where P: AsRef<Path>
     {
-        let img_file = match image::open(path)
-        {
-            Ok(file) => file,
-            Err(e) => {
-                eprintln!("{}", e);
-                image::DynamicImage::new_rgb8(1, 1)
-            } 
-        };
+        DynamicTexture::with_sampling(path, _type, FilterMode::Bilinear, WrapMode::Repeat)
+    }
 
-        DynamicTexture{img: img_file, load_type: _type}
-    }    
+    pub fn with_sampling<P>(path: P, _type: DynamicTextureType, _filter: FilterMode, _wrap: WrapMode) -> DynamicTexture
+    where P: AsRef<Path>
+    {
+        let (width, height, texels) = load_linear(path.as_ref(), _type);
+        DynamicTexture::from_texels(width, height, texels, _type, _filter, _wrap)
+    }
+
+    pub fn from_texels(_width: usize, _height: usize, _texels: std::vec::Vec<Vec4>, _type: DynamicTextureType, _filter: FilterMode, _wrap: WrapMode) -> DynamicTexture
+    {
+        let base = TiledImage::new(_width, _height, &_texels);
+        DynamicTexture{levels: DynamicTexture::build_mips(base), load_type: _type, filter: _filter, wrap: _wrap, mipmap: true}
+    }
 
     pub fn texture<P>(path: P, _type: DynamicTextureType) -> Texture
     where P: AsRef<Path>
     {
-        let img_file = match image::open(path)
+        Texture::DynamicTexture{tex: DynamicTexture::new(path, _type)}
+    }
+
+    // source texels and weights of coarse texel x when halving n texels, odd sizes use three taps so no texel is dropped
+    fn mip_taps(n: usize, x: usize) -> [(usize, f32); 3]
+    {
+        if n == 1
         {
-            Ok(file) => file,
-            Err(e) => {
-                eprintln!("{}", e);
-                image::DynamicImage::new_rgb8(1, 1)
-            } 
-        };
+            [(0, 1.0), (0, 0.0), (0, 0.0)]
+        }
+        else if n % 2 == 0
+        {
+            [(2 * x, 0.5), (2 * x + 1, 0.5), (2 * x, 0.0)]
+        }
+        else
+        {
+            let c = (n / 2) as f32;
+            let norm = 1.0 / n as f32;
+            [(2 * x, (c - x as f32) * norm), (2 * x + 1, c * norm), (2 * x + 2, (x as f32 + 1.0) * norm)]
+        }
+    }
+
+    // box filtered halving down to 1x1, in linear space
+    fn build_mips(base: TiledImage) -> std::vec::Vec<TiledImage>
+    {
+        let mut levels = vec![base];
 
-        Texture::DynamicTexture{tex: DynamicTexture{img: img_file, load_type: _type}}
+        loop
+        {
+            let (w, h) = levels.last().map(|l| (l.width, l.height)).unwrap();
+            if w <= 1 && h <= 1
+            {
+                break;
+            }
+
+            let fine = levels.last().unwrap();
+            let (cw, ch) = ((w / 2).max(1), (h / 2).max(1));
+            let mut texels = std::vec::Vec::with_capacity(cw * ch);
+            for y in 0..ch
+            {
+                let ty = DynamicTexture::mip_taps(h, y);
+                for x in 0..cw
+                {
+                    let tx = DynamicTexture::mip_taps(w, x);
+                    let mut sum = Vec4::zero();
+                    for (yi, wy) in ty.iter()
+                    {
+                        for (xi, wx) in tx.iter()
+                        {
+                            if wx * wy > 0.0 { sum += fine.get(*xi, *yi) * (wx * wy); }
+                        }
+                    }
+                    texels.push(sum);
+                }
+            }
+
+            let coarse = TiledImage::new(cw, ch, &texels);
+            levels.push(coarse);
+        }
+
+        levels
+    }
+
+    pub fn width(&self) -> usize
+    {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize
+    {
+        self.levels[0].height
+    }
+
+    pub fn level_count(&self) -> usize
+    {
+        self.levels.len()
+    }
+
+    // filtered lookup of the finest level, for callers without a hit
+    pub fn lookup(&self, u: f32, v: f32) -> Vec4
+    {
+        self.sample_level(0, u, v)
+    }
+
+    fn wrap_coord(&self, i: i32, n: i32, vertical: bool) -> i32
+    {
+        match self.wrap
+        {
+            WrapMode::Repeat => {i.rem_euclid(n)},
+            WrapMode::LatLong => {if vertical { i.max(0).min(n - 1) } else { i.rem_euclid(n) }},
+            WrapMode::Clamp => {i.max(0).min(n - 1)},
+            WrapMode::Mirror => {
+                let p = i.rem_euclid(2 * n);
+                if p < n { p } else { 2 * n - 1 - p }
+            }
+        }
+    }
+
+    fn texel(&self, img: &TiledImage, x: i32, y: i32) -> Vec4
+    {
+        img.get(self.wrap_coord(x, img.width as i32, false) as usize, self.wrap_coord(y, img.height as i32, true) as usize)
+    }
+
+    // filtered lookup of a single mip level, texel centers at half integers
+    fn sample_level(&self, level: usize, u: f32, v: f32) -> Vec4
+    {
+        let img = &self.levels[level];
+        let x = u * img.width as f32 - 0.5;
+        let y = v * img.height as f32 - 0.5;
+
+        match self.filter
+        {
+            FilterMode::Nearest => {self.texel(img, (x + 0.5).floor() as i32, (y + 0.5).floor() as i32)},
+            FilterMode::Bilinear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i32, y0 as i32);
+
+                (self.texel(img, x0, y0) * (1.0 - fx) + self.texel(img, x0 + 1, y0) * fx) * (1.0 - fy)
+                    + (self.texel(img, x0, y0 + 1) * (1.0 - fx) + self.texel(img, x0 + 1, y0 + 1) * fx) * fy
+            },
+            FilterMode::Bicubic => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (wx, wy) = (catmull_rom(x - x0), catmull_rom(y - y0));
+                let (x0, y0) = (x0 as i32, y0 as i32);
+
+                let mut sum = Vec4::zero();
+                for j in 0..4
+                {
+                    let mut row = Vec4::zero();
+                    for i in 0..4
+                    {
+                        row += self.texel(img, x0 + i as i32 - 1, y0 + j as i32 - 1) * wx[i];
+                    }
+                    sum += row * wy[j];
+                }
+
+                // the negative lobes can ring below zero
+                sum.max(&Vec4::zero())
+            }
+        }
+    }
+}
+
+//######################################################################
+// TiledImage
+//######################################################################
+
+const TILE_SIZE: usize = 8;
+
+impl TiledImage
+{
+    // texels are row major
+    pub fn new(_width: usize, _height: usize, _texels: &[Vec4]) -> TiledImage
+    {
+        let tiles_x = (_width + TILE_SIZE - 1) / TILE_SIZE;
+        let tiles_y = (_height + TILE_SIZE - 1) / TILE_SIZE;
+
+        let mut img = TiledImage{width: _width, height: _height, tiles_x: tiles_x, texels: vec![Vec4::zero(); tiles_x * tiles_y * TILE_SIZE * TILE_SIZE]};
+        for y in 0.._height
+        {
+            for x in 0.._width
+            {
+                let idx = img.index(x, y);
+                img.texels[idx] = _texels[y * _width + x];
+            }
+        }
+
+        img
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize
+    {
+        let tile = (y / TILE_SIZE) * self.tiles_x + x / TILE_SIZE;
+        tile * TILE_SIZE * TILE_SIZE + (y % TILE_SIZE) * TILE_SIZE + x % TILE_SIZE
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Vec4
+    {
+        self.texels[self.index(x, y)]
     }
 }
 
+//######################################################################
+// loading
+//######################################################################
+
+// https://en.wikipedia.org/wiki/SRGB#Transfer_function_(%22gamma%22)
+pub fn srgb_to_linear(c: f32) -> f32
+{
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+// 8 bit rgba to linear, alpha is always stored linearly
+pub fn decode_texel(c: &Vec4, _type: DynamicTextureType) -> Vec4
+{
+    let n = c / 255.0;
+
+    // https://github.com/opencv/opencv/blob/master/modules/imgcodecs/src/rgbe.cpp
+    match _type
+    {
+        DynamicTextureType::Linear => {n},
+        DynamicTextureType::sRGB => {Vec4::new(srgb_to_linear(n.x()), srgb_to_linear(n.y()), srgb_to_linear(n.z()), n.w())},
+        DynamicTextureType::RGBe => {n * (2.0 as f32).powf(c.a() - 128.0)}
+    }
+}
+
+// width, height and linear rgba texels of an image, a black texel if it can't be read
+// float formats are picked by extension and ignore the texture type
+pub fn load_linear(path: &Path, _type: DynamicTextureType) -> (usize, usize, std::vec::Vec<Vec4>)
+{
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+
+    let loaded = match ext.as_str()
+    {
+        "hdr" => {load_hdr(path)},
+        "exr" => {load_exr(path)},
+        "pfm" => {load_pfm(path)},
+        _ => {load_ldr(path, _type)}
+    };
+
+    match loaded
+    {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            (1, 1, vec![Vec4::zero()])
+        }
+    }
+}
+
+fn load_ldr(path: &Path, _type: DynamicTextureType) -> Result<(usize, usize, std::vec::Vec<Vec4>), String>
+{
+    let img = image::open(path).map_err(|e| e.to_string())?;
+
+    let (w, h) = img.dimensions();
+    let texels = img.to_rgba().pixels().map(|p| decode_texel(&Vec4::new(p[0] as f32, p[1] as f32, p[2] as f32, p[3] as f32), _type)).collect();
+
+    Ok((w as usize, h as usize, texels))
+}
+
+// radiance rgbe, the decoder hands out floats
+fn load_hdr(path: &Path) -> Result<(usize, usize, std::vec::Vec<Vec4>), String>
+{
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let decoder = image::hdr::HDRDecoder::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+
+    let meta = decoder.metadata();
+    let pixels = decoder.read_image_hdr().map_err(|e| e.to_string())?;
+
+    Ok((meta.width as usize, meta.height as usize, pixels.iter().map(|p| Vec4::new(p[0], p[1], p[2], 1.0)).collect()))
+}
+
+// first rgba layer, missing channels are filled in by the exr crate
+fn load_exr(path: &Path) -> Result<(usize, usize, std::vec::Vec<Vec4>), String>
+{
+    use exr::prelude::*;
+
+    let image = read_first_rgba_layer_from_file(
+        path,
+        |resolution, _| (resolution.width(), vec![Vec4::zero(); resolution.width() * resolution.height()]),
+        |(width, texels): &mut (usize, std::vec::Vec<Vec4>), pos, (r, g, b, a): (f32, f32, f32, f32)| texels[pos.y() * *width + pos.x()] = Vec4::new(r, g, b, a)
+    ).map_err(|e| e.to_string())?;
+
+    let size = image.layer_data.size;
+    Ok((size.width(), size.height(), image.layer_data.channel_data.pixels.1))
+}
+
+// http://www.pauldebevec.com/Research/HDR/PFM/
+fn load_pfm(path: &Path) -> Result<(usize, usize, std::vec::Vec<Vec4>), String>
+{
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+
+    // magic, width, height and scale separated by whitespace, a single whitespace byte precedes the data
+    let mut pos = 0;
+    let mut tokens = std::vec::Vec::new();
+    while tokens.len() < 4
+    {
+        while pos < data.len() && (data[pos] as char).is_whitespace() { pos += 1; }
+        let start = pos;
+        while pos < data.len() && !(data[pos] as char).is_whitespace() { pos += 1; }
+        if start == pos { return Err("pfm: truncated header".to_string()); }
+        tokens.push(String::from_utf8_lossy(&data[start..pos]).to_string());
+    }
+    let body = &data[(pos + 1).min(data.len())..];
+
+    let channels = match tokens[0].as_str()
+    {
+        "PF" => 3,
+        "Pf" => 1,
+        magic => return Err(format!("pfm: unknown magic {}", magic))
+    };
+    let width: usize = tokens[1].parse().map_err(|_| "pfm: bad width".to_string())?;
+    let height: usize = tokens[2].parse().map_err(|_| "pfm: bad height".to_string())?;
+    let scale: f32 = tokens[3].parse().map_err(|_| "pfm: bad scale".to_string())?;
+
+    // release builds don't check for overflow, a hostile header could wrap the size check
+    let overflow = || "pfm: size overflow".to_string();
+    let texel_count = width.checked_mul(height).ok_or_else(overflow)?;
+    let count = texel_count.checked_mul(channels).ok_or_else(overflow)?;
+    if body.len() < count.checked_mul(4).ok_or_else(overflow)?
+    {
+        return Err("pfm: truncated data".to_string());
+    }
+
+    let little_endian = scale < 0.0;
+    let value = |i: usize| {
+        let b = [body[i * 4], body[i * 4 + 1], body[i * 4 + 2], body[i * 4 + 3]];
+        if little_endian { f32::from_le_bytes(b) } else { f32::from_be_bytes(b) }
+    };
+
+    // rows are stored bottom to top
+    let mut texels = std::vec::Vec::with_capacity(texel_count);
+    for y in (0..height).rev()
+    {
+        for x in 0..width
+        {
+            let i = (y * width + x) * channels;
+            texels.push(if channels == 3 { Vec4::new(value(i), value(i + 1), value(i + 2), 1.0) } else { Vec4::new(value(i), value(i), value(i), 1.0) });
+        }
+    }
+
+    Ok((width, height, texels))
+}
+
+// weights of the 4 texels around a sample at fraction t past the second one
+fn catmull_rom(t: f32) -> [f32; 4]
+{
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2)
+    ]
+}
+
 impl Sample for DynamicTexture
 {
     fn sample(&self, hit: &HitInfo) -> Vec4
     {
-        let (x, y) = self.img.dimensions();
-        let u = (x as f32 * hit.u) as u32;
-        let v = (y as f32 * hit.v) as u32;
-        let data = self.img.get_pixel(u.min(x-1), v.min(y-1));
+        if !self.mipmap || hit.footprint <= 0.0 || self.levels.len() == 1
+        {
+            return self.sample_level(0, hit.u, hit.v);
+        }
 
-        let in_color = Vec4::new(data[0] as f32 , data[1] as f32,  data[2] as f32, data[3] as f32);
+        // level whose texels match the ray cone footprint
+        let lod = (hit.footprint * ((self.width() * self.height()) as f32).sqrt()).log2().max(0.0).min((self.levels.len() - 1) as f32);
 
-        let out_color = match self.load_type
+        let level = lod.floor() as usize;
+        let t = lod - level as f32;
+        if t <= 0.0 || level + 1 >= self.level_count()
         {
-            DynamicTextureType::Linear => {in_color / 255.0},
-            DynamicTextureType::sRGB  => {(in_color / 255.0).pow3(2.2)},
-            DynamicTextureType::RGBe => { (in_color / 255.0) * (2.0 as f32).powf(in_color.a() - 128.0)} 
-        };
+            return self.sample_level(level, hit.u, hit.v);
+        }
+
+        self.sample_level(level, hit.u, hit.v) * (1.0 - t) + self.sample_level(level + 1, hit.u, hit.v) * t
+    }
+}
 
-        // https://github.com/opencv/opencv/blob/master/modules/imgcodecs/src/rgbe.cpp
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn pfm(name: &str, data: &[u8]) -> Result<(usize, usize, std::vec::Vec<Vec4>), String>
+    {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, data).unwrap();
+        let result = load_pfm(&path);
+        std::fs::remove_file(&path).ok();
+        result
+    }
+
+    fn mean(level: &TiledImage) -> f32
+    {
+        let mut sum = 0.0;
+        for y in 0..level.height { for x in 0..level.width { sum += level.get(x, y).x(); } }
+        sum / (level.width * level.height) as f32
+    }
+
+    #[test]
+    fn mips_odd_sizes()
+    {
+        // only the last column is lit, odd widths must not drop it
+        let texels: std::vec::Vec<Vec4> = (0..15).map(|i| Vec4::from(if i % 5 == 4 { 1.0 } else { 0.0 })).collect();
+        let levels = DynamicTexture::build_mips(TiledImage::new(5, 3, &texels));
+        assert_eq!(levels.len(), 3);
+        assert_eq!((levels[1].width, levels[1].height), (2, 1));
+
+        for level in levels.iter()
+        {
+            assert!((mean(level) - 0.2).abs() < 1e-6);
+        }
+        assert!(levels[1].get(1, 0).x() > levels[1].get(0, 0).x());
+    }
+
+    #[test]
+    fn pfm_rgb()
+    {
+        let mut data = b"PF\n2 1\n-1.0\n".to_vec();
+        for v in [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0].iter()
+        {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
 
-        out_color
+        let (width, height, texels) = pfm("strahl_rgb.pfm", &data).unwrap();
+        assert_eq!((width, height), (2, 1));
+        assert!(texels[1] == Vec4::new(4.0, 5.0, 6.0, 1.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn pfm_size_overflow()
+    {
+        // 2^32 * 2^32 * 3 wraps to zero without checks
+        let result = pfm("strahl_overflow.pfm", b"PF\n4294967296 4294967296\n-1.0\n");
+        assert_eq!(result.err(), Some("pfm: size overflow".to_string()));
+
+        let result = pfm("strahl_truncated.pfm", b"Pf\n4 4\n-1.0\n\0\0\0\0");
+        assert_eq!(result.err(), Some("pfm: truncated data".to_string()));
+    }
+}
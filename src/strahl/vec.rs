@@ -46,7 +46,7 @@ impl Vec4 {
 
     pub fn set(&mut self, x: f32, idx: usize)
     {
-        self.v.replace(idx, x);
+        self.v = self.v.replace(idx, x);
     }
 
     pub fn x(&self) -> f32 {
@@ -292,6 +292,29 @@ impl Vec4 {
         // mask out sign bit
         Vec4{v: f32x4::from_bits(u32x4::from_bits(self.v) & u32x4::splat(0x7FFFFFFFu32))}
     }
+
+    pub fn lerp(&self, o: &Vec4, t: f32) -> Vec4
+    {
+        self + (o - self) * t
+    }
+
+    // Rec. 709 relative luminance of the rgb components
+    pub fn luminance(&self) -> f32
+    {
+        0.2126 * self.r() + 0.7152 * self.g() + 0.0722 * self.b()
+    }
+
+    // tangent & bitangent completing a right handed basis with this (normalized) vector
+    // https://graphics.pixar.com/library/OrthonormalB/paper.pdf
+    pub fn orthonormal_basis(&self) -> (Vec4, Vec4)
+    {
+        let (x, y, z) = self.extract_xyz();
+        let sign = 1.0f32.copysign(z);
+        let a = -1.0 / (sign + z);
+        let b = x * y * a;
+
+        (Vec4::from3(1.0 + sign * x * x * a, sign * b, -sign * x), Vec4::from3(b, sign + y * y * a, -y))
+    }
 }
 
 //######################################################################